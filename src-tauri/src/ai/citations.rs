@@ -0,0 +1,146 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::db::embeddings::ScoredChunk;
+use crate::project::markdown;
+
+/// A source reference parsed out of an assistant answer.
+#[derive(Debug, Clone, Serialize)]
+pub struct Citation {
+  pub id: String,
+  pub file_path: Option<String>,
+  pub heading: Option<String>,
+  /// 1-based, inclusive line range of the cited chunk in the current file.
+  pub start_line: Option<usize>,
+  pub end_line: Option<usize>,
+  /// False when the id was not part of the prompt context or the chunk can
+  /// no longer be found in the file on disk.
+  pub verified: bool,
+  pub problem: Option<String>,
+}
+
+/// Stable id for a context chunk, derived from its path and content so the
+/// same chunk gets the same id on every turn.
+pub fn chunk_id(chunk: &ScoredChunk) -> String {
+  // FNV-1a, truncated to 24 bits: short enough for the model to copy reliably.
+  let mut hash: u64 = 0xcbf29ce484222325;
+  for byte in chunk
+    .relative_path
+    .bytes()
+    .chain(std::iter::once(0))
+    .chain(chunk.content.bytes())
+  {
+    hash ^= byte as u64;
+    hash = hash.wrapping_mul(0x100000001b3);
+  }
+  format!("S-{:06x}", hash & 0xffffff)
+}
+
+/// Prompt instructions describing the citation format.
+pub const CITATION_INSTRUCTIONS: &str = "## Citing Sources
+Each project file below is labelled with a source id such as [S-1a2b3c]. When your answer states a rule, number or fact taken from those files, cite the source id in square brackets right after the statement, e.g. \"Flanking grants +2 to attack rolls [S-1a2b3c].\" Only cite ids that appear in the context. Never invent ids.
+
+";
+
+/// Find every `[S-xxxxxx]` reference in `response` and resolve it against the
+/// chunks that were sent to the model.
+pub fn extract_citations(
+  response: &str,
+  chunks: &[ScoredChunk],
+  project_root: Option<&str>,
+) -> Vec<Citation> {
+  let mut citations: Vec<Citation> = Vec::new();
+
+  for id in cited_ids(response) {
+    if citations.iter().any(|c| c.id == id) {
+      continue;
+    }
+
+    let citation = match chunks.iter().find(|c| chunk_id(c) == id) {
+      Some(chunk) => locate_chunk(id, chunk, project_root),
+      None => Citation {
+        id,
+        file_path: None,
+        heading: None,
+        start_line: None,
+        end_line: None,
+        verified: false,
+        problem: Some("Source id was not part of the provided context".to_string()),
+      },
+    };
+    citations.push(citation);
+  }
+
+  citations
+}
+
+fn cited_ids(response: &str) -> Vec<String> {
+  let mut ids = Vec::new();
+  let mut rest = response;
+
+  while let Some(open) = rest.find('[') {
+    let after = &rest[open + 1..];
+    let Some(close) = after.find(']') else {
+      break;
+    };
+    for token in after[..close].split(|c: char| c == ',' || c == ';' || c.is_whitespace()) {
+      if is_chunk_id(token) {
+        ids.push(token.to_lowercase().replacen("s-", "S-", 1));
+      }
+    }
+    rest = &after[close + 1..];
+  }
+
+  ids
+}
+
+fn is_chunk_id(token: &str) -> bool {
+  let bytes = token.as_bytes();
+  bytes.len() == 8
+    && (bytes[0] == b'S' || bytes[0] == b's')
+    && bytes[1] == b'-'
+    && bytes[2..].iter().all(|b| b.is_ascii_hexdigit())
+}
+
+fn locate_chunk(id: String, chunk: &ScoredChunk, project_root: Option<&str>) -> Citation {
+  let mut citation = Citation {
+    id,
+    file_path: Some(chunk.relative_path.clone()),
+    heading: None,
+    start_line: None,
+    end_line: None,
+    verified: false,
+    problem: None,
+  };
+
+  let Some(root) = project_root else {
+    citation.problem = Some("No project is open".to_string());
+    return citation;
+  };
+
+  let Ok(current) = std::fs::read_to_string(Path::new(root).join(&chunk.relative_path)) else {
+    citation.problem = Some("File no longer exists".to_string());
+    return citation;
+  };
+
+  let needle = chunk.content.trim();
+  let Some(offset) = current.find(needle) else {
+    citation.problem = Some("File has changed since it was indexed".to_string());
+    return citation;
+  };
+
+  let start_line = current[..offset].matches('\n').count() + 1;
+  let end_line = start_line + needle.matches('\n').count();
+
+  citation.heading = markdown::parse_headings(&current)
+    .into_iter()
+    .take_while(|h| h.line <= start_line)
+    .last()
+    .or_else(|| markdown::parse_headings(needle).into_iter().next())
+    .map(|h| h.text);
+  citation.start_line = Some(start_line);
+  citation.end_line = Some(end_line);
+  citation.verified = true;
+  citation
+}
@@ -0,0 +1,88 @@
+use super::llm_client::{LlmClient, Message};
+use super::manifest::estimate_tokens;
+
+/// Estimated history size (summary plus verbatim turns) above which older
+/// turns are folded into the summary.
+pub const COMPACTION_THRESHOLD_TOKENS: usize = 12_000;
+
+/// Number of most recent messages that are always sent verbatim.
+pub const KEEP_RECENT_MESSAGES: usize = 6;
+
+pub struct Compaction {
+  pub summary: String,
+  /// How many leading messages of the history are covered by `summary`.
+  pub summarized: usize,
+}
+
+/// Summarize the older part of a conversation once it grows too large.
+///
+/// `previous_summary` covers turns before `history`; the new summary covers
+/// both, so summaries are built incrementally rather than from the full
+/// transcript each time. Returns `None` when the history is under the
+/// threshold or the model call fails (the caller then sends it unchanged).
+pub async fn compact_history(
+  client: &(dyn LlmClient + Send + Sync),
+  previous_summary: Option<&str>,
+  history: &[Message],
+) -> Option<Compaction> {
+  let total = previous_summary.map(estimate_tokens).unwrap_or(0)
+    + history.iter().map(|m| estimate_tokens(&m.content)).sum::<usize>();
+  if total <= COMPACTION_THRESHOLD_TOKENS || history.len() <= KEEP_RECENT_MESSAGES {
+    return None;
+  }
+
+  let summarized = history.len() - KEEP_RECENT_MESSAGES;
+  let prompt = build_summary_prompt(previous_summary, &history[..summarized]);
+
+  match client.chat_completion(&prompt).await {
+    Ok(summary) if !summary.trim().is_empty() => Some(Compaction {
+      summary: summary.trim().to_string(),
+      summarized,
+    }),
+    Ok(_) => {
+      eprintln!("[Compaction] Model returned an empty summary, sending full history");
+      None
+    }
+    Err(err) => {
+      eprintln!("[Compaction] Failed to summarize history, sending full history: {err}");
+      None
+    }
+  }
+}
+
+/// Messages that stand in for the summarized turns at the start of the history.
+pub fn summary_messages(summary: &str) -> Vec<Message> {
+  vec![
+    Message {
+      role: "user".to_string(),
+      content: format!(
+        "[SUMMARY OF EARLIER CONVERSATION]\n{}\n[/SUMMARY OF EARLIER CONVERSATION]",
+        summary
+      ),
+    },
+    Message {
+      role: "assistant".to_string(),
+      content: "Understood. I'll keep that earlier discussion in mind.".to_string(),
+    },
+  ]
+}
+
+fn build_summary_prompt(previous_summary: Option<&str>, messages: &[Message]) -> String {
+  let mut prompt = String::new();
+  prompt.push_str(
+    "You are compacting the history of a long design conversation between a tabletop RPG writer and an AI assistant. \
+     Write a concise summary that preserves every decision made, rules or numbers agreed on, file names discussed, \
+     open questions and the writer's stated preferences. Omit pleasantries. Use short bullet points.\n\n",
+  );
+  if let Some(summary) = previous_summary {
+    prompt.push_str("Summary of the conversation so far:\n");
+    prompt.push_str(summary);
+    prompt.push_str("\n\n");
+  }
+  prompt.push_str("Newer messages to fold into the summary:\n\n");
+  for msg in messages {
+    prompt.push_str(&format!("{}: {}\n\n", msg.role.to_uppercase(), msg.content));
+  }
+  prompt.push_str("Return only the updated summary.");
+  prompt
+}
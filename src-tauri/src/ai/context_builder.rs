@@ -1,5 +1,8 @@
 use crate::db::embeddings::ScoredChunk;
 
+use super::citations;
+use super::manifest::{estimate_tokens, SectionTokens};
+
 pub struct ContextBuilder {
     context_chunks: Vec<ScoredChunk>,
     templates: Vec<String>,
@@ -25,42 +28,80 @@ impl ContextBuilder {
 
   /// Build system context string (templates + file contents) for multi-turn conversations
   pub fn build_system_context(&self) -> String {
+    let mut context_str = self.instructions_section();
+    context_str.push_str(&self.templates_section());
+    context_str.push_str(&self.files_section());
+    context_str
+  }
+
+  /// Estimated prompt tokens for each part of the system context.
+  pub fn estimate_section_tokens(&self) -> SectionTokens {
+    SectionTokens {
+        instructions: estimate_tokens(&self.instructions_section()),
+        templates: estimate_tokens(&self.templates_section()),
+        files: estimate_tokens(&self.files_section()),
+    }
+  }
+
+  fn instructions_section(&self) -> String {
     let mut context_str = String::new();
-    
+
     // File editing instructions
     context_str.push_str(r#"You are an AI assistant for CodexLotus, a TTRPG rulebook editor.
 
 ## File Editing Capability
-When the user asks you to edit, update, modify, or change a file, you MUST output the complete updated file content using this exact format:
+When the user asks you to change part of an existing file, output a patch using this exact format:
+
+<file_patch path="FILENAME.md">
+<<<<<<< SEARCH
+... exact lines currently in the file ...
+=======
+... the lines that replace them ...
+>>>>>>> REPLACE
+</file_patch>
+
+When the user asks for a new file, or for a rewrite of most of a file, output the complete file content instead:
 
 <file_edit path="FILENAME.md">
-... complete updated file content here ...
+... complete file content here ...
 </file_edit>
 
 IMPORTANT RULES FOR FILE EDITS:
 - The path should be the relative path shown in the context (e.g., "chapter-3.md", "rules/combat.md")
-- Include the COMPLETE file content, not just the changed parts
-- Do NOT wrap the content in markdown code fences inside the file_edit tags
-- After the file_edit block, briefly explain what you changed
+- Prefer <file_patch> for existing files; it is faster and never loses content you did not mean to change
+- Each SEARCH section must copy the current lines exactly and match only one place in the file; include a few unchanged lines around the change if needed
+- A <file_patch> may contain several SEARCH/REPLACE blocks; they are applied in order
+- A <file_edit> must include the COMPLETE file content, not just the changed parts
+- Do NOT wrap the content in markdown code fences inside the tags
+- After the edit blocks, briefly explain what you changed
 
 Example:
 User: "Add a section about flanking to combat.md"
-Assistant: <file_edit path="combat.md">
-# Combat Rules
-
+Assistant: <file_patch path="combat.md">
+<<<<<<< SEARCH
+## Basic Combat
+Roll a d20 and add your attack bonus.
+=======
 ## Basic Combat
-[existing content...]
+Roll a d20 and add your attack bonus.
 
 ## Flanking
 When two allies are on opposite sides of an enemy, they gain +2 to attack rolls against that enemy.
-</file_edit>
+>>>>>>> REPLACE
+</file_patch>
 
 I've added a new "Flanking" section that grants +2 to attack rolls when allies are positioned on opposite sides of an enemy.
 
 ---
 
 "#);
-    
+
+    context_str
+  }
+
+  fn templates_section(&self) -> String {
+    let mut context_str = String::new();
+
     if !self.templates.is_empty() {
         context_str.push_str("Available Table Templates (Schemas):\n");
         for tmpl in &self.templates {
@@ -70,12 +111,17 @@ I've added a new "Flanking" section that grants +2 to attack rolls when allies a
         context_str.push_str("If the user asks to generate an item/statblock using a specific template, output valid YAML inside a ```codex block that adheres to the schema. Set the 'template' field to the template ID.\n\n");
     }
 
+    context_str
+  }
+
+  fn files_section(&self) -> String {
+    let mut context_str = String::new();
+
     if !self.context_chunks.is_empty() {
+        context_str.push_str(citations::CITATION_INSTRUCTIONS);
         context_str.push_str("Project files for reference:\n\n");
         for chunk in &self.context_chunks {
-            context_str.push_str(&format!("--- BEGIN FILE: {} ---\n", chunk.relative_path));
-            context_str.push_str(&chunk.content);
-            context_str.push_str(&format!("\n--- END FILE: {} ---\n\n", chunk.relative_path));
+            push_labelled_chunk(&mut context_str, chunk);
         }
     }
 
@@ -95,11 +141,10 @@ I've added a new "Flanking" section that grants +2 to attack rolls when allies a
     }
 
     if !self.context_chunks.is_empty() {
+        context_str.push_str(citations::CITATION_INSTRUCTIONS);
         context_str.push_str("Use the following project context to answer the user's question. References to 'file' or 'chapter' usually refer to these snippets:\n\n");
         for chunk in &self.context_chunks {
-            context_str.push_str(&format!("--- BEGIN FILE: {} ---\n", chunk.relative_path));
-            context_str.push_str(&chunk.content);
-            context_str.push_str(&format!("\n--- END FILE: {} ---\n\n", chunk.relative_path));
+            push_labelled_chunk(&mut context_str, chunk);
         }
         context_str.push_str("---\n\n");
     }
@@ -107,3 +152,10 @@ I've added a new "Flanking" section that grants +2 to attack rolls when allies a
     format!("{}{}", context_str, user_prompt)
   }
 }
+
+fn push_labelled_chunk(context_str: &mut String, chunk: &ScoredChunk) {
+    let id = citations::chunk_id(chunk);
+    context_str.push_str(&format!("--- BEGIN FILE: {} [{}] ---\n", chunk.relative_path, id));
+    context_str.push_str(&chunk.content);
+    context_str.push_str(&format!("\n--- END FILE: {} [{}] ---\n\n", chunk.relative_path, id));
+}
@@ -0,0 +1,231 @@
+use serde::Serialize;
+use similar::TextDiff;
+
+use crate::project::patch::{self, HunkResult};
+use crate::project::paths::{resolve_project_path, validate_relative_path, Access};
+
+/// Tags understood by the edit protocol: `file_edit` carries the complete new
+/// file, `file_patch` carries search/replace hunks or a unified diff.
+const TAGS: [EditKind; 2] = [EditKind::FullFile, EditKind::Patch];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditKind {
+  FullFile,
+  Patch,
+}
+
+impl EditKind {
+  fn tag(self) -> &'static str {
+    match self {
+      EditKind::FullFile => "file_edit",
+      EditKind::Patch => "file_patch",
+    }
+  }
+
+  fn open_tag(self) -> String {
+    format!("<{}", self.tag())
+  }
+
+  fn close_tag(self) -> String {
+    format!("</{}>", self.tag())
+  }
+}
+
+/// A file edit proposed by the model through the `<file_edit>` / `<file_patch>`
+/// protocol described in `ContextBuilder::build_system_context`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProposedEdit {
+  pub kind: EditKind,
+  /// Path exactly as written by the model (relative to the project root).
+  pub path: String,
+  pub absolute_path: Option<String>,
+  pub exists: bool,
+  pub original_contents: Option<String>,
+  /// Contents after the edit. For a patch that failed to apply this is the
+  /// unchanged original, never a partially patched file.
+  pub new_contents: String,
+  /// Unified diff from the current file (or nothing) to `new_contents`.
+  pub diff: String,
+  /// Per-hunk results for `file_patch` edits; empty for whole-file edits.
+  pub hunks: Vec<HunkResult>,
+  /// Problems that make this edit unsafe to apply. Empty for a valid edit.
+  pub errors: Vec<String>,
+}
+
+/// Parse every `<file_edit path="...">` and `<file_patch path="...">` block
+/// in a response, in the order they appear.
+///
+/// Malformed blocks (missing path, unterminated tag, missing close tag) are
+/// still returned, with `errors` describing the problem, so the UI can show
+/// them instead of silently dropping part of the answer.
+pub fn parse_file_edits(response: &str, project_root: Option<&str>) -> Vec<ProposedEdit> {
+  let mut edits = Vec::new();
+  let mut rest = response;
+
+  while let Some((open, kind)) = find_next_open(rest) {
+    let open_tag = kind.open_tag();
+    let close_tag = kind.close_tag();
+    let after_open = &rest[open + open_tag.len()..];
+    let mut errors = Vec::new();
+
+    let Some(tag_end) = after_open.find('>') else {
+      edits.push(invalid_edit(
+        kind,
+        String::new(),
+        format!("Unterminated <{}> tag: missing '>'", kind.tag()),
+      ));
+      break;
+    };
+
+    let attributes = &after_open[..tag_end];
+    let path = match parse_path_attribute(attributes) {
+      Some(path) if !path.trim().is_empty() => path.trim().to_string(),
+      _ => {
+        errors.push(format!("<{}> tag is missing a path attribute", kind.tag()));
+        String::new()
+      }
+    };
+
+    let body = &after_open[tag_end + 1..];
+    // A new opening tag before the close tag means this block was never closed.
+    let next_open = find_next_open(body).map(|(idx, _)| idx);
+    let (content, consumed) = match body.find(&close_tag) {
+      Some(close) if next_open.map(|n| close < n).unwrap_or(true) => {
+        (&body[..close], close + close_tag.len())
+      }
+      _ => {
+        errors.push(format!(
+          "Missing closing </{}> tag; the content may be truncated",
+          kind.tag()
+        ));
+        let end = next_open.unwrap_or(body.len());
+        (&body[..end], end)
+      }
+    };
+
+    edits.push(build_edit(kind, path, strip_block_newlines(content), errors, project_root));
+    rest = &body[consumed..];
+  }
+
+  edits
+}
+
+fn find_next_open(text: &str) -> Option<(usize, EditKind)> {
+  TAGS
+    .iter()
+    .filter_map(|kind| text.find(&kind.open_tag()).map(|idx| (idx, *kind)))
+    .min_by_key(|(idx, _)| *idx)
+}
+
+fn build_edit(
+  kind: EditKind,
+  path: String,
+  body: &str,
+  mut errors: Vec<String>,
+  project_root: Option<&str>,
+) -> ProposedEdit {
+  let mut edit = ProposedEdit {
+    kind,
+    path,
+    absolute_path: None,
+    exists: false,
+    original_contents: None,
+    new_contents: String::new(),
+    diff: String::new(),
+    hunks: Vec::new(),
+    errors: Vec::new(),
+  };
+
+  if !edit.path.is_empty() {
+    if let Some(problem) = validate_relative_path(&edit.path) {
+      errors.push(problem);
+    } else if let Some(root) = project_root {
+      match resolve_project_path(root, &edit.path, Access::Write) {
+        Ok(absolute) => {
+          edit.exists = absolute.is_file();
+          if edit.exists {
+            match std::fs::read_to_string(&absolute) {
+              Ok(contents) => edit.original_contents = Some(contents),
+              Err(err) => errors.push(format!("Could not read current file: {err}")),
+            }
+          }
+          edit.absolute_path = Some(absolute.to_string_lossy().to_string());
+        }
+        Err(err) => errors.push(err.to_string()),
+      }
+    } else {
+      errors.push("No project is open to apply this edit to".to_string());
+    }
+  }
+
+  let original = edit.original_contents.as_deref().unwrap_or("");
+  edit.new_contents = match kind {
+    EditKind::FullFile => body.to_string(),
+    EditKind::Patch => match patch::apply_patch(original, body) {
+      Ok(outcome) => {
+        for failed in outcome.failed_hunks() {
+          errors.push(format!(
+            "Patch hunk {} did not apply: {}",
+            failed.index + 1,
+            failed.error.as_deref().unwrap_or("unknown error")
+          ));
+        }
+        edit.hunks = outcome.hunks;
+        outcome.contents.unwrap_or_else(|| original.to_string())
+      }
+      Err(err) => {
+        errors.push(err);
+        original.to_string()
+      }
+    },
+  };
+
+  edit.diff = unified_diff(&edit.path, original, &edit.new_contents, edit.exists);
+  edit.errors = errors;
+  edit
+}
+
+fn invalid_edit(kind: EditKind, path: String, error: String) -> ProposedEdit {
+  ProposedEdit {
+    kind,
+    path,
+    absolute_path: None,
+    exists: false,
+    original_contents: None,
+    new_contents: String::new(),
+    diff: String::new(),
+    hunks: Vec::new(),
+    errors: vec![error],
+  }
+}
+
+/// Unified diff with `a/` and `b/` headers; a new file diffs from /dev/null.
+pub fn unified_diff(path: &str, original: &str, updated: &str, exists: bool) -> String {
+  let old_header = if exists { format!("a/{path}") } else { "/dev/null".to_string() };
+  let new_header = format!("b/{path}");
+  TextDiff::from_lines(original, updated)
+    .unified_diff()
+    .context_radius(3)
+    .header(&old_header, &new_header)
+    .to_string()
+}
+
+fn parse_path_attribute(attributes: &str) -> Option<String> {
+  let idx = attributes.find("path")?;
+  let after = attributes[idx + "path".len()..].trim_start();
+  let after = after.strip_prefix('=')?.trim_start();
+  let quote = after.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+  let value = &after[1..];
+  let end = value.find(quote)?;
+  Some(value[..end].to_string())
+}
+
+/// Drop the newline that follows the opening tag. The newline before the
+/// closing tag is kept, since it is the file's own trailing newline.
+fn strip_block_newlines(content: &str) -> &str {
+  content
+    .strip_prefix("\r\n")
+    .or_else(|| content.strip_prefix('\n'))
+    .unwrap_or(content)
+}
@@ -0,0 +1,64 @@
+use serde::Serialize;
+
+/// Describes exactly what went into a chat prompt, so a wrong answer can be
+/// traced to retrieval or to the model.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct ContextManifest {
+  pub provider: String,
+  pub model: String,
+  /// True when the retrieved chunks were reordered by the LLM reranker.
+  pub reranked: bool,
+  pub chunks: Vec<ManifestChunk>,
+  pub mentioned_file: Option<MentionedFileStatus>,
+  /// File names of the templates included from `.codex/templates`.
+  pub templates: Vec<String>,
+  /// Older history messages replaced by a summary for this request.
+  pub compacted_messages: usize,
+  pub tokens: TokenEstimates,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestChunk {
+  pub id: String,
+  pub relative_path: String,
+  pub score: f32,
+  /// `"rag"` for vector-search hits, `"mentioned_file"` for a file loaded
+  /// because the prompt named it.
+  pub source: String,
+  pub estimated_tokens: usize,
+}
+
+/// Outcome of looking for a file named in the user prompt.
+#[derive(Debug, Clone, Serialize)]
+pub struct MentionedFileStatus {
+  pub name: String,
+  pub loaded: bool,
+  /// The file was not loaded again because retrieval already returned it.
+  pub already_in_context: bool,
+  pub problem: Option<String>,
+}
+
+/// Rough token counts per prompt section (about four characters per token).
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct TokenEstimates {
+  pub instructions: usize,
+  pub templates: usize,
+  pub files: usize,
+  /// Summary of compacted history turns.
+  pub summary: usize,
+  pub history: usize,
+  pub prompt: usize,
+  pub total: usize,
+}
+
+/// Token estimates for the sections produced by `ContextBuilder`.
+pub struct SectionTokens {
+  pub instructions: usize,
+  pub templates: usize,
+  pub files: usize,
+}
+
+/// Cheap provider-independent token estimate.
+pub fn estimate_tokens(text: &str) -> usize {
+  text.chars().count().div_ceil(4)
+}
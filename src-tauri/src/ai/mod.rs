@@ -2,3 +2,8 @@ pub mod llm_client;
 pub mod openai_client;
 pub mod gemini_client;
 pub mod context_builder;
+pub mod reranker;
+pub mod citations;
+pub mod manifest;
+pub mod compaction;
+pub mod file_edits;
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::db::embeddings::{EmbeddingDb, RerankScore, ScoredChunk};
+
+use super::llm_client::LlmClient;
+
+/// Number of vector-search candidates handed to the reranker.
+pub const RERANK_CANDIDATES: usize = 30;
+
+/// Excerpts are truncated to this many characters in the rerank prompt so a
+/// batch of 30 stays well inside the model context.
+const MAX_EXCERPT_CHARS: usize = 1500;
+
+#[derive(Deserialize)]
+struct RawScore {
+  id: usize,
+  score: f32,
+}
+
+/// Second-stage reranking of vector-search candidates.
+///
+/// All candidates are scored by the chat model in a single batched call and
+/// the best `top_k` are returned, with `score` replaced by the model's
+/// relevance (0.0 - 1.0). Scores are cached per project, query and model in
+/// the embeddings database. If the model call or its output fails, the
+/// original vector order is kept. The flag tells whether model scores were
+/// actually applied.
+pub async fn rerank(
+  client: &(dyn LlmClient + Send + Sync),
+  project_root: &str,
+  model: &str,
+  query: &str,
+  candidates: Vec<ScoredChunk>,
+  top_k: usize,
+) -> (Vec<ScoredChunk>, bool) {
+  if candidates.len() <= 1 {
+    return (candidates, false);
+  }
+
+  let cache_key = normalize_query(query);
+  let db = EmbeddingDb::open_for_project(project_root).ok();
+
+  let cached = db
+    .as_ref()
+    .and_then(|db| db.get_rerank_scores(project_root, &cache_key, model).ok().flatten())
+    .filter(|scores| {
+      candidates
+        .iter()
+        .all(|c| scores.iter().any(|s| s.relative_path == c.relative_path))
+    });
+
+  let scores = match cached {
+    Some(scores) => scores,
+    None => match score_candidates(client, query, &candidates).await {
+      Ok(scores) => {
+        if let Some(db) = &db {
+          if let Err(err) = db.store_rerank_scores(project_root, &cache_key, model, &scores) {
+            eprintln!("[Rerank] Failed to cache scores: {err}");
+          }
+        }
+        scores
+      }
+      Err(err) => {
+        eprintln!("[Rerank] Falling back to vector order: {err}");
+        let mut candidates = candidates;
+        candidates.truncate(top_k);
+        return (candidates, false);
+      }
+    },
+  };
+
+  let by_path: HashMap<&str, f32> = scores
+    .iter()
+    .map(|s| (s.relative_path.as_str(), s.score))
+    .collect();
+
+  let mut reranked: Vec<ScoredChunk> = candidates
+    .into_iter()
+    .map(|mut chunk| {
+      chunk.score = by_path.get(chunk.relative_path.as_str()).copied().unwrap_or(0.0);
+      chunk
+    })
+    .collect();
+
+  // Stable sort keeps the vector order among equally scored chunks.
+  reranked.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+  reranked.truncate(top_k);
+  (reranked, true)
+}
+
+async fn score_candidates(
+  client: &(dyn LlmClient + Send + Sync),
+  query: &str,
+  candidates: &[ScoredChunk],
+) -> anyhow::Result<Vec<RerankScore>> {
+  let mut prompt = String::new();
+  prompt.push_str("You are ranking excerpts from a tabletop RPG rulebook by how useful they are for answering a question.\n\n");
+  prompt.push_str(&format!("Question: {}\n\n", query));
+  prompt.push_str("Excerpts:\n\n");
+  for (id, chunk) in candidates.iter().enumerate() {
+    prompt.push_str(&format!("[{}] {}\n", id, chunk.relative_path));
+    prompt.push_str(truncate_chars(&chunk.content, MAX_EXCERPT_CHARS));
+    prompt.push_str("\n\n");
+  }
+  prompt.push_str(
+    "Score every excerpt from 0 (irrelevant) to 10 (directly answers the question). \
+     Respond with ONLY a JSON array like [{\"id\": 0, \"score\": 7}] and no other text.",
+  );
+
+  let response = client.chat_completion(&prompt).await?;
+  let raw = parse_scores(&response)?;
+
+  let mut scores: Vec<RerankScore> = candidates
+    .iter()
+    .map(|c| RerankScore {
+      relative_path: c.relative_path.clone(),
+      score: 0.0,
+    })
+    .collect();
+  for entry in raw {
+    if let Some(score) = scores.get_mut(entry.id) {
+      score.score = (entry.score / 10.0).clamp(0.0, 1.0);
+    }
+  }
+
+  Ok(scores)
+}
+
+fn parse_scores(response: &str) -> anyhow::Result<Vec<RawScore>> {
+  let start = response.find('[');
+  let end = response.rfind(']');
+  match (start, end) {
+    (Some(start), Some(end)) if start < end => Ok(serde_json::from_str(&response[start..=end])?),
+    _ => anyhow::bail!("reranker response did not contain a JSON array"),
+  }
+}
+
+fn normalize_query(query: &str) -> String {
+  query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn truncate_chars(s: &str, max: usize) -> &str {
+  match s.char_indices().nth(max) {
+    Some((idx, _)) => &s[..idx],
+    None => s,
+  }
+}
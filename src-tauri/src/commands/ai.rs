@@ -1,11 +1,19 @@
 use serde::{Deserialize, Serialize};
 
 use crate::ai::{
-    context_builder::ContextBuilder, gemini_client::GeminiClient, llm_client::LlmClient,
-    openai_client::OpenAiClient,
+    citations::{self, Citation},
+    compaction,
+    context_builder::ContextBuilder,
+    file_edits::{self, ProposedEdit}, gemini_client::GeminiClient, llm_client::LlmClient,
+    manifest::{estimate_tokens, ContextManifest, ManifestChunk, MentionedFileStatus, TokenEstimates},
+    openai_client::OpenAiClient, reranker,
 };
 use crate::commands::settings::{get_api_key_sync, AppSettings};
+use crate::db::chat_sessions::{ChatSessionDb, SessionSummary};
 use crate::db::embeddings::{EmbeddingDb, ScoredChunk};
+use crate::project::markdown;
+use crate::project::patch::{self, HunkResult};
+use crate::project::paths::{resolve_project_path, Access};
 use crate::util::error::Error;
 
 /// Extract a mentioned file name from the user prompt.
@@ -53,6 +61,9 @@ pub struct ChatRequest {
     pub project_root: Option<String>,
     /// Full conversation history for multi-turn chat
     pub conversation: Option<Vec<ChatMessage>>,
+    /// Persisted chat session. When set, history is loaded from the project
+    /// database instead of `conversation`, and the new turn is stored.
+    pub session_id: Option<i64>,
     // Optional: allow overriding model for this request
     pub provider: Option<String>,
     pub model: Option<String>,
@@ -61,6 +72,12 @@ pub struct ChatRequest {
 #[derive(Serialize)]
 pub struct ChatResponse {
     pub content: String,
+    /// Sources cited in `content`, resolved against the context chunks.
+    pub citations: Vec<Citation>,
+    /// What was sent to the model. `None` if the request never reached it.
+    pub manifest: Option<ContextManifest>,
+    /// `<file_edit>` / `<file_patch>` blocks from `content`, parsed and diffed against disk.
+    pub edits: Vec<ProposedEdit>,
 }
 
 async fn get_client(
@@ -90,6 +107,9 @@ pub async fn ai_chat_completion(
             Err(_) => {
                 return Ok(ChatResponse {
                     content: "API key is not configured. Please enter your API key in the Settings tab.".to_string(),
+                    citations: Vec::new(),
+                    manifest: None,
+                    edits: Vec::new(),
                 });
             }
         },
@@ -109,6 +129,7 @@ pub async fn ai_chat_completion(
             foreground_panel_color: None,
             statblock_bg_color: None,
             statblock_font_color: None,
+            rerank_enabled: false,
         });
 
     // For RAG embedding, we currently only support OpenAI's embedding model
@@ -122,44 +143,125 @@ pub async fn ai_chat_completion(
 
     let client = get_client(&settings, api_key.clone()).await;
 
+    // For stored sessions, only messages after the stored summary are loaded.
+    // `stored_ids` lines up with `conversation` so a new summary can record
+    // which message it covers.
+    let mut stored_summary: Option<SessionSummary> = None;
+    let mut stored_ids: Vec<i64> = Vec::new();
+    let conversation: Vec<ChatMessage> = match (req.session_id, &req.project_root) {
+        (Some(session_id), Some(root)) => {
+            let db = ChatSessionDb::open_for_project(root)?;
+            db.get_session(session_id)?;
+            stored_summary = db.get_summary(session_id)?;
+            let covered = stored_summary
+                .as_ref()
+                .map(|s| s.covers_through_message_id)
+                .unwrap_or(0);
+            db.get_messages(session_id)?
+                .into_iter()
+                .filter(|m| m.id > covered && m.role != "system")
+                .map(|m| {
+                    stored_ids.push(m.id);
+                    ChatMessage { role: m.role, content: m.content }
+                })
+                .collect()
+        }
+        (Some(_), None) => {
+            return Err(Error::Anyhow(anyhow::Error::msg(
+                "A project must be open to use a stored chat session",
+            )));
+        }
+        (None, _) => req.conversation.clone().unwrap_or_default(),
+    };
+
     // 1. Embed User Query and search for relevant context
     let mut context_chunks = Vec::new();
+    let mut manifest = ContextManifest {
+        provider: settings.provider.clone(),
+        model: settings.chat_model.clone(),
+        ..Default::default()
+    };
 
     if let Some(root) = &req.project_root {
         // Try to get embeddings and search - works for both OpenAI and Gemini
         if let Ok(query_vecs) = client.embed(&[req.prompt.clone()]).await {
             if let Some(query_vec) = query_vecs.first() {
                 // Search DB for similar chunks
+                let limit = if settings.rerank_enabled { reranker::RERANK_CANDIDATES } else { 5 };
                 if let Ok(db) = EmbeddingDb::open_for_project(root) {
-                    if let Ok(hits) = db.query_similar_chunks(root, query_vec, 5) {
+                    if let Ok(hits) = db.query_similar_chunks(root, query_vec, limit) {
                         context_chunks = hits;
                     }
                 }
+
+                if settings.rerank_enabled && !context_chunks.is_empty() {
+                    let (reranked, applied) = reranker::rerank(
+                        client.as_ref(),
+                        root,
+                        &settings.chat_model,
+                        &req.prompt,
+                        context_chunks,
+                        5,
+                    )
+                    .await;
+                    context_chunks = reranked;
+                    manifest.reranked = applied;
+                }
             }
         }
 
         // Additionally, detect if user mentions a specific file and load it directly
         let mentioned_file = extract_mentioned_file(&req.prompt);
         if let Some(file_name) = mentioned_file {
-            let file_path = std::path::Path::new(root).join(&file_name);
-            if file_path.exists() {
-                if let Ok(content) = std::fs::read_to_string(&file_path) {
-                    // Check if this file is already in context_chunks
-                    let already_included = context_chunks.iter().any(|c| {
-                        c.relative_path.to_lowercase() == file_name.to_lowercase()
-                    });
-                    if !already_included {
-                        context_chunks.insert(0, ScoredChunk {
-                            relative_path: file_name,
-                            content,
-                            score: 1.0, // Exact match gets highest score
+            let mut status = MentionedFileStatus {
+                name: file_name.clone(),
+                loaded: false,
+                already_in_context: false,
+                problem: None,
+            };
+            match resolve_project_path(root, &file_name, Access::Read) {
+                Ok(file_path) if file_path.exists() => match std::fs::read_to_string(&file_path) {
+                    Ok(content) => {
+                        // Check if this file is already in context_chunks
+                        let already_included = context_chunks.iter().any(|c| {
+                            c.relative_path.to_lowercase() == file_name.to_lowercase()
                         });
+                        if already_included {
+                            status.already_in_context = true;
+                        } else {
+                            context_chunks.insert(0, ScoredChunk {
+                                relative_path: file_name,
+                                content,
+                                score: 1.0, // Exact match gets highest score
+                            });
+                            status.loaded = true;
+                        }
                     }
-                }
+                    Err(err) => status.problem = Some(format!("Could not read file: {err}")),
+                },
+                Ok(_) => status.problem = Some("File not found in project".to_string()),
+                Err(err) => status.problem = Some(err.to_string()),
             }
+            manifest.mentioned_file = Some(status);
         }
     }
 
+    manifest.chunks = context_chunks
+        .iter()
+        .enumerate()
+        .map(|(idx, chunk)| {
+            let is_mentioned = idx == 0
+                && manifest.mentioned_file.as_ref().map(|m| m.loaded).unwrap_or(false);
+            ManifestChunk {
+                id: citations::chunk_id(chunk),
+                relative_path: chunk.relative_path.clone(),
+                score: chunk.score,
+                source: if is_mentioned { "mentioned_file" } else { "rag" }.to_string(),
+                estimated_tokens: estimate_tokens(&chunk.content),
+            }
+        })
+        .collect();
+
     // 3. Load Templates (if project root exists)
     let mut templates = Vec::new();
     if let Some(root) = &req.project_root {
@@ -169,6 +271,9 @@ pub async fn ai_chat_completion(
                 for entry in entries.flatten() {
                     if let Ok(content) = std::fs::read_to_string(entry.path()) {
                         templates.push(content);
+                        manifest
+                            .templates
+                            .push(entry.file_name().to_string_lossy().to_string());
                     }
                 }
             }
@@ -177,11 +282,12 @@ pub async fn ai_chat_completion(
 
     // 4. Build Prompt with Context
     let context_builder = ContextBuilder::new()
-        .with_context(context_chunks)
+        .with_context(context_chunks.clone())
         .with_templates(templates);
     
     let system_context = context_builder.build_system_context();
     let _user_prompt_with_context = context_builder.build_prompt(&req.prompt);
+    let section_tokens = context_builder.estimate_section_tokens();
 
     // 5. Build messages array with conversation history
     let mut messages: Vec<crate::ai::llm_client::Message> = Vec::new();
@@ -199,22 +305,47 @@ pub async fn ai_chat_completion(
     }
 
     // Add conversation history if provided
-    if let Some(history) = &req.conversation {
-        for msg in history {
-            // Skip adding the last user message since we'll add it with context
-            if msg.role != "system" {
-                messages.push(crate::ai::llm_client::Message {
-                    role: msg.role.clone(),
-                    content: msg.content.clone(),
-                });
+    let mut history: Vec<crate::ai::llm_client::Message> = conversation
+        .iter()
+        .filter(|msg| msg.role != "system")
+        .map(|msg| crate::ai::llm_client::Message {
+            role: msg.role.clone(),
+            content: msg.content.clone(),
+        })
+        .collect();
+
+    // Fold older turns into a summary once the history gets too long
+    let mut history_summary = stored_summary.map(|s| s.summary);
+    if let Some(compacted) =
+        compaction::compact_history(client.as_ref(), history_summary.as_deref(), &history).await
+    {
+        if let (Some(session_id), Some(root)) = (req.session_id, &req.project_root) {
+            if let Some(&covers_through_message_id) = stored_ids.get(compacted.summarized - 1) {
+                let db = ChatSessionDb::open_for_project(root)?;
+                db.store_summary(
+                    session_id,
+                    &SessionSummary {
+                        summary: compacted.summary.clone(),
+                        covers_through_message_id,
+                    },
+                )?;
             }
         }
+        history.drain(..compacted.summarized);
+        history_summary = Some(compacted.summary);
+        manifest.compacted_messages = compacted.summarized;
     }
-    
+
+    if let Some(summary) = &history_summary {
+        messages.extend(compaction::summary_messages(summary));
+    }
+    messages.extend(history.iter().cloned());
+
     // Add the current user message (already last in conversation, but ensure it's there)
     // Only if not already added via conversation history
-    let already_has_current = req.conversation.as_ref()
-        .map(|c| c.last().map(|m| m.content == req.prompt && m.role == "user").unwrap_or(false))
+    let already_has_current = history
+        .last()
+        .map(|m| m.content == req.prompt && m.role == "user")
         .unwrap_or(false);
     
     if !already_has_current {
@@ -224,25 +355,132 @@ pub async fn ai_chat_completion(
         });
     }
 
+    let prompt_tokens = estimate_tokens(&req.prompt);
+    let summary_tokens = history_summary.as_deref().map(estimate_tokens).unwrap_or(0);
+    let history_tokens = history
+        .iter()
+        .map(|m| estimate_tokens(&m.content))
+        .sum::<usize>()
+        .saturating_sub(if already_has_current { prompt_tokens } else { 0 });
+    manifest.tokens = TokenEstimates {
+        instructions: section_tokens.instructions,
+        templates: section_tokens.templates,
+        files: section_tokens.files,
+        summary: summary_tokens,
+        history: history_tokens,
+        prompt: prompt_tokens,
+        total: section_tokens.instructions
+            + section_tokens.templates
+            + section_tokens.files
+            + summary_tokens
+            + history_tokens
+            + prompt_tokens,
+    };
+
     // 6. Send to LLM with full conversation
     let content = match client.chat_completion_with_history(&messages).await {
-        Ok(c) => c,
+        Ok(c) => {
+            // Only successful turns are stored, so a retry doesn't duplicate the prompt.
+            if let (Some(session_id), Some(root)) = (req.session_id, &req.project_root) {
+                let mut db = ChatSessionDb::open_for_project(root)?;
+                let mut turn = Vec::new();
+                if !already_has_current {
+                    turn.push(("user", req.prompt.as_str()));
+                }
+                turn.push(("assistant", c.as_str()));
+                db.append_messages(session_id, &turn)?;
+            }
+            c
+        }
         Err(err) => format!("AI error: {err}"),
     };
 
-    Ok(ChatResponse { content })
+    let citations =
+        citations::extract_citations(&content, &context_chunks, req.project_root.as_deref());
+
+    let edits = file_edits::parse_file_edits(&content, req.project_root.as_deref());
+
+    Ok(ChatResponse {
+        content,
+        citations,
+        manifest: Some(manifest),
+        edits,
+    })
 }
 
 #[derive(Deserialize)]
 pub struct FileEditRequest {
+    /// Relative path of the file. `combat.md#Flanking` targets one section.
     pub path: String,
     pub contents: String,
     pub instruction: String,
+    /// `"patch"` asks the model for search/replace hunks instead of the whole
+    /// file. Defaults to a whole-file rewrite.
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// Heading of the section to edit; an alternative to `path#Heading`.
+    #[serde(default)]
+    pub section: Option<String>,
 }
 
 #[derive(Serialize)]
 pub struct FileEditResponse {
     pub updated_contents: String,
+    /// The contents the model edited. Pass it to `merge_file_edit` as the
+    /// base if the file may have changed while the request ran.
+    pub base_contents: String,
+    /// Per-hunk results in patch mode. If any hunk failed, `updated_contents`
+    /// is the unchanged input.
+    pub hunks: Vec<HunkResult>,
+    pub error: Option<String>,
+}
+
+impl FileEditResponse {
+    /// A response that leaves the file untouched and reports why.
+    fn unchanged(req: &FileEditRequest, error: String) -> Self {
+        Self {
+            updated_contents: req.contents.clone(),
+            base_contents: req.contents.clone(),
+            hunks: Vec::new(),
+            error: Some(error),
+        }
+    }
+}
+
+/// Lines of text around a section that are sent as read-only context.
+const SECTION_CONTEXT_LINES: usize = 12;
+
+/// The byte range of the file an edit request may change.
+struct EditTarget {
+    start: usize,
+    end: usize,
+    heading: Option<String>,
+}
+
+impl EditTarget {
+    fn text<'a>(&self, contents: &'a str) -> &'a str {
+        &contents[self.start..self.end]
+    }
+
+    /// Replace the target range, leaving the rest of the file byte-identical.
+    fn splice(&self, contents: &str, replacement: &str) -> String {
+        let mut updated = String::with_capacity(contents.len() + replacement.len());
+        updated.push_str(&contents[..self.start]);
+        updated.push_str(replacement);
+        updated.push_str(&contents[self.end..]);
+        updated
+    }
+}
+
+/// Split `combat.md#Flanking` into the file path and section heading.
+fn split_section_path<'a>(path: &'a str, section: Option<&'a str>) -> (&'a str, Option<&'a str>) {
+    if let Some(section) = section.filter(|s| !s.trim().is_empty()) {
+        return (path, Some(section));
+    }
+    match path.rsplit_once('#') {
+        Some((file, heading)) if !heading.trim().is_empty() => (file, Some(heading)),
+        _ => (path, None),
+    }
 }
 
 #[tauri::command]
@@ -262,6 +500,30 @@ pub async fn ai_file_edit(
         .unwrap_or_default();
     let client = get_client(&settings, api_key).await;
 
+    let (path, section) = split_section_path(&req.path, req.section.as_deref());
+    let target = match section {
+        Some(name) => match markdown::find_section(&req.contents, name) {
+            Ok(found) => EditTarget {
+                start: found.start,
+                end: found.end,
+                heading: Some(found.heading.text),
+            },
+            Err(err) => return Ok(FileEditResponse::unchanged(&req, err)),
+        },
+        None => EditTarget {
+            start: 0,
+            end: req.contents.len(),
+            heading: None,
+        },
+    };
+
+    if req.mode.as_deref() == Some("patch") {
+        return patch_file_edit(client.as_ref(), &req, path, &target).await;
+    }
+    if target.heading.is_some() {
+        return section_file_edit(client.as_ref(), &req, path, &target).await;
+    }
+
     let prompt = format!(
         "You are an expert editor.
 User Instruction: {}
@@ -276,16 +538,155 @@ Return ONLY the full updated file content. Do not add markdown code fences aroun
     );
 
     let updated_contents = match client.chat_completion(&prompt).await {
-        Ok(c) => {
-            // Naive cleanup if the LLM wraps it in ```markdown ... ```
-            c.trim()
-                .trim_start_matches("```markdown")
-                .trim_start_matches("```")
-                .trim_end_matches("```")
-                .to_string()
-        }
-        Err(err) => format!("Error generating edit: {}", err),
+        Ok(c) => strip_output_fences(&c),
+        Err(err) => return Ok(FileEditResponse::unchanged(&req, format!("Error generating edit: {err}"))),
+    };
+
+    Ok(FileEditResponse {
+        updated_contents,
+        base_contents: req.contents,
+        hunks: Vec::new(),
+        error: None,
+    })
+}
+
+/// Naive cleanup if the LLM wraps its output in ```markdown ... ```
+fn strip_output_fences(output: &str) -> String {
+    output
+        .trim()
+        .trim_start_matches("```markdown")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .to_string()
+}
+
+/// Read-only text around a section: the document outline and the lines just
+/// before and after it.
+fn section_context(contents: &str, target: &EditTarget) -> String {
+    let outline: Vec<String> = markdown::parse_headings(contents)
+        .iter()
+        .map(|h| format!("{} {}", "#".repeat(h.level), h.text))
+        .collect();
+
+    let before: Vec<&str> = contents[..target.start].lines().collect();
+    let before = before[before.len().saturating_sub(SECTION_CONTEXT_LINES)..].join("\n");
+    let after: Vec<&str> = contents[target.end..]
+        .lines()
+        .take(SECTION_CONTEXT_LINES)
+        .collect();
+    let after = after.join("\n");
+
+    format!(
+        "Document outline:\n{}\n\nText just before the section:\n```markdown\n{}\n```\n\nText just after the section:\n```markdown\n{}\n```",
+        outline.join("\n"),
+        before,
+        after
+    )
+}
+
+async fn section_file_edit(
+    client: &(dyn LlmClient + Send + Sync),
+    req: &FileEditRequest,
+    path: &str,
+    target: &EditTarget,
+) -> Result<FileEditResponse, Error> {
+    let section_text = target.text(&req.contents);
+    let prompt = format!(
+        "You are an expert editor working on one section of the file `{}`.
+User Instruction: {}
+
+{}
+
+Here is the section to edit:
+```markdown
+{}
+```
+
+The surrounding text is for reference only. Return ONLY the full updated section, starting with its heading line. \
+Do not return any text from outside the section. Do not add markdown code fences around the output unless the section itself contains them. Do not add conversational text.",
+        path,
+        req.instruction,
+        section_context(&req.contents, target),
+        section_text
+    );
+
+    let output = match client.chat_completion(&prompt).await {
+        Ok(output) => strip_output_fences(&output),
+        Err(err) => return Ok(FileEditResponse::unchanged(req, format!("Error generating edit: {err}"))),
+    };
+
+    // Keep the blank lines that separated the section from the next heading.
+    let trailing = &section_text[section_text.trim_end().len()..];
+    let replacement = format!("{}{}", output.trim_end(), trailing);
+
+    Ok(FileEditResponse {
+        updated_contents: target.splice(&req.contents, &replacement),
+        base_contents: req.contents.clone(),
+        hunks: Vec::new(),
+        error: None,
+    })
+}
+
+async fn patch_file_edit(
+    client: &(dyn LlmClient + Send + Sync),
+    req: &FileEditRequest,
+    path: &str,
+    target: &EditTarget,
+) -> Result<FileEditResponse, Error> {
+    let target_text = target.text(&req.contents);
+    let scope = match &target.heading {
+        Some(heading) => format!(
+            "Here is the section \"{}\" of the file `{}`. Only change text inside this section.\n\n{}\n\nSection:",
+            heading,
+            path,
+            section_context(&req.contents, target)
+        ),
+        None => format!("Here is the file `{}`:", path),
     };
+    let prompt = format!(
+        "You are an expert editor.
+User Instruction: {}
 
-    Ok(FileEditResponse { updated_contents })
+{}
+```markdown
+{}
+```
+
+Return ONLY the changes as one or more blocks in this exact format:
+<<<<<<< SEARCH
+exact lines currently in the text
+=======
+the lines that replace them
+>>>>>>> REPLACE
+
+Each SEARCH section must copy the current lines exactly and match only one place in the text. \
+Blocks are applied in order. Do not add conversational text.",
+        req.instruction, scope, target_text
+    );
+
+    let patch_text = match client.chat_completion(&prompt).await {
+        Ok(patch_text) => patch_text,
+        Err(err) => return Ok(FileEditResponse::unchanged(req, format!("Error generating edit: {err}"))),
+    };
+
+    let outcome = match patch::apply_patch(target_text, &patch_text) {
+        Ok(outcome) => outcome,
+        Err(err) => return Ok(FileEditResponse::unchanged(req, err)),
+    };
+
+    let failed = outcome.failed_hunks().count();
+    Ok(FileEditResponse {
+        updated_contents: match &outcome.contents {
+            Some(patched) => target.splice(&req.contents, patched),
+            None => req.contents.clone(),
+        },
+        base_contents: req.contents.clone(),
+        error: (failed > 0).then(|| {
+            format!(
+                "{failed} of {} patch hunks did not apply; no changes were made",
+                outcome.hunks.len()
+            )
+        }),
+        hunks: outcome.hunks,
+    })
 }
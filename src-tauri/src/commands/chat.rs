@@ -0,0 +1,72 @@
+use crate::db::chat_sessions::{
+  ChatSearchHit, ChatSession, ChatSessionDb, StoredChatMessage, DEFAULT_SESSION_TITLE,
+};
+use crate::util::error::Error;
+
+#[tauri::command]
+pub fn create_chat_session(project_root: String, title: Option<String>) -> Result<ChatSession, Error> {
+  let db = ChatSessionDb::open_for_project(&project_root)?;
+  let title = title
+    .map(|t| t.trim().to_string())
+    .filter(|t| !t.is_empty())
+    .unwrap_or_else(|| DEFAULT_SESSION_TITLE.to_string());
+  db.create_session(&title)
+}
+
+#[tauri::command]
+pub fn list_chat_sessions(project_root: String) -> Result<Vec<ChatSession>, Error> {
+  let db = ChatSessionDb::open_for_project(&project_root)?;
+  db.list_sessions()
+}
+
+#[tauri::command]
+pub fn get_chat_session_messages(
+  project_root: String,
+  session_id: i64,
+) -> Result<Vec<StoredChatMessage>, Error> {
+  let db = ChatSessionDb::open_for_project(&project_root)?;
+  db.get_session(session_id)?;
+  db.get_messages(session_id)
+}
+
+#[tauri::command]
+pub fn rename_chat_session(
+  project_root: String,
+  session_id: i64,
+  title: String,
+) -> Result<ChatSession, Error> {
+  let title = title.trim();
+  if title.is_empty() {
+    return Err(Error::Anyhow(anyhow::Error::msg("Chat session title cannot be empty")));
+  }
+  let db = ChatSessionDb::open_for_project(&project_root)?;
+  db.rename_session(session_id, title)
+}
+
+#[tauri::command]
+pub fn delete_chat_session(project_root: String, session_id: i64) -> Result<(), Error> {
+  let db = ChatSessionDb::open_for_project(&project_root)?;
+  db.delete_session(session_id)
+}
+
+/// Start a new session containing the history of `session_id` up to and
+/// including `message_id`.
+#[tauri::command]
+pub fn fork_chat_session(
+  project_root: String,
+  session_id: i64,
+  message_id: i64,
+) -> Result<ChatSession, Error> {
+  let mut db = ChatSessionDb::open_for_project(&project_root)?;
+  db.fork_session(session_id, message_id)
+}
+
+#[tauri::command]
+pub fn search_chat_sessions(
+  project_root: String,
+  query: String,
+  limit: Option<usize>,
+) -> Result<Vec<ChatSearchHit>, Error> {
+  let db = ChatSessionDb::open_for_project(&project_root)?;
+  db.search_messages(&query, limit.unwrap_or(50))
+}
@@ -1,6 +1,18 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use tauri::api::dialog::blocking::FileDialogBuilder;
+
+use crate::project::merge::{merge_three_way, MergeResult, Resolution};
+use crate::project::patch::{self, HunkResult};
+use crate::db::snapshots::SnapshotSource;
+use crate::project::paths::{resolve_project_path, Access};
+use crate::project::rename::{plan_rename, update_embedding_index};
+use crate::project::snapshots::{snapshot_change, snapshot_transaction};
+use crate::project::transactions::{EditJournal, TransactionSummary};
+use crate::project::tree::{self, TreeNode};
+use crate::project::versions::{ensure_version, version_token};
+use crate::util::error::Error;
 
 #[derive(Serialize)]
 pub struct FileEntry {
@@ -36,7 +48,12 @@ pub fn list_markdown_files(project_root: String) -> tauri::Result<Vec<FileEntry>
   // Instead, we rely on WalkDir returning paths relative to the root we gave it
   // (if we gave relative) or absolute if we gave absolute.
   // Since project_root from dialog is usually absolute, WalkDir yields absolute paths.
-  for entry_result in walkdir::WalkDir::new(&root_path) {
+  // Hidden files and folders, including `.git` and `.codexlotus` with its
+  // trash, are skipped.
+  let walker = walkdir::WalkDir::new(&root_path)
+    .into_iter()
+    .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'));
+  for entry_result in walker {
     match entry_result {
       Ok(entry) => {
         let path = entry.path();
@@ -77,31 +94,95 @@ pub fn list_markdown_files(project_root: String) -> tauri::Result<Vec<FileEntry>
   Ok(entries)
 }
 
+/// The project as a nested tree of folders and files with size, modification
+/// time and, for markdown files, word count, title, frontmatter tags and
+/// embedding index status. Runs off the async runtime since it reads every
+/// markdown file.
+#[tauri::command]
+pub async fn get_project_tree(project_root: String, markdown_only: Option<bool>) -> Result<TreeNode, Error> {
+  tokio::task::spawn_blocking(move || tree::project_tree(&project_root, markdown_only.unwrap_or(false)))
+    .await
+    .map_err(|err| Error::Anyhow(anyhow::anyhow!("Project tree task failed: {err}")))?
+}
+
 #[tauri::command]
-pub fn read_file(path: String) -> tauri::Result<String> {
+pub fn read_file(project_root: String, path: String) -> Result<String, Error> {
+  let path = resolve_project_path(&project_root, &path, Access::Read)?;
   let contents = fs::read_to_string(path)?;
   Ok(contents)
 }
 
+#[derive(Serialize)]
+pub struct VersionedFile {
+  pub contents: String,
+  /// Pass back to `write_file` as `expected_version` to detect changes made
+  /// by other programs in the meantime.
+  pub version: String,
+}
+
 #[tauri::command]
-pub fn write_file(path: String, contents: String) -> tauri::Result<()> {
-  if let Some(parent) = Path::new(&path).parent() {
+pub fn read_file_versioned(project_root: String, path: String) -> Result<VersionedFile, Error> {
+  let path = resolve_project_path(&project_root, &path, Access::Read)?;
+  let bytes = fs::read(&path)?;
+  let version = version_token(&path, &bytes)?;
+  let contents = String::from_utf8(bytes)
+    .map_err(|err| Error::Anyhow(anyhow::anyhow!("{:?} is not valid UTF-8: {err}", path)))?;
+  Ok(VersionedFile { contents, version })
+}
+
+/// Write a file and return its new version. With `expected_version`, the
+/// write fails with a conflict error if the file changed on disk since that
+/// version was read. Every save is recorded in the file's version history.
+#[tauri::command]
+pub fn write_file(
+  project_root: String,
+  path: String,
+  contents: String,
+  expected_version: Option<String>,
+) -> Result<String, Error> {
+  let resolved = resolve_project_path(&project_root, &path, Access::Write)?;
+  if let Some(expected) = expected_version.as_deref() {
+    ensure_version(&resolved, &path, expected)?;
+  }
+  let before = fs::read_to_string(&resolved).ok();
+  if let Some(parent) = resolved.parent() {
     fs::create_dir_all(parent)?;
   }
-  fs::write(path, contents)?;
-  Ok(())
+  fs::write(&resolved, &contents)?;
+  snapshot_change(
+    &project_root,
+    &resolved,
+    before.as_deref(),
+    Some(&contents),
+    SnapshotSource::Save,
+    None,
+  );
+  version_token(&resolved, contents.as_bytes())
 }
 
 #[tauri::command]
-pub fn create_directory(path: String) -> tauri::Result<()> {
+pub fn create_directory(project_root: String, path: String) -> Result<(), Error> {
+  let path = resolve_project_path(&project_root, &path, Access::Write)?;
   fs::create_dir_all(path)?;
   Ok(())
 }
 
+/// Create the folder for a new project. This is the only command that takes
+/// a path outside any project, since the folder becomes the project root.
 #[tauri::command]
-pub fn list_files_in_dir(path: String) -> tauri::Result<Vec<String>> {
+pub fn create_project_root(path: String) -> Result<(), Error> {
+  let root = Path::new(&path);
+  if root.is_file() {
+    return Err(Error::Anyhow(anyhow::anyhow!("'{path}' already exists and is a file")));
+  }
+  fs::create_dir_all(root)?;
+  Ok(())
+}
+
+#[tauri::command]
+pub fn list_files_in_dir(project_root: String, path: String) -> Result<Vec<String>, Error> {
   let mut files = Vec::new();
-  let dir_path = PathBuf::from(&path);
+  let dir_path = resolve_project_path(&project_root, &path, Access::Read)?;
 
   if !dir_path.is_dir() {
       return Ok(files); // Not a directory or doesn't exist, just return empty
@@ -117,11 +198,193 @@ pub fn list_files_in_dir(path: String) -> tauri::Result<Vec<String>> {
   Ok(files)
 }
 
+/// Copy a file within the project. Files from outside it come in through
+/// `import_files`.
 #[tauri::command]
-pub fn copy_file(source: String, destination: String) -> tauri::Result<()> {
-  if let Some(parent) = Path::new(&destination).parent() {
+pub fn copy_file(project_root: String, source: String, destination: String) -> Result<(), Error> {
+  let source = resolve_project_path(&project_root, &source, Access::Read)?;
+  let destination = resolve_project_path(&project_root, &destination, Access::Write)?;
+  if let Some(parent) = destination.parent() {
     fs::create_dir_all(parent)?;
   }
   fs::copy(source, destination)?;
   Ok(())
 }
+
+/// File types offered by `import_files`.
+const IMPORT_EXTENSIONS: &[&str] = &["md", "markdown", "mdx", "png", "jpg", "jpeg", "gif", "webp", "svg"];
+
+/// Let the user pick files from anywhere with the native file dialog and copy
+/// them into the project root under their own names. The dialog is opened
+/// here so the webview never names a file outside the project. Returns how
+/// many files were imported, none when the dialog is cancelled.
+#[tauri::command]
+pub async fn import_files(project_root: String) -> Result<usize, Error> {
+  tokio::task::spawn_blocking(move || {
+    let Some(sources) = FileDialogBuilder::new()
+      .set_title("Import Files")
+      .add_filter("Supported Files", IMPORT_EXTENSIONS)
+      .pick_files()
+    else {
+      return Ok(0);
+    };
+    let mut imported = 0;
+    for source in sources {
+      let Some(name) = source.file_name().and_then(|name| name.to_str()) else {
+        continue;
+      };
+      let destination = resolve_project_path(&project_root, name, Access::Write)?;
+      if fs::canonicalize(&source).is_ok_and(|source| source == destination) {
+        continue;
+      }
+      fs::copy(&source, &destination)?;
+      imported += 1;
+    }
+    Ok(imported)
+  })
+  .await
+  .map_err(|err| Error::Anyhow(anyhow::anyhow!("Import task failed: {err}")))?
+}
+
+#[derive(Serialize)]
+pub struct RenameResponse {
+  pub from: String,
+  pub to: String,
+  /// Links in other files (and relative links inside the moved files) that
+  /// were rewritten to the new location.
+  pub updated_links: usize,
+  pub transaction: TransactionSummary,
+}
+
+/// Move a file or folder within the project and rewrite every wiki and
+/// markdown link pointing into it. The move and the link updates are one
+/// undoable step.
+#[tauri::command]
+pub fn move_path(project_root: String, from: String, to: String) -> Result<RenameResponse, Error> {
+  let plan = plan_rename(&project_root, &from, &to)?;
+  let journal = EditJournal::open(&project_root)?;
+  let tx = journal.apply_with_moves(&plan.label(), std::slice::from_ref(&plan.file_move), &plan.edits)?;
+  snapshot_transaction(&project_root, &tx, SnapshotSource::Rename);
+  update_embedding_index(&project_root, &tx);
+  Ok(RenameResponse {
+    from: plan.file_move.from,
+    to: plan.file_move.to,
+    updated_links: plan.updated_links,
+    transaction: TransactionSummary::from(&tx),
+  })
+}
+
+/// Rename a file or folder in place; `new_name` is a file name, not a path.
+#[tauri::command]
+pub fn rename_path(project_root: String, path: String, new_name: String) -> Result<RenameResponse, Error> {
+  let new_name = new_name.trim();
+  if new_name.is_empty() || new_name.contains(['/', '\\']) || new_name == "." || new_name == ".." {
+    return Err(Error::Anyhow(anyhow::anyhow!("'{new_name}' is not a valid file name")));
+  }
+  let to = match path.trim_end_matches(['/', '\\']).rsplit_once(['/', '\\']) {
+    Some((parent, _)) => format!("{parent}/{new_name}"),
+    None => new_name.to_string(),
+  };
+  move_path(project_root, path, to)
+}
+
+#[derive(Serialize)]
+pub struct ApplyPatchResponse {
+  /// True when every hunk applied and the file was written.
+  pub applied: bool,
+  pub hunks: Vec<HunkResult>,
+  pub error: Option<String>,
+}
+
+/// Apply a search/replace or unified-diff patch to a file on disk. The file is
+/// only written when every hunk applies; otherwise it is left untouched and the
+/// failed hunks are reported.
+#[tauri::command]
+pub fn apply_file_patch(
+  project_root: String,
+  path: String,
+  patch: String,
+) -> Result<ApplyPatchResponse, Error> {
+  let path = resolve_project_path(&project_root, &path, Access::Write)?;
+  let existed = path.exists();
+  let original = if existed {
+    fs::read_to_string(&path)?
+  } else {
+    String::new()
+  };
+
+  let outcome = match patch::apply_patch(&original, &patch) {
+    Ok(outcome) => outcome,
+    Err(err) => {
+      return Ok(ApplyPatchResponse {
+        applied: false,
+        hunks: Vec::new(),
+        error: Some(err),
+      })
+    }
+  };
+
+  let Some(contents) = outcome.contents else {
+    let failed = outcome.failed_hunks().count();
+    return Ok(ApplyPatchResponse {
+      applied: false,
+      error: Some(format!(
+        "{failed} of {} patch hunks did not apply; the file was not changed",
+        outcome.hunks.len()
+      )),
+      hunks: outcome.hunks,
+    });
+  };
+
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  fs::write(&path, &contents)?;
+  snapshot_change(
+    &project_root,
+    &path,
+    existed.then_some(original.as_str()),
+    Some(&contents),
+    SnapshotSource::AiEdit,
+    Some("Patch"),
+  );
+  Ok(ApplyPatchResponse {
+    applied: true,
+    hunks: outcome.hunks,
+    error: None,
+  })
+}
+
+#[derive(Deserialize)]
+pub struct MergeFileEditRequest {
+  pub project_root: String,
+  pub path: String,
+  /// The contents the AI saw when it produced `proposed`.
+  pub base: String,
+  pub proposed: String,
+  /// The newest version of the file. Defaults to the contents on disk; the
+  /// editor passes its unsaved buffer instead.
+  #[serde(default)]
+  pub current: Option<String>,
+  /// Choices for the conflicts of a previous call, by conflict index.
+  #[serde(default)]
+  pub resolutions: Vec<Resolution>,
+}
+
+/// Three-way merge of an AI edit with changes made since the AI read the
+/// file. Nothing is written; conflicts are returned for the user to resolve.
+#[tauri::command]
+pub fn merge_file_edit(req: MergeFileEditRequest) -> Result<MergeResult, Error> {
+  let current = match req.current {
+    Some(current) => current,
+    None => {
+      let path = resolve_project_path(&req.project_root, &req.path, Access::Read)?;
+      if path.exists() {
+        fs::read_to_string(path)?
+      } else {
+        String::new()
+      }
+    }
+  };
+  Ok(merge_three_way(&req.base, &current, &req.proposed, &req.resolutions))
+}
@@ -0,0 +1,79 @@
+use crate::db::snapshots::SnapshotSource;
+use crate::project::git::{GitCommit, GitDiff, GitStatus, ProjectRepo};
+use crate::project::paths::{project_relative_path, resolve_project_path, Access};
+use crate::project::snapshots::snapshot_transaction;
+use crate::project::transactions::{EditJournal, EditRequest, TransactionSummary};
+use crate::util::error::Error;
+
+const DEFAULT_LOG_LIMIT: usize = 50;
+
+#[tauri::command]
+pub fn git_status(project_root: String) -> Result<GitStatus, Error> {
+  ProjectRepo::open(&project_root)?.status()
+}
+
+#[tauri::command]
+pub fn git_stage(project_root: String, paths: Vec<String>) -> Result<(), Error> {
+  ProjectRepo::open(&project_root)?.stage(&paths)
+}
+
+#[tauri::command]
+pub fn git_unstage(project_root: String, paths: Vec<String>) -> Result<(), Error> {
+  ProjectRepo::open(&project_root)?.unstage(&paths)
+}
+
+#[tauri::command]
+pub fn git_commit(project_root: String, message: String) -> Result<GitCommit, Error> {
+  ProjectRepo::open(&project_root)?.commit(&message)
+}
+
+/// Diff against HEAD for one file, or the whole project without `path`.
+#[tauri::command]
+pub fn git_diff(project_root: String, path: Option<String>) -> Result<GitDiff, Error> {
+  ProjectRepo::open(&project_root)?.diff_head(path.as_deref())
+}
+
+#[tauri::command]
+pub fn git_log(
+  project_root: String,
+  path: Option<String>,
+  limit: Option<usize>,
+) -> Result<Vec<GitCommit>, Error> {
+  ProjectRepo::open(&project_root)?.log(path.as_deref(), limit.unwrap_or(DEFAULT_LOG_LIMIT))
+}
+
+/// A file's contents at a commit, for previewing before checking it out.
+#[tauri::command]
+pub fn git_show_file(
+  project_root: String,
+  path: String,
+  revision: String,
+) -> Result<Option<String>, Error> {
+  ProjectRepo::open(&project_root)?.file_at(&path, &revision)
+}
+
+/// Replace a file in the working tree with its version from a commit. This
+/// goes through the edit journal, so it can be undone, and is recorded in the
+/// file's local history. A file that did not exist in that commit is deleted.
+#[tauri::command]
+pub fn git_checkout_file(
+  project_root: String,
+  path: String,
+  revision: String,
+) -> Result<TransactionSummary, Error> {
+  let resolved = resolve_project_path(&project_root, &path, Access::Write)?;
+  let contents = ProjectRepo::open(&project_root)?.file_at(&path, &revision)?;
+  let relative = project_relative_path(&project_root, &resolved)?;
+
+  let journal = EditJournal::open(&project_root)?;
+  let label = format!("Check out {relative} from {revision}");
+  let tx = journal.apply(
+    &label,
+    &[EditRequest {
+      path: relative,
+      contents,
+    }],
+  )?;
+  snapshot_transaction(&project_root, &tx, SnapshotSource::Restore);
+  Ok(TransactionSummary::from(&tx))
+}
@@ -0,0 +1,88 @@
+use serde::Deserialize;
+
+use crate::project::graph::{GraphNode, GraphView, KnowledgeGraph, LoreMap, Neighbourhood};
+use crate::project::graph_export::{self, GraphFilter, GraphFormat};
+use crate::util::error::{self, Error};
+
+#[derive(Deserialize)]
+pub struct ExportGraphRequest {
+  pub project_root: String,
+  pub format: GraphFormat,
+  #[serde(flatten)]
+  pub filter: GraphFilter,
+}
+
+/// The whole knowledge graph: files, headings, tags and codex entities.
+#[tauri::command]
+pub async fn get_knowledge_graph(project_root: String) -> Result<KnowledgeGraph, Error> {
+  with_graph(project_root, Ok).await
+}
+
+/// The knowledge graph as drawn by the lore map, with its `hub_limit`
+/// (default 10) most connected nodes and its orphans, so the map builds the
+/// graph only once.
+#[tauri::command]
+pub async fn get_lore_graph(project_root: String, hub_limit: Option<usize>) -> Result<LoreMap, Error> {
+  with_graph(project_root, move |graph| {
+    Ok(LoreMap {
+      graph: graph.force_graph(),
+      hubs: graph.hubs(hub_limit.unwrap_or(10)),
+      orphans: graph.orphans(),
+    })
+  })
+  .await
+}
+
+/// Nodes within `hops` (default 1) of a file, heading, tag or entity.
+#[tauri::command]
+pub async fn graph_neighbours(project_root: String, node: String, hops: Option<usize>) -> Result<Neighbourhood, Error> {
+  with_graph(project_root, move |graph| graph.neighbours(&node, hops.unwrap_or(1))).await
+}
+
+/// The fewest hops between two concepts, or `null` if they are not connected.
+#[tauri::command]
+pub async fn graph_shortest_path(project_root: String, from: String, to: String) -> Result<Option<GraphView>, Error> {
+  with_graph(project_root, move |graph| graph.shortest_path(&from, &to)).await
+}
+
+/// Files that link nowhere, are linked from nowhere and carry no tags.
+#[tauri::command]
+pub async fn graph_orphans(project_root: String) -> Result<Vec<GraphNode>, Error> {
+  with_graph(project_root, |graph| Ok(graph.orphans())).await
+}
+
+/// The most connected nodes, `limit` (default 10) of them.
+#[tauri::command]
+pub async fn graph_hubs(project_root: String, limit: Option<usize>) -> Result<Vec<GraphNode>, Error> {
+  with_graph(project_root, move |graph| Ok(graph.hubs(limit.unwrap_or(10)))).await
+}
+
+/// Node ids of each connected part of the graph, largest first.
+#[tauri::command]
+pub async fn graph_components(project_root: String) -> Result<Vec<Vec<String>>, Error> {
+  with_graph(project_root, |graph| Ok(graph.components())).await
+}
+
+/// The lore map graph as GraphML, Graphviz DOT or an Obsidian canvas,
+/// filtered by folder, tag and connection kind. Returns the file contents.
+#[tauri::command]
+pub async fn export_graph(req: ExportGraphRequest) -> Result<String, Error> {
+  let ExportGraphRequest {
+    project_root,
+    format,
+    filter,
+  } = req;
+  with_graph(project_root, move |graph| Ok(graph_export::export_graph(&graph, format, &filter))).await
+}
+
+/// Build the graph off the async runtime, since it reads every markdown
+/// file, and run `query` on it.
+async fn with_graph<T, F>(project_root: String, query: F) -> Result<T, Error>
+where
+  T: Send + 'static,
+  F: FnOnce(KnowledgeGraph) -> error::Result<T> + Send + 'static,
+{
+  tokio::task::spawn_blocking(move || query(KnowledgeGraph::build(&project_root)?))
+    .await
+    .map_err(|err| Error::Anyhow(anyhow::anyhow!("Graph query failed: {err}")))?
+}
@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+
+use crate::db::snapshots::{FileSnapshot, SnapshotSource};
+use crate::project::paths::{project_relative_path, resolve_project_path, Access};
+use crate::project::rename::update_embedding_index;
+use crate::project::snapshots::{snapshot_transaction, SnapshotStore};
+use crate::project::transactions::{EditJournal, EditRequest, TransactionSummary};
+use crate::util::error::Error;
+
+#[derive(Deserialize)]
+pub struct ApplyTransactionRequest {
+  pub project_root: String,
+  /// Short description shown in the undo history, e.g. "AI edit: combat.md".
+  pub label: String,
+  pub edits: Vec<EditRequest>,
+}
+
+/// Write a set of file edits atomically and record them as one undoable step.
+#[tauri::command]
+pub fn apply_edit_transaction(req: ApplyTransactionRequest) -> Result<TransactionSummary, Error> {
+  let journal = EditJournal::open(&req.project_root)?;
+  let tx = journal.apply(&req.label, &req.edits)?;
+  snapshot_transaction(&req.project_root, &tx, SnapshotSource::AiEdit);
+  Ok(TransactionSummary::from(&tx))
+}
+
+#[tauri::command]
+pub fn undo_edit_transaction(project_root: String) -> Result<Option<TransactionSummary>, Error> {
+  let journal = EditJournal::open(&project_root)?;
+  let tx = journal.undo()?;
+  if let Some(tx) = &tx {
+    snapshot_transaction(&project_root, tx, SnapshotSource::Undo);
+    update_embedding_index(&project_root, tx);
+  }
+  Ok(tx.as_ref().map(TransactionSummary::from))
+}
+
+#[tauri::command]
+pub fn redo_edit_transaction(project_root: String) -> Result<Option<TransactionSummary>, Error> {
+  let journal = EditJournal::open(&project_root)?;
+  let tx = journal.redo()?;
+  if let Some(tx) = &tx {
+    snapshot_transaction(&project_root, tx, SnapshotSource::Redo);
+    update_embedding_index(&project_root, tx);
+  }
+  Ok(tx.as_ref().map(TransactionSummary::from))
+}
+
+#[tauri::command]
+pub fn list_edit_transactions(project_root: String) -> Result<Vec<TransactionSummary>, Error> {
+  let journal = EditJournal::open(&project_root)?;
+  journal.list()
+}
+
+/// Snapshots of one file, newest first.
+#[tauri::command]
+pub fn list_file_versions(project_root: String, path: String) -> Result<Vec<FileSnapshot>, Error> {
+  let relative = relative_path(&project_root, &path)?;
+  SnapshotStore::open(&project_root)?.list(&relative)
+}
+
+#[derive(Serialize)]
+pub struct FileVersionDiff {
+  pub original: String,
+  pub modified: String,
+  /// Unified diff of `original` against `modified`.
+  pub unified: String,
+  pub insertions: usize,
+  pub deletions: usize,
+}
+
+/// Diff two snapshots of a file. Without `to_id` the snapshot is compared
+/// with the file as it is on disk now.
+#[tauri::command]
+pub fn diff_file_versions(
+  project_root: String,
+  path: String,
+  from_id: i64,
+  to_id: Option<i64>,
+) -> Result<FileVersionDiff, Error> {
+  let resolved = resolve_project_path(&project_root, &path, Access::Read)?;
+  let relative = project_relative_path(&project_root, &resolved)?;
+  let store = SnapshotStore::open(&project_root)?;
+
+  let original = store.contents(&store.get(&relative, from_id)?)?;
+  let (modified, to_label) = match to_id {
+    Some(id) => (store.contents(&store.get(&relative, id)?)?, format!("version {id}")),
+    None if resolved.exists() => (std::fs::read_to_string(&resolved)?, "current".to_string()),
+    None => (String::new(), "deleted".to_string()),
+  };
+
+  let diff = TextDiff::from_lines(&original, &modified);
+  let mut insertions = 0;
+  let mut deletions = 0;
+  for change in diff.iter_all_changes() {
+    match change.tag() {
+      ChangeTag::Insert => insertions += 1,
+      ChangeTag::Delete => deletions += 1,
+      ChangeTag::Equal => {}
+    }
+  }
+  let unified = diff
+    .unified_diff()
+    .context_radius(3)
+    .header(&format!("{relative} (version {from_id})"), &format!("{relative} ({to_label})"))
+    .to_string();
+
+  Ok(FileVersionDiff {
+    original,
+    modified,
+    unified,
+    insertions,
+    deletions,
+  })
+}
+
+/// Put a snapshot's contents back on disk. The restore goes through the edit
+/// journal, so it can be undone like any other edit.
+#[tauri::command]
+pub fn restore_file_version(
+  project_root: String,
+  path: String,
+  snapshot_id: i64,
+) -> Result<TransactionSummary, Error> {
+  let relative = relative_path(&project_root, &path)?;
+  let store = SnapshotStore::open(&project_root)?;
+  let snapshot = store.get(&relative, snapshot_id)?;
+  let contents = store.contents(&snapshot)?;
+
+  let journal = EditJournal::open(&project_root)?;
+  let label = format!("Restore {relative} to version {snapshot_id}");
+  let tx = journal.apply(
+    &label,
+    &[EditRequest {
+      path: relative,
+      contents: Some(contents),
+    }],
+  )?;
+  snapshot_transaction(&project_root, &tx, SnapshotSource::Restore);
+  Ok(TransactionSummary::from(&tx))
+}
+
+fn relative_path(project_root: &str, path: &str) -> Result<String, Error> {
+  let resolved = resolve_project_path(project_root, path, Access::Read)?;
+  project_relative_path(project_root, &resolved)
+}
@@ -0,0 +1,67 @@
+use crate::db::links::{IndexedLink, LinkDb};
+use crate::project::link_check::{self, LinkReport};
+use crate::project::link_index::refresh_link_index;
+use crate::project::markdown::slugify;
+use crate::project::paths::{project_relative_path, resolve_project_path, Access};
+use crate::util::error::{self, Error};
+
+/// Find wiki links, markdown links, images and heading anchors that point
+/// nowhere. Runs off the async runtime since it reads every markdown file.
+#[tauri::command]
+pub async fn check_links(project_root: String) -> Result<LinkReport, Error> {
+  tokio::task::spawn_blocking(move || link_check::check_links(&project_root))
+    .await
+    .map_err(|err| Error::Anyhow(anyhow::anyhow!("Link check failed: {err}")))?
+}
+
+/// Links pointing at a file, or at one of its headings when `heading` is
+/// given (by text or slug).
+#[tauri::command]
+pub async fn get_backlinks(
+  project_root: String,
+  path: String,
+  heading: Option<String>,
+) -> Result<Vec<IndexedLink>, Error> {
+  query_links(project_root, move |root, db| {
+    let path = relative_path(root, &path)?;
+    db.backlinks(&path, heading.as_deref().map(slugify).as_deref())
+  })
+  .await
+}
+
+/// Links in a file, in document order, with the files they resolve to.
+#[tauri::command]
+pub async fn get_outgoing_links(project_root: String, path: String) -> Result<Vec<IndexedLink>, Error> {
+  query_links(project_root, move |root, db| {
+    let path = relative_path(root, &path)?;
+    db.outgoing(&path)
+  })
+  .await
+}
+
+/// Links anywhere in the project whose target file does not exist.
+#[tauri::command]
+pub async fn get_unresolved_links(project_root: String) -> Result<Vec<IndexedLink>, Error> {
+  query_links(project_root, |_, db| db.unresolved()).await
+}
+
+/// Refresh the link index, which only reads files changed since the last
+/// refresh, then run `query` against it.
+async fn query_links<T, F>(project_root: String, query: F) -> Result<T, Error>
+where
+  T: Send + 'static,
+  F: FnOnce(&str, &LinkDb) -> error::Result<T> + Send + 'static,
+{
+  tokio::task::spawn_blocking(move || {
+    refresh_link_index(&project_root)?;
+    let db = LinkDb::open_for_project(&project_root)?;
+    query(&project_root, &db)
+  })
+  .await
+  .map_err(|err| Error::Anyhow(anyhow::anyhow!("Link index query failed: {err}")))?
+}
+
+fn relative_path(project_root: &str, path: &str) -> error::Result<String> {
+  let resolved = resolve_project_path(project_root, path, Access::Read)?;
+  project_relative_path(project_root, &resolved)
+}
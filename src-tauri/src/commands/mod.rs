@@ -2,3 +2,11 @@ pub mod files;
 pub mod settings;
 pub mod ai;
 pub mod rag;
+pub mod chat;
+pub mod history;
+pub mod git;
+pub mod search;
+pub mod trash;
+pub mod links;
+pub mod graph;
+pub mod tags;
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::ai::{
     gemini_client::GeminiClient, llm_client::LlmClient,
-    openai_client::OpenAiClient,
+    openai_client::OpenAiClient, reranker,
 };
 use crate::commands::settings::{get_api_key_sync, AppSettings};
 use crate::db::embeddings::EmbeddingDb;
@@ -62,6 +62,7 @@ async fn get_client(
             foreground_panel_color: None,
             statblock_bg_color: None,
             statblock_font_color: None,
+            rerank_enabled: false,
         });
 
     if settings.provider == "gemini" {
@@ -122,7 +123,12 @@ pub async fn rag_query(
         }
     };
 
-    let scored = match db.query_similar_chunks(&req.project_root, &query_embedding, 10) {
+    let settings = crate::commands::settings::load_settings(app)
+        .await
+        .unwrap_or_default();
+    let limit = if settings.rerank_enabled { reranker::RERANK_CANDIDATES } else { 10 };
+
+    let mut scored = match db.query_similar_chunks(&req.project_root, &query_embedding, limit) {
         Ok(results) => results,
         Err(err) => {
             eprintln!("Error querying embeddings: {err}");
@@ -130,6 +136,18 @@ pub async fn rag_query(
         }
     };
 
+    if settings.rerank_enabled {
+        (scored, _) = reranker::rerank(
+            client.as_ref(),
+            &req.project_root,
+            &settings.chat_model,
+            &req.query,
+            scored,
+            10,
+        )
+        .await;
+    }
+
     let hits = scored
         .into_iter()
         .map(|chunk| RagHit {
@@ -0,0 +1,73 @@
+use serde::Deserialize;
+
+use crate::db::snapshots::SnapshotSource;
+use crate::project::replace::{self, ReplaceOptions, ReplacePreview, ReplaceSelection};
+use crate::project::search::{self, SearchOptions, SearchResults};
+use crate::project::snapshots::snapshot_transaction;
+use crate::project::transactions::{EditJournal, TransactionSummary};
+use crate::util::error::Error;
+
+#[derive(Deserialize)]
+pub struct SearchProjectRequest {
+  pub project_root: String,
+  #[serde(flatten)]
+  pub options: SearchOptions,
+}
+
+/// Plain-text / regex search over the project files. Runs off the async
+/// runtime since it reads the whole project.
+#[tauri::command]
+pub async fn search_project(req: SearchProjectRequest) -> Result<SearchResults, Error> {
+  tokio::task::spawn_blocking(move || search::search_project(&req.project_root, &req.options))
+    .await
+    .map_err(|err| Error::Anyhow(anyhow::anyhow!("Search task failed: {err}")))?
+}
+
+#[derive(Deserialize)]
+pub struct ReplaceRequest {
+  pub project_root: String,
+  #[serde(flatten)]
+  pub options: ReplaceOptions,
+}
+
+/// Dry run of a project-wide find and replace; nothing is written.
+#[tauri::command]
+pub async fn preview_replace(req: ReplaceRequest) -> Result<ReplacePreview, Error> {
+  tokio::task::spawn_blocking(move || replace::preview_replace(&req.project_root, &req.options))
+    .await
+    .map_err(|err| Error::Anyhow(anyhow::anyhow!("Replace preview failed: {err}")))?
+}
+
+#[derive(Deserialize)]
+pub struct ApplyReplaceRequest {
+  pub project_root: String,
+  #[serde(flatten)]
+  pub options: ReplaceOptions,
+  /// Changes picked from the preview, per file.
+  pub selections: Vec<ReplaceSelection>,
+}
+
+/// Apply the selected changes of a preview to all files at once, as a single
+/// step in the undo history.
+#[tauri::command]
+pub fn apply_replace(req: ApplyReplaceRequest) -> Result<TransactionSummary, Error> {
+  let edits = replace::replacement_edits(&req.project_root, &req.options, &req.selections)?;
+  let label = format!(
+    "Replace \"{}\" with \"{}\"",
+    truncate_label(&req.options.search.query),
+    truncate_label(&req.options.replacement)
+  );
+  let journal = EditJournal::open(&req.project_root)?;
+  let tx = journal.apply(&label, &edits)?;
+  snapshot_transaction(&req.project_root, &tx, SnapshotSource::Replace);
+  Ok(TransactionSummary::from(&tx))
+}
+
+fn truncate_label(text: &str) -> String {
+  const MAX_CHARS: usize = 40;
+  if text.chars().count() <= MAX_CHARS {
+    text.to_string()
+  } else {
+    format!("{}…", text.chars().take(MAX_CHARS).collect::<String>())
+  }
+}
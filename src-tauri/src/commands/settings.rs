@@ -18,6 +18,9 @@ pub struct AppSettings {
   pub input_font_color: Option<String>,
   pub statblock_bg_color: Option<String>,
   pub statblock_font_color: Option<String>,
+  /// Rerank retrieved context chunks with the chat model before prompting.
+  #[serde(default)]
+  pub rerank_enabled: bool,
 }
 
 #[derive(Serialize)]
@@ -64,6 +67,7 @@ pub async fn load_settings(app: tauri::AppHandle) -> Result<AppSettings, Error>
     input_font_color: None,
     statblock_bg_color: Some("#fdf1dc".to_string()),
     statblock_font_color: Some("#58180D".to_string()),
+    rerank_enabled: false,
   };
 
   if let Some(path) = get_settings_path(&app) {
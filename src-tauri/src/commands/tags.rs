@@ -0,0 +1,44 @@
+use crate::project::mentions::{self, FileMentions};
+use crate::project::semantic_tags::{self, FileSemanticTags};
+use crate::project::paths::{project_relative_path, resolve_project_path, Access};
+use crate::util::error::{self, Error};
+
+/// Mentions of project files, their aliases and codex entities, with the
+/// tags they suggest, in one file or in every markdown file when `path` is
+/// not given.
+#[tauri::command]
+pub async fn detect_mentions(project_root: String, path: Option<String>) -> Result<Vec<FileMentions>, Error> {
+  tokio::task::spawn_blocking(move || {
+    let path = relative_path(&project_root, path)?;
+    mentions::detect_mentions(&project_root, path.as_deref())
+  })
+  .await
+  .map_err(|err| Error::Anyhow(anyhow::anyhow!("Mention detection failed: {err}")))?
+}
+
+/// Tags whose files are semantically close to a file that neither carries
+/// nor names them, with confidence scores, for one file or every indexed
+/// markdown file. Uses the embeddings already in the project index, so it
+/// needs no API key but only covers indexed files.
+#[tauri::command]
+pub async fn suggest_semantic_tags(
+  project_root: String,
+  path: Option<String>,
+) -> Result<Vec<FileSemanticTags>, Error> {
+  tokio::task::spawn_blocking(move || {
+    let path = relative_path(&project_root, path)?;
+    semantic_tags::suggest_tags(&project_root, path.as_deref())
+  })
+  .await
+  .map_err(|err| Error::Anyhow(anyhow::anyhow!("Semantic tag suggestion failed: {err}")))?
+}
+
+fn relative_path(project_root: &str, path: Option<String>) -> error::Result<Option<String>> {
+  match path {
+    Some(path) => {
+      let resolved = resolve_project_path(project_root, &path, Access::Read)?;
+      Ok(Some(project_relative_path(project_root, &resolved)?))
+    }
+    None => Ok(None),
+  }
+}
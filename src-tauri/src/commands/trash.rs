@@ -0,0 +1,46 @@
+use crate::db::embeddings::EmbeddingDb;
+use crate::project::trash::{ProjectTrash, TrashEntry};
+use crate::util::error::Error;
+
+/// Delete a file or folder by moving it into the project trash. It is
+/// dropped from the embedding index right away so search and AI context
+/// stop returning it.
+#[tauri::command]
+pub fn delete_path(project_root: String, path: String) -> Result<TrashEntry, Error> {
+  let entry = ProjectTrash::open(&project_root)?.delete(&path)?;
+  let removed = EmbeddingDb::open_for_project(&project_root)
+    .and_then(|mut db| db.remove_path(&project_root, &entry.original_path));
+  if let Err(err) = removed {
+    eprintln!("[Trash] Failed to remove {} from the embedding index: {err}", entry.original_path);
+  }
+  Ok(entry)
+}
+
+/// Items in the trash, most recently deleted first.
+#[tauri::command]
+pub fn list_trash(project_root: String) -> Result<Vec<TrashEntry>, Error> {
+  ProjectTrash::open(&project_root)?.list()
+}
+
+/// Move an item back to its original path, or to `destination` when that
+/// path is taken. Returns the path it was restored to. Restored files are
+/// searchable by meaning again after the next re-index.
+#[tauri::command]
+pub fn restore_from_trash(
+  project_root: String,
+  id: u64,
+  destination: Option<String>,
+) -> Result<String, Error> {
+  ProjectTrash::open(&project_root)?.restore(id, destination.as_deref())
+}
+
+/// Permanently delete items from the trash; without `ids`, empty it.
+/// Returns the number of items removed.
+#[tauri::command]
+pub fn purge_trash(project_root: String, ids: Option<Vec<u64>>) -> Result<usize, Error> {
+  let trash = ProjectTrash::open(&project_root)?;
+  match ids {
+    Some(ids) => trash.purge(&ids),
+    None => trash.empty(),
+  }
+}
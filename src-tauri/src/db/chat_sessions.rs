@@ -0,0 +1,290 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+
+use crate::util::error::{Error, Result};
+
+use super::open_project_connection;
+
+pub const DEFAULT_SESSION_TITLE: &str = "New chat";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatSession {
+  pub id: i64,
+  pub title: String,
+  pub forked_from: Option<i64>,
+  pub created_at: i64,
+  pub updated_at: i64,
+  pub message_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredChatMessage {
+  pub id: i64,
+  pub session_id: i64,
+  pub role: String,
+  pub content: String,
+  pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatSearchHit {
+  pub session_id: i64,
+  pub session_title: String,
+  pub message_id: i64,
+  pub role: String,
+  pub snippet: String,
+  pub created_at: i64,
+}
+
+/// Summary of every message up to and including `covers_through_message_id`.
+#[derive(Debug, Clone)]
+pub struct SessionSummary {
+  pub summary: String,
+  pub covers_through_message_id: i64,
+}
+
+/// Chat sessions stored alongside the embeddings in the project database.
+#[derive(Debug)]
+pub struct ChatSessionDb {
+  conn: Connection,
+}
+
+const SESSION_COLUMNS: &str = "s.id, s.title, s.forked_from, s.created_at, s.updated_at, \
+   (SELECT COUNT(*) FROM chat_messages m WHERE m.session_id = s.id)";
+
+impl ChatSessionDb {
+  pub fn open_for_project(project_root: &str) -> Result<Self> {
+    let conn = open_project_connection(project_root)?;
+    Ok(Self { conn })
+  }
+
+  pub fn create_session(&self, title: &str) -> Result<ChatSession> {
+    self
+      .conn
+      .execute("INSERT INTO chat_sessions (title) VALUES (?1)", params![title])?;
+    self.get_session(self.conn.last_insert_rowid())
+  }
+
+  pub fn get_session(&self, session_id: i64) -> Result<ChatSession> {
+    self
+      .conn
+      .query_row(
+        &format!("SELECT {SESSION_COLUMNS} FROM chat_sessions s WHERE s.id = ?1"),
+        params![session_id],
+        session_from_row,
+      )
+      .optional()?
+      .ok_or_else(|| session_not_found(session_id))
+  }
+
+  /// All sessions, most recently updated first.
+  pub fn list_sessions(&self) -> Result<Vec<ChatSession>> {
+    let mut stmt = self.conn.prepare(&format!(
+      "SELECT {SESSION_COLUMNS} FROM chat_sessions s ORDER BY s.updated_at DESC, s.id DESC"
+    ))?;
+    let sessions = stmt
+      .query_map([], session_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(sessions)
+  }
+
+  pub fn rename_session(&self, session_id: i64, title: &str) -> Result<ChatSession> {
+    let updated = self.conn.execute(
+      "UPDATE chat_sessions SET title = ?1, updated_at = strftime('%s', 'now') WHERE id = ?2",
+      params![title, session_id],
+    )?;
+    if updated == 0 {
+      return Err(session_not_found(session_id));
+    }
+    self.get_session(session_id)
+  }
+
+  pub fn delete_session(&self, session_id: i64) -> Result<()> {
+    let deleted = self
+      .conn
+      .execute("DELETE FROM chat_sessions WHERE id = ?1", params![session_id])?;
+    if deleted == 0 {
+      return Err(session_not_found(session_id));
+    }
+    Ok(())
+  }
+
+  /// Copy a session up to and including `message_id` into a new session.
+  pub fn fork_session(&mut self, session_id: i64, message_id: i64) -> Result<ChatSession> {
+    let source = self.get_session(session_id)?;
+    let tx = self.conn.transaction()?;
+
+    let belongs: bool = tx.query_row(
+      "SELECT EXISTS(SELECT 1 FROM chat_messages WHERE id = ?1 AND session_id = ?2)",
+      params![message_id, session_id],
+      |row| row.get(0),
+    )?;
+    if !belongs {
+      return Err(Error::Anyhow(anyhow::anyhow!(
+        "Message {message_id} does not belong to chat session {session_id}"
+      )));
+    }
+
+    tx.execute(
+      "INSERT INTO chat_sessions (title, forked_from) VALUES (?1, ?2)",
+      params![format!("{} (fork)", source.title), session_id],
+    )?;
+    let fork_id = tx.last_insert_rowid();
+    tx.execute(
+      "INSERT INTO chat_messages (session_id, role, content, created_at) \
+       SELECT ?1, role, content, created_at FROM chat_messages \
+       WHERE session_id = ?2 AND id <= ?3 ORDER BY id",
+      params![fork_id, session_id, message_id],
+    )?;
+    tx.commit()?;
+
+    self.get_session(fork_id)
+  }
+
+  pub fn get_messages(&self, session_id: i64) -> Result<Vec<StoredChatMessage>> {
+    let mut stmt = self.conn.prepare(
+      "SELECT id, session_id, role, content, created_at FROM chat_messages \
+       WHERE session_id = ?1 ORDER BY id",
+    )?;
+    let messages = stmt
+      .query_map(params![session_id], message_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(messages)
+  }
+
+  /// Append messages to a session in one transaction and bump its
+  /// `updated_at`. A session still carrying the default title is named after
+  /// its first user message.
+  pub fn append_messages(&mut self, session_id: i64, messages: &[(&str, &str)]) -> Result<()> {
+    let session = self.get_session(session_id)?;
+    let tx = self.conn.transaction()?;
+
+    for (role, content) in messages {
+      tx.execute(
+        "INSERT INTO chat_messages (session_id, role, content) VALUES (?1, ?2, ?3)",
+        params![session_id, role, content],
+      )?;
+    }
+
+    let first_user = messages.iter().find(|(role, _)| *role == "user");
+    let title = match first_user {
+      Some((_, content)) if session.title == DEFAULT_SESSION_TITLE && session.message_count == 0 => {
+        title_from_message(content)
+      }
+      _ => session.title,
+    };
+    tx.execute(
+      "UPDATE chat_sessions SET title = ?1, updated_at = strftime('%s', 'now') WHERE id = ?2",
+      params![title, session_id],
+    )?;
+
+    tx.commit()?;
+    Ok(())
+  }
+
+  pub fn get_summary(&self, session_id: i64) -> Result<Option<SessionSummary>> {
+    let summary = self
+      .conn
+      .query_row(
+        "SELECT summary, covers_through_message_id FROM chat_session_summaries WHERE session_id = ?1",
+        params![session_id],
+        |row| {
+          Ok(SessionSummary {
+            summary: row.get(0)?,
+            covers_through_message_id: row.get(1)?,
+          })
+        },
+      )
+      .optional()?;
+    Ok(summary)
+  }
+
+  pub fn store_summary(&self, session_id: i64, summary: &SessionSummary) -> Result<()> {
+    self.conn.execute(
+      "INSERT OR REPLACE INTO chat_session_summaries (session_id, summary, covers_through_message_id, updated_at) \
+       VALUES (?1, ?2, ?3, strftime('%s', 'now'))",
+      params![session_id, summary.summary, summary.covers_through_message_id],
+    )?;
+    Ok(())
+  }
+
+  /// Full-text search across all messages in the project.
+  pub fn search_messages(&self, query: &str, limit: usize) -> Result<Vec<ChatSearchHit>> {
+    let match_expr = fts_match_expression(query);
+    if match_expr.is_empty() {
+      return Ok(Vec::new());
+    }
+
+    let mut stmt = self.conn.prepare(
+      "SELECT s.id, s.title, m.id, m.role, \
+         snippet(chat_messages_fts, 0, '[', ']', '…', 16), m.created_at \
+       FROM chat_messages_fts \
+       JOIN chat_messages m ON m.id = chat_messages_fts.rowid \
+       JOIN chat_sessions s ON s.id = m.session_id \
+       WHERE chat_messages_fts MATCH ?1 \
+       ORDER BY rank \
+       LIMIT ?2",
+    )?;
+    let hits = stmt
+      .query_map(params![match_expr, limit as i64], |row| {
+        Ok(ChatSearchHit {
+          session_id: row.get(0)?,
+          session_title: row.get(1)?,
+          message_id: row.get(2)?,
+          role: row.get(3)?,
+          snippet: row.get(4)?,
+          created_at: row.get(5)?,
+        })
+      })?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(hits)
+  }
+}
+
+fn session_from_row(row: &Row<'_>) -> rusqlite::Result<ChatSession> {
+  Ok(ChatSession {
+    id: row.get(0)?,
+    title: row.get(1)?,
+    forked_from: row.get(2)?,
+    created_at: row.get(3)?,
+    updated_at: row.get(4)?,
+    message_count: row.get::<_, i64>(5)? as usize,
+  })
+}
+
+fn message_from_row(row: &Row<'_>) -> rusqlite::Result<StoredChatMessage> {
+  Ok(StoredChatMessage {
+    id: row.get(0)?,
+    session_id: row.get(1)?,
+    role: row.get(2)?,
+    content: row.get(3)?,
+    created_at: row.get(4)?,
+  })
+}
+
+fn session_not_found(session_id: i64) -> Error {
+  Error::Anyhow(anyhow::anyhow!("Chat session {session_id} not found"))
+}
+
+fn title_from_message(content: &str) -> String {
+  let line = content.lines().find(|l| !l.trim().is_empty()).unwrap_or("").trim();
+  let mut title: String = line.chars().take(60).collect();
+  if line.chars().count() > 60 {
+    title.push('…');
+  }
+  if title.is_empty() {
+    DEFAULT_SESSION_TITLE.to_string()
+  } else {
+    title
+  }
+}
+
+/// Turn free text into an FTS5 query: every word must match, as a prefix.
+/// Words are quoted so FTS operators in user input are treated literally.
+fn fts_match_expression(query: &str) -> String {
+  query
+    .split_whitespace()
+    .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+    .collect::<Vec<_>>()
+    .join(" ")
+}
@@ -1,12 +1,11 @@
-use std::fs;
-use std::path::PathBuf;
+use std::collections::HashMap;
 
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 
 use crate::util::error::Result;
 
-use super::migrations;
+use super::open_project_connection;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScoredChunk {
@@ -15,6 +14,13 @@ pub struct ScoredChunk {
   pub score: f32,
 }
 
+/// Relevance score assigned to a chunk by the reranker for a given query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RerankScore {
+  pub relative_path: String,
+  pub score: f32,
+}
+
 #[derive(Debug)]
 pub struct EmbeddingDb {
   conn: Connection,
@@ -29,15 +35,7 @@ pub struct FileChunkInput {
 
 impl EmbeddingDb {
   pub fn open_for_project(project_root: &str) -> Result<Self> {
-    let db_path = database_path_for_project(project_root);
-    println!("[DB] Opening database at: {:?}", db_path);
-    if let Some(parent) = db_path.parent() {
-      fs::create_dir_all(parent)?;
-    }
-
-    let mut conn = Connection::open(&db_path)?;
-    migrations::run_migrations(&mut conn)?;
-
+    let conn = open_project_connection(project_root)?;
     Ok(Self { conn })
   }
 
@@ -63,6 +61,9 @@ impl EmbeddingDb {
     )?;
     tx.execute("DELETE FROM files WHERE project_root = ?1", params![project_root])?;
 
+    // Cached rerank scores refer to the old chunks, so they are stale now.
+    tx.execute("DELETE FROM rerank_cache WHERE project_root = ?1", params![project_root])?;
+
     // Reset any existing VSS index if present. This is best-effort and will
     // be ignored if the sqlite-vss virtual table is not available.
     let _ = tx.execute("DELETE FROM vss_chunks", []);
@@ -109,6 +110,104 @@ impl EmbeddingDb {
     Ok(())
   }
 
+  /// Point indexed files at their new location after `from`, a file or a
+  /// folder, was renamed to `to`. Paths use `/` separators; the indexer
+  /// stores native separators, so `\` paths from Windows match too.
+  pub fn rename_path(&mut self, project_root: &str, from: &str, to: &str) -> Result<usize> {
+    let tx = self.conn.transaction()?;
+    let moved = tx.execute(
+      "UPDATE files SET relative_path = ?3 || substr(REPLACE(relative_path, '\\', '/'), length(?2) + 1) \
+       WHERE project_root = ?1 AND (REPLACE(relative_path, '\\', '/') = ?2 \
+         OR substr(REPLACE(relative_path, '\\', '/'), 1, length(?2) + 1) = ?2 || '/')",
+      params![project_root, from, to],
+    )?;
+    // Cached rerank scores name files by their old paths.
+    tx.execute("DELETE FROM rerank_cache WHERE project_root = ?1", params![project_root])?;
+    tx.commit()?;
+    Ok(moved)
+  }
+
+  /// Drop `path`, and every file under it when it is a folder, from the
+  /// index. Returns the number of files removed.
+  pub fn remove_path(&mut self, project_root: &str, path: &str) -> Result<usize> {
+    const MATCHES: &str = "project_root = ?1 AND (REPLACE(relative_path, '\\', '/') = ?2 \
+       OR substr(REPLACE(relative_path, '\\', '/'), 1, length(?2) + 1) = ?2 || '/')";
+    let tx = self.conn.transaction()?;
+    // Best-effort, like the rest of the sqlite-vss handling.
+    let _ = tx.execute(
+      &format!(
+        "DELETE FROM vss_chunks WHERE rowid IN (SELECT c.id FROM chunks c JOIN files f ON c.file_id = f.id WHERE {MATCHES})"
+      ),
+      params![project_root, path],
+    );
+    tx.execute(
+      &format!(
+        "DELETE FROM embeddings WHERE chunk_id IN (SELECT c.id FROM chunks c JOIN files f ON c.file_id = f.id WHERE {MATCHES})"
+      ),
+      params![project_root, path],
+    )?;
+    tx.execute(
+      &format!("DELETE FROM chunks WHERE file_id IN (SELECT id FROM files WHERE {MATCHES})"),
+      params![project_root, path],
+    )?;
+    let removed = tx.execute(&format!("DELETE FROM files WHERE {MATCHES}"), params![project_root, path])?;
+    tx.execute("DELETE FROM rerank_cache WHERE project_root = ?1", params![project_root])?;
+    tx.commit()?;
+    Ok(removed)
+  }
+
+  /// Replace the stored text of an indexed file without re-embedding it,
+  /// for edits that do not change its meaning such as rewritten links.
+  pub fn update_file_content(&self, project_root: &str, path: &str, content: &str) -> Result<()> {
+    self.conn.execute(
+      "UPDATE chunks SET content = ?3 WHERE file_id IN \
+       (SELECT id FROM files WHERE project_root = ?1 AND REPLACE(relative_path, '\\', '/') = ?2)",
+      params![project_root, path, content],
+    )?;
+    Ok(())
+  }
+
+  /// Stored text of every indexed file, by path with `/` separators. Compared
+  /// with the file on disk to tell whether its embedding is current.
+  pub fn indexed_contents(&self, project_root: &str) -> Result<HashMap<String, String>> {
+    let mut stmt = self.conn.prepare(
+      "SELECT f.relative_path, c.content FROM chunks c JOIN files f ON c.file_id = f.id \
+       WHERE f.project_root = ?1 ORDER BY c.chunk_index",
+    )?;
+    let rows = stmt.query_map(params![project_root], |row| {
+      Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+    let mut contents: HashMap<String, String> = HashMap::new();
+    for row in rows {
+      let (path, content) = row?;
+      contents.entry(path.replace('\\', "/")).or_default().push_str(&content);
+    }
+    Ok(contents)
+  }
+
+  /// Chunk embeddings of every indexed file, by path with `/` separators,
+  /// in chunk order.
+  pub fn file_embeddings(&self, project_root: &str) -> Result<HashMap<String, Vec<Vec<f32>>>> {
+    let mut stmt = self.conn.prepare(
+      "SELECT f.relative_path, e.vector_json FROM files f \
+       JOIN chunks c ON c.file_id = f.id \
+       JOIN embeddings e ON e.chunk_id = c.id \
+       WHERE f.project_root = ?1 ORDER BY c.chunk_index",
+    )?;
+    let rows = stmt.query_map(params![project_root], |row| {
+      Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+    let mut embeddings: HashMap<String, Vec<Vec<f32>>> = HashMap::new();
+    for row in rows {
+      let (path, vector_json) = row?;
+      let embedding: Vec<f32> = serde_json::from_str(&vector_json)?;
+      if !embedding.is_empty() {
+        embeddings.entry(path.replace('\\', "/")).or_default().push(embedding);
+      }
+    }
+    Ok(embeddings)
+  }
+
   /// Naive cosine-similarity search over all embeddings for a project.
   ///
   /// This can later be swapped to sqlite-vss by creating a virtual table
@@ -161,6 +260,42 @@ impl EmbeddingDb {
     Ok(scored)
   }
 
+  /// Look up previously computed rerank scores for a query.
+  pub fn get_rerank_scores(
+    &self,
+    project_root: &str,
+    query: &str,
+    model: &str,
+  ) -> Result<Option<Vec<RerankScore>>> {
+    let mut stmt = self.conn.prepare(
+      "SELECT scores_json FROM rerank_cache WHERE project_root = ?1 AND query = ?2 AND model = ?3",
+    )?;
+    let mut rows = stmt.query(params![project_root, query, model])?;
+
+    match rows.next()? {
+      Some(row) => {
+        let scores_json: String = row.get(0)?;
+        Ok(Some(serde_json::from_str(&scores_json)?))
+      }
+      None => Ok(None),
+    }
+  }
+
+  pub fn store_rerank_scores(
+    &self,
+    project_root: &str,
+    query: &str,
+    model: &str,
+    scores: &[RerankScore],
+  ) -> Result<()> {
+    let scores_json = serde_json::to_string(scores)?;
+    self.conn.execute(
+      "INSERT OR REPLACE INTO rerank_cache (project_root, query, model, scores_json) VALUES (?1, ?2, ?3, ?4)",
+      params![project_root, query, model, scores_json],
+    )?;
+    Ok(())
+  }
+
   pub fn get_chunk_count(&self, project_root: &str) -> Result<usize> {
     println!("[DB] get_chunk_count for: {}", project_root);
     
@@ -189,13 +324,6 @@ impl EmbeddingDb {
   }
 }
 
-fn database_path_for_project(project_root: &str) -> PathBuf {
-  let mut path = PathBuf::from(project_root);
-  path.push(".codexlotus");
-  path.push("index.db");
-  path
-}
-
 fn insert_file(tx: &rusqlite::Transaction<'_>, project_root: &str, relative_path: &str) -> Result<i64> {
   tx.execute(
     "INSERT INTO files (project_root, relative_path) VALUES (?1, ?2)",
@@ -212,7 +340,7 @@ fn f32s_to_le_blob(vec: &[f32]) -> Vec<u8> {
   bytes
 }
 
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
   let dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
   let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
   let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection, Row};
+use serde::Serialize;
+
+use crate::project::links::LinkKind;
+use crate::util::error::Result;
+
+use super::open_project_connection;
+
+/// A link stored in the link index.
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexedLink {
+  /// File containing the link.
+  pub source: String,
+  /// 1-based line number.
+  pub line: usize,
+  /// 1-based column of the link target, counted in characters.
+  pub column: usize,
+  pub kind: LinkKind,
+  /// Target as written, without the anchor.
+  pub target: String,
+  pub anchor: Option<String>,
+  /// The line the link is on, trimmed.
+  pub context: String,
+  /// The file or folder the link points to, or `None` if it does not exist.
+  pub resolved: Option<String>,
+}
+
+/// Size and modification time of a file when its links were last read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp {
+  pub size: u64,
+  /// Milliseconds since the Unix epoch.
+  pub modified: i64,
+}
+
+/// Links of one file, read from disk.
+#[derive(Debug, Clone)]
+pub struct FileLinks {
+  pub path: String,
+  pub stamp: FileStamp,
+  /// Each link with the slug of the heading its anchor names.
+  pub links: Vec<(IndexedLink, Option<String>)>,
+}
+
+/// The link index in the project database.
+#[derive(Debug)]
+pub struct LinkDb {
+  conn: Connection,
+}
+
+const LINK_COLUMNS: &str = "source_path, line, col, kind, target, anchor, context, resolved_path";
+
+impl LinkDb {
+  pub fn open_for_project(project_root: &str) -> Result<Self> {
+    let conn = open_project_connection(project_root)?;
+    Ok(Self { conn })
+  }
+
+  /// Every file in the index with the stamp it was read at.
+  pub fn stamps(&self) -> Result<HashMap<String, FileStamp>> {
+    let mut stmt = self
+      .conn
+      .prepare("SELECT relative_path, size, modified FROM link_files")?;
+    let stamps = stmt
+      .query_map([], |row| {
+        Ok((
+          row.get::<_, String>(0)?,
+          FileStamp {
+            size: row.get::<_, i64>(1)? as u64,
+            modified: row.get(2)?,
+          },
+        ))
+      })?
+      .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+    Ok(stamps)
+  }
+
+  /// Id, source, kind and target of every stored link, for resolving them
+  /// again after files were added or removed.
+  pub fn targets(&self) -> Result<Vec<(i64, String, LinkKind, String)>> {
+    let mut stmt = self
+      .conn
+      .prepare("SELECT id, source_path, kind, target FROM links")?;
+    let targets = stmt
+      .query_map([], |row| {
+        Ok((
+          row.get(0)?,
+          row.get(1)?,
+          parse_kind(&row.get::<_, String>(2)?),
+          row.get(3)?,
+        ))
+      })?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(targets)
+  }
+
+  /// Drop `removed` files, replace the links of `files`, and store new
+  /// resolved paths for existing links, in one transaction.
+  pub fn update(
+    &mut self,
+    removed: &[String],
+    files: &[FileLinks],
+    resolutions: &[(i64, Option<String>)],
+  ) -> Result<()> {
+    let tx = self.conn.transaction()?;
+    for path in removed {
+      tx.execute("DELETE FROM links WHERE source_path = ?1", params![path])?;
+      tx.execute("DELETE FROM link_files WHERE relative_path = ?1", params![path])?;
+    }
+    for (id, resolved) in resolutions {
+      tx.execute(
+        "UPDATE links SET resolved_path = ?2 WHERE id = ?1",
+        params![id, resolved],
+      )?;
+    }
+    for file in files {
+      tx.execute("DELETE FROM links WHERE source_path = ?1", params![file.path])?;
+      tx.execute(
+        "INSERT INTO link_files (relative_path, size, modified) VALUES (?1, ?2, ?3) \
+         ON CONFLICT(relative_path) DO UPDATE SET size = ?2, modified = ?3",
+        params![file.path, file.stamp.size as i64, file.stamp.modified],
+      )?;
+      for (link, anchor_slug) in &file.links {
+        tx.execute(
+          "INSERT INTO links (source_path, line, col, kind, target, anchor, anchor_slug, context, resolved_path) \
+           VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+          params![
+            file.path,
+            link.line as i64,
+            link.column as i64,
+            kind_str(link.kind),
+            link.target,
+            link.anchor,
+            anchor_slug,
+            link.context,
+            link.resolved,
+          ],
+        )?;
+      }
+    }
+    tx.commit()?;
+    Ok(())
+  }
+
+  /// Links pointing at `path`, or only at the heading with slug
+  /// `anchor_slug` in it, ordered by source file and line.
+  pub fn backlinks(&self, path: &str, anchor_slug: Option<&str>) -> Result<Vec<IndexedLink>> {
+    let mut stmt = self.conn.prepare(&format!(
+      "SELECT {LINK_COLUMNS} FROM links WHERE resolved_path = ?1 AND (?2 IS NULL OR anchor_slug = ?2) \
+       ORDER BY source_path, line, col"
+    ))?;
+    let links = stmt
+      .query_map(params![path, anchor_slug], link_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(links)
+  }
+
+  /// Links in `path`, in document order.
+  pub fn outgoing(&self, path: &str) -> Result<Vec<IndexedLink>> {
+    let mut stmt = self.conn.prepare(&format!(
+      "SELECT {LINK_COLUMNS} FROM links WHERE source_path = ?1 ORDER BY line, col"
+    ))?;
+    let links = stmt
+      .query_map(params![path], link_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(links)
+  }
+
+  /// Links whose target exists, ordered by source file and line.
+  pub fn resolved(&self) -> Result<Vec<IndexedLink>> {
+    let mut stmt = self.conn.prepare(&format!(
+      "SELECT {LINK_COLUMNS} FROM links WHERE resolved_path IS NOT NULL ORDER BY source_path, line, col"
+    ))?;
+    let links = stmt
+      .query_map([], link_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(links)
+  }
+
+  /// Links whose target does not exist, ordered by source file and line.
+  pub fn unresolved(&self) -> Result<Vec<IndexedLink>> {
+    let mut stmt = self.conn.prepare(&format!(
+      "SELECT {LINK_COLUMNS} FROM links WHERE resolved_path IS NULL ORDER BY source_path, line, col"
+    ))?;
+    let links = stmt
+      .query_map([], link_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(links)
+  }
+}
+
+fn kind_str(kind: LinkKind) -> &'static str {
+  match kind {
+    LinkKind::Wiki => "wiki",
+    LinkKind::Markdown => "markdown",
+  }
+}
+
+fn parse_kind(value: &str) -> LinkKind {
+  match value {
+    "wiki" => LinkKind::Wiki,
+    _ => LinkKind::Markdown,
+  }
+}
+
+fn link_from_row(row: &Row<'_>) -> rusqlite::Result<IndexedLink> {
+  Ok(IndexedLink {
+    source: row.get(0)?,
+    line: row.get::<_, i64>(1)? as usize,
+    column: row.get::<_, i64>(2)? as usize,
+    kind: parse_kind(&row.get::<_, String>(3)?),
+    target: row.get(4)?,
+    anchor: row.get(5)?,
+    context: row.get(6)?,
+    resolved: row.get(7)?,
+  })
+}
@@ -32,6 +32,101 @@ pub fn run_migrations(conn: &mut Connection) -> Result<()> {
     );
 
     CREATE INDEX IF NOT EXISTS idx_chunks_file_id ON chunks(file_id);
+
+    CREATE TABLE IF NOT EXISTS rerank_cache (
+      project_root TEXT NOT NULL,
+      query TEXT NOT NULL,
+      model TEXT NOT NULL,
+      scores_json TEXT NOT NULL,
+      PRIMARY KEY(project_root, query, model)
+    );
+
+    CREATE TABLE IF NOT EXISTS chat_sessions (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      title TEXT NOT NULL,
+      forked_from INTEGER REFERENCES chat_sessions(id) ON DELETE SET NULL,
+      created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+      updated_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+    );
+
+    CREATE TABLE IF NOT EXISTS chat_messages (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      session_id INTEGER NOT NULL REFERENCES chat_sessions(id) ON DELETE CASCADE,
+      role TEXT NOT NULL,
+      content TEXT NOT NULL,
+      created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_chat_messages_session_id ON chat_messages(session_id);
+
+    -- Rolling summary of the older part of a session, see ai::compaction.
+    CREATE TABLE IF NOT EXISTS chat_session_summaries (
+      session_id INTEGER PRIMARY KEY REFERENCES chat_sessions(id) ON DELETE CASCADE,
+      summary TEXT NOT NULL,
+      covers_through_message_id INTEGER NOT NULL,
+      updated_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+    );
+
+    -- Full-text index over message content, kept in sync by triggers.
+    CREATE VIRTUAL TABLE IF NOT EXISTS chat_messages_fts USING fts5(
+      content,
+      content = 'chat_messages',
+      content_rowid = 'id'
+    );
+
+    CREATE TRIGGER IF NOT EXISTS chat_messages_fts_insert AFTER INSERT ON chat_messages BEGIN
+      INSERT INTO chat_messages_fts(rowid, content) VALUES (new.id, new.content);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS chat_messages_fts_delete AFTER DELETE ON chat_messages BEGIN
+      INSERT INTO chat_messages_fts(chat_messages_fts, rowid, content) VALUES ('delete', old.id, old.content);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS chat_messages_fts_update AFTER UPDATE ON chat_messages BEGIN
+      INSERT INTO chat_messages_fts(chat_messages_fts, rowid, content) VALUES ('delete', old.id, old.content);
+      INSERT INTO chat_messages_fts(rowid, content) VALUES (new.id, new.content);
+    END;
+
+    -- Per-file version history; contents live in .codexlotus/snapshots by hash.
+    CREATE TABLE IF NOT EXISTS file_snapshots (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      relative_path TEXT NOT NULL,
+      content_hash TEXT NOT NULL,
+      size INTEGER NOT NULL,
+      source TEXT NOT NULL,
+      label TEXT,
+      created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_file_snapshots_path ON file_snapshots(relative_path, id);
+    CREATE INDEX IF NOT EXISTS idx_file_snapshots_hash ON file_snapshots(content_hash);
+
+    -- Link index, refreshed from disk by project::link_index. Every project
+    -- file is listed so that renames and deletions are noticed; only
+    -- markdown files have links.
+    CREATE TABLE IF NOT EXISTS link_files (
+      relative_path TEXT PRIMARY KEY,
+      size INTEGER NOT NULL,
+      modified INTEGER NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS links (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      source_path TEXT NOT NULL REFERENCES link_files(relative_path) ON DELETE CASCADE,
+      line INTEGER NOT NULL,
+      col INTEGER NOT NULL,
+      kind TEXT NOT NULL,
+      target TEXT NOT NULL,
+      anchor TEXT,
+      -- Slug of the heading the anchor names, for heading backlinks.
+      anchor_slug TEXT,
+      context TEXT NOT NULL,
+      -- NULL when the target does not exist.
+      resolved_path TEXT
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_links_source ON links(source_path);
+    CREATE INDEX IF NOT EXISTS idx_links_resolved ON links(resolved_path, anchor_slug);
     "#,
   )?;
 
@@ -1,2 +1,32 @@
+use std::fs;
+use std::path::PathBuf;
+
+use rusqlite::Connection;
+
+use crate::util::error::Result;
+
+pub mod chat_sessions;
 pub mod embeddings;
+pub mod links;
 pub mod migrations;
+pub mod snapshots;
+
+/// Open (and migrate) the per-project database at `.codexlotus/index.db`.
+pub fn open_project_connection(project_root: &str) -> Result<Connection> {
+  let db_path = database_path_for_project(project_root);
+  println!("[DB] Opening database at: {:?}", db_path);
+  if let Some(parent) = db_path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+
+  let mut conn = Connection::open(&db_path)?;
+  migrations::run_migrations(&mut conn)?;
+  Ok(conn)
+}
+
+fn database_path_for_project(project_root: &str) -> PathBuf {
+  let mut path = PathBuf::from(project_root);
+  path.push(".codexlotus");
+  path.push("index.db");
+  path
+}
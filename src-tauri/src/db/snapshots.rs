@@ -0,0 +1,176 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+
+use crate::util::error::{Error, Result};
+
+use super::open_project_connection;
+
+/// What produced a snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotSource {
+  /// Contents found on disk that no earlier snapshot covers: the file as it
+  /// was before its first tracked save, or after a change made by another
+  /// program.
+  External,
+  Save,
+  AiEdit,
+  Undo,
+  Redo,
+  Restore,
+  /// Project-wide find and replace.
+  Replace,
+  /// Links rewritten when a file or folder was renamed.
+  Rename,
+}
+
+impl SnapshotSource {
+  fn as_str(self) -> &'static str {
+    match self {
+      SnapshotSource::External => "external",
+      SnapshotSource::Save => "save",
+      SnapshotSource::AiEdit => "ai_edit",
+      SnapshotSource::Undo => "undo",
+      SnapshotSource::Redo => "redo",
+      SnapshotSource::Restore => "restore",
+      SnapshotSource::Replace => "replace",
+      SnapshotSource::Rename => "rename",
+    }
+  }
+
+  fn parse(value: &str) -> Self {
+    match value {
+      "save" => SnapshotSource::Save,
+      "ai_edit" => SnapshotSource::AiEdit,
+      "undo" => SnapshotSource::Undo,
+      "redo" => SnapshotSource::Redo,
+      "restore" => SnapshotSource::Restore,
+      "replace" => SnapshotSource::Replace,
+      "rename" => SnapshotSource::Rename,
+      _ => SnapshotSource::External,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileSnapshot {
+  pub id: i64,
+  pub path: String,
+  pub content_hash: String,
+  pub size: u64,
+  pub source: SnapshotSource,
+  pub label: Option<String>,
+  pub created_at: i64,
+}
+
+/// Index of file snapshots in the project database. The contents themselves
+/// are stored by `project::snapshots`.
+#[derive(Debug)]
+pub struct SnapshotDb {
+  conn: Connection,
+}
+
+const SNAPSHOT_COLUMNS: &str = "id, relative_path, content_hash, size, source, label, created_at";
+
+impl SnapshotDb {
+  pub fn open_for_project(project_root: &str) -> Result<Self> {
+    let conn = open_project_connection(project_root)?;
+    Ok(Self { conn })
+  }
+
+  pub fn insert(
+    &self,
+    path: &str,
+    content_hash: &str,
+    size: u64,
+    source: SnapshotSource,
+    label: Option<&str>,
+  ) -> Result<FileSnapshot> {
+    self.conn.execute(
+      "INSERT INTO file_snapshots (relative_path, content_hash, size, source, label) \
+       VALUES (?1, ?2, ?3, ?4, ?5)",
+      params![path, content_hash, size as i64, source.as_str(), label],
+    )?;
+    self.get(self.conn.last_insert_rowid())
+  }
+
+  pub fn get(&self, id: i64) -> Result<FileSnapshot> {
+    self
+      .conn
+      .query_row(
+        &format!("SELECT {SNAPSHOT_COLUMNS} FROM file_snapshots WHERE id = ?1"),
+        params![id],
+        snapshot_from_row,
+      )
+      .optional()?
+      .ok_or_else(|| Error::Anyhow(anyhow::anyhow!("Snapshot {id} not found")))
+  }
+
+  pub fn latest_for_path(&self, path: &str) -> Result<Option<FileSnapshot>> {
+    let snapshot = self
+      .conn
+      .query_row(
+        &format!(
+          "SELECT {SNAPSHOT_COLUMNS} FROM file_snapshots WHERE relative_path = ?1 \
+           ORDER BY id DESC LIMIT 1"
+        ),
+        params![path],
+        snapshot_from_row,
+      )
+      .optional()?;
+    Ok(snapshot)
+  }
+
+  /// Snapshots of one file, newest first.
+  pub fn list_for_path(&self, path: &str) -> Result<Vec<FileSnapshot>> {
+    let mut stmt = self.conn.prepare(&format!(
+      "SELECT {SNAPSHOT_COLUMNS} FROM file_snapshots WHERE relative_path = ?1 ORDER BY id DESC"
+    ))?;
+    let snapshots = stmt
+      .query_map(params![path], snapshot_from_row)?
+      .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(snapshots)
+  }
+
+  /// Move the history of `from`, and of every file under it when it is a
+  /// folder, to the corresponding path under `to`.
+  pub fn rename_path(&self, from: &str, to: &str) -> Result<usize> {
+    let moved = self.conn.execute(
+      "UPDATE file_snapshots SET relative_path = ?2 || substr(relative_path, length(?1) + 1) \
+       WHERE relative_path = ?1 OR substr(relative_path, 1, length(?1) + 1) = ?1 || '/'",
+      params![from, to],
+    )?;
+    Ok(moved)
+  }
+
+  pub fn delete(&mut self, ids: &[i64]) -> Result<()> {
+    let tx = self.conn.transaction()?;
+    for id in ids {
+      tx.execute("DELETE FROM file_snapshots WHERE id = ?1", params![id])?;
+    }
+    tx.commit()?;
+    Ok(())
+  }
+
+  /// Whether any snapshot, of any file, still refers to `content_hash`.
+  pub fn hash_in_use(&self, content_hash: &str) -> Result<bool> {
+    let in_use = self.conn.query_row(
+      "SELECT EXISTS(SELECT 1 FROM file_snapshots WHERE content_hash = ?1)",
+      params![content_hash],
+      |row| row.get(0),
+    )?;
+    Ok(in_use)
+  }
+}
+
+fn snapshot_from_row(row: &Row<'_>) -> rusqlite::Result<FileSnapshot> {
+  Ok(FileSnapshot {
+    id: row.get(0)?,
+    path: row.get(1)?,
+    content_hash: row.get(2)?,
+    size: row.get::<_, i64>(3)? as u64,
+    source: SnapshotSource::parse(&row.get::<_, String>(4)?),
+    label: row.get(5)?,
+    created_at: row.get(6)?,
+  })
+}
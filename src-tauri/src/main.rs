@@ -6,17 +6,43 @@ mod ai;
 mod project;
 mod util;
 
-use commands::{files, settings, ai as ai_cmd, rag};
+use commands::{files, settings, ai as ai_cmd, rag, chat, history, git, search, trash, links, graph, tags};
 
 fn main() {
   tauri::Builder::default()
     .invoke_handler(tauri::generate_handler![
       files::list_markdown_files,
+      files::get_project_tree,
       files::read_file,
+      files::read_file_versioned,
       files::write_file,
       files::create_directory,
+      files::create_project_root,
       files::copy_file,
+      files::import_files,
       files::list_files_in_dir,
+      files::move_path,
+      files::rename_path,
+      trash::delete_path,
+      trash::list_trash,
+      trash::restore_from_trash,
+      trash::purge_trash,
+      links::check_links,
+      links::get_backlinks,
+      links::get_outgoing_links,
+      links::get_unresolved_links,
+      graph::get_knowledge_graph,
+      graph::get_lore_graph,
+      graph::graph_neighbours,
+      graph::graph_shortest_path,
+      graph::graph_orphans,
+      graph::graph_hubs,
+      graph::graph_components,
+      graph::export_graph,
+      tags::detect_mentions,
+      tags::suggest_semantic_tags,
+      files::apply_file_patch,
+      files::merge_file_edit,
       settings::save_settings,
       settings::load_settings,
       settings::save_api_key,
@@ -25,7 +51,32 @@ fn main() {
       ai_cmd::ai_file_edit,
       rag::initialize_project_index,
       rag::rag_query,
-      rag::get_index_stats
+      rag::get_index_stats,
+      chat::create_chat_session,
+      chat::list_chat_sessions,
+      chat::get_chat_session_messages,
+      chat::rename_chat_session,
+      chat::delete_chat_session,
+      chat::fork_chat_session,
+      chat::search_chat_sessions,
+      history::apply_edit_transaction,
+      history::undo_edit_transaction,
+      history::redo_edit_transaction,
+      history::list_edit_transactions,
+      history::list_file_versions,
+      history::diff_file_versions,
+      history::restore_file_version,
+      git::git_status,
+      git::git_stage,
+      git::git_unstage,
+      git::git_commit,
+      git::git_diff,
+      git::git_log,
+      git::git_show_file,
+      git::git_checkout_file,
+      search::search_project,
+      search::preview_replace,
+      search::apply_replace
     ])
     .run(tauri::generate_context!())
     .expect("error while running CodexLotus app");
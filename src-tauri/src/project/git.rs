@@ -0,0 +1,466 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use git2::{
+  Commit, Delta, DiffFormat, DiffOptions, ErrorCode, IndexAddOption, Repository, Sort, Status,
+  StatusOptions,
+};
+use serde::Serialize;
+
+use crate::util::error::{Error, Result};
+
+use super::paths::{resolve_project_path, Access};
+
+/// App data that lives in the project but is never shown as a git change.
+const APP_DIR: &str = ".codexlotus";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GitChange {
+  Added,
+  Modified,
+  Deleted,
+  Renamed,
+  TypeChange,
+  Untracked,
+  Conflicted,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GitFileStatus {
+  /// Relative to the project root.
+  pub path: String,
+  /// Change recorded in the index, i.e. what the next commit will contain.
+  pub staged: Option<GitChange>,
+  /// Change in the working tree that is not staged yet.
+  pub unstaged: Option<GitChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GitStatus {
+  /// `None` when HEAD is detached.
+  pub branch: Option<String>,
+  /// `None` before the first commit.
+  pub head: Option<String>,
+  pub files: Vec<GitFileStatus>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GitCommit {
+  pub id: String,
+  pub short_id: String,
+  pub summary: String,
+  pub message: String,
+  pub author_name: String,
+  pub author_email: String,
+  /// Seconds since the Unix epoch.
+  pub time: i64,
+}
+
+impl From<&Commit<'_>> for GitCommit {
+  fn from(commit: &Commit<'_>) -> Self {
+    let id = commit.id().to_string();
+    let author = commit.author();
+    Self {
+      short_id: id.chars().take(7).collect(),
+      id,
+      summary: commit.summary().unwrap_or_default().to_string(),
+      message: commit.message().unwrap_or_default().to_string(),
+      author_name: author.name().unwrap_or_default().to_string(),
+      author_email: author.email().unwrap_or_default().to_string(),
+      time: commit.time().seconds(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GitDiffFile {
+  pub path: String,
+  pub change: GitChange,
+  pub insertions: usize,
+  pub deletions: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GitDiff {
+  /// Unified diff of HEAD against the working tree, including unstaged and
+  /// untracked changes.
+  pub patch: String,
+  pub files: Vec<GitDiffFile>,
+}
+
+/// The git repository containing a project. The project may be the whole
+/// work tree or a folder inside it; either way paths passed in and returned
+/// are relative to the project root, and files outside it are left out.
+pub struct ProjectRepo {
+  repo: Repository,
+  project_root: String,
+  workdir: PathBuf,
+  /// The project root relative to the work tree; empty when they are the same.
+  prefix: PathBuf,
+}
+
+impl ProjectRepo {
+  pub fn open(project_root: &str) -> Result<Self> {
+    let repo = Repository::discover(project_root).map_err(|err| match err.code() {
+      ErrorCode::NotFound => Error::Anyhow(anyhow::anyhow!(
+        "'{project_root}' is not inside a git repository"
+      )),
+      _ => Error::Git(err),
+    })?;
+    let workdir = repo
+      .workdir()
+      .ok_or_else(|| Error::Anyhow(anyhow::anyhow!("The git repository has no working tree")))?;
+    let workdir = fs::canonicalize(workdir)?;
+    let prefix = fs::canonicalize(project_root)?
+      .strip_prefix(&workdir)
+      .map(Path::to_path_buf)
+      .map_err(|_| Error::Anyhow(anyhow::anyhow!("'{project_root}' is outside the git work tree")))?;
+    Ok(Self {
+      repo,
+      project_root: project_root.to_string(),
+      workdir,
+      prefix,
+    })
+  }
+
+  pub fn status(&self) -> Result<GitStatus> {
+    let mut opts = StatusOptions::new();
+    opts
+      .include_untracked(true)
+      .recurse_untracked_dirs(true)
+      .include_ignored(false)
+      .exclude_submodules(true);
+
+    let mut files = Vec::new();
+    for entry in self.repo.statuses(Some(&mut opts))?.iter() {
+      let Some(path) = entry.path().and_then(|p| self.project_path(Path::new(p))) else {
+        continue;
+      };
+      let status = entry.status();
+      let (staged, unstaged) = if status.contains(Status::CONFLICTED) {
+        (Some(GitChange::Conflicted), Some(GitChange::Conflicted))
+      } else {
+        (index_change(status), worktree_change(status))
+      };
+      if staged.is_some() || unstaged.is_some() {
+        files.push(GitFileStatus {
+          path,
+          staged,
+          unstaged,
+        });
+      }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(GitStatus {
+      branch: self.branch_name()?,
+      head: self
+        .head_commit()?
+        .map(|commit| GitCommit::from(&commit).short_id),
+      files,
+    })
+  }
+
+  /// Stage files or folders. Deleted files are staged as deletions.
+  pub fn stage(&self, paths: &[String]) -> Result<()> {
+    let mut index = self.repo.index()?;
+    for path in paths {
+      let repo_path = self.repo_path(path, Access::Write)?;
+      let absolute = self.workdir.join(&repo_path);
+      if absolute.is_dir() {
+        let spec = [pathspec(&repo_path)];
+        // Staging the project folder must not pick up app data, which
+        // `status` never shows. A non-zero return skips the path.
+        let mut skip_app_data = |path: &Path, _: &[u8]| i32::from(self.is_app_data(path));
+        index.add_all(spec.iter(), IndexAddOption::DEFAULT, Some(&mut skip_app_data))?;
+        index.update_all(spec.iter(), Some(&mut skip_app_data))?;
+      } else if absolute.exists() {
+        index.add_path(&repo_path)?;
+      } else {
+        index.remove_path(&repo_path)?;
+      }
+    }
+    index.write()?;
+    Ok(())
+  }
+
+  /// Put the index entries for `paths` back to their state in HEAD, keeping
+  /// the working tree as it is.
+  pub fn unstage(&self, paths: &[String]) -> Result<()> {
+    let specs = paths
+      .iter()
+      .map(|path| self.repo_path(path, Access::Write).map(|p| pathspec(&p)))
+      .collect::<Result<Vec<_>>>()?;
+    match self.head_commit()? {
+      Some(head) => self.repo.reset_default(Some(head.as_object()), specs.iter())?,
+      None => {
+        // Before the first commit there is nothing to reset to.
+        let mut index = self.repo.index()?;
+        for spec in &specs {
+          index.remove_all([spec].iter(), None)?;
+        }
+        index.write()?;
+      }
+    }
+    Ok(())
+  }
+
+  /// Commit whatever is staged.
+  pub fn commit(&self, message: &str) -> Result<GitCommit> {
+    let message = message.trim();
+    if message.is_empty() {
+      return Err(Error::Anyhow(anyhow::Error::msg("The commit message is empty")));
+    }
+    let signature = self.repo.signature().map_err(|_| {
+      Error::Anyhow(anyhow::Error::msg(
+        "Set user.name and user.email in your git config before committing",
+      ))
+    })?;
+
+    let mut index = self.repo.index()?;
+    if index.has_conflicts() {
+      return Err(Error::Anyhow(anyhow::Error::msg(
+        "Resolve the merge conflicts before committing",
+      )));
+    }
+    let tree = self.repo.find_tree(index.write_tree()?)?;
+    let parent = self.head_commit()?;
+    let nothing_staged = match &parent {
+      Some(parent) => parent.tree_id() == tree.id(),
+      None => index.is_empty(),
+    };
+    if nothing_staged {
+      return Err(Error::Anyhow(anyhow::Error::msg("Nothing is staged to commit")));
+    }
+
+    let parents: Vec<&Commit<'_>> = parent.iter().collect();
+    let id = self
+      .repo
+      .commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)?;
+    Ok(GitCommit::from(&self.repo.find_commit(id)?))
+  }
+
+  /// Diff of HEAD against the working tree for one file, or for the whole
+  /// project when `path` is `None`.
+  pub fn diff_head(&self, path: Option<&str>) -> Result<GitDiff> {
+    let head_tree = self.head_commit()?.map(|c| c.tree()).transpose()?;
+    let mut opts = DiffOptions::new();
+    opts
+      .include_untracked(true)
+      .recurse_untracked_dirs(true)
+      .show_untracked_content(true);
+    let scope = match path {
+      Some(path) => Some(self.repo_path(path, Access::Read)?),
+      None if self.prefix.as_os_str().is_empty() => None,
+      None => Some(self.prefix.clone()),
+    };
+    if let Some(scope) = &scope {
+      opts.pathspec(pathspec(scope)).disable_pathspec_match(true);
+    }
+
+    let diff = self
+      .repo
+      .diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut opts))?;
+    let mut patch = String::new();
+    let mut files: Vec<GitDiffFile> = Vec::new();
+    diff.print(DiffFormat::Patch, |delta, _hunk, line| {
+      let Some(path) = delta
+        .new_file()
+        .path()
+        .or_else(|| delta.old_file().path())
+        .and_then(|p| self.project_path(p))
+      else {
+        return true;
+      };
+      if files.last().map(|f| &f.path) != Some(&path) {
+        files.push(GitDiffFile {
+          path,
+          change: delta_change(delta.status()),
+          insertions: 0,
+          deletions: 0,
+        });
+      }
+      let file = files.last_mut().expect("file entry pushed above");
+      match line.origin() {
+        '+' => file.insertions += 1,
+        '-' => file.deletions += 1,
+        _ => {}
+      }
+      if matches!(line.origin(), '+' | '-' | ' ') {
+        patch.push(line.origin());
+      }
+      patch.push_str(&String::from_utf8_lossy(line.content()));
+      true
+    })?;
+
+    Ok(GitDiff { patch, files })
+  }
+
+  /// Newest commits first, limited to those that touch `path` (or the
+  /// project folder, when it is not the whole work tree).
+  pub fn log(&self, path: Option<&str>, limit: usize) -> Result<Vec<GitCommit>> {
+    if self.head_commit()?.is_none() {
+      return Ok(Vec::new());
+    }
+    let scope = match path {
+      Some(path) => Some(self.repo_path(path, Access::Read)?),
+      None if self.prefix.as_os_str().is_empty() => None,
+      None => Some(self.prefix.clone()),
+    };
+
+    let mut walk = self.repo.revwalk()?;
+    walk.push_head()?;
+    walk.set_sorting(Sort::TIME)?;
+
+    let mut commits = Vec::new();
+    for id in walk {
+      let commit = self.repo.find_commit(id?)?;
+      if let Some(scope) = &scope {
+        if !self.touches(&commit, scope)? {
+          continue;
+        }
+      }
+      commits.push(GitCommit::from(&commit));
+      if commits.len() >= limit {
+        break;
+      }
+    }
+    Ok(commits)
+  }
+
+  /// Contents of `path` as of `revision` (a commit id, branch or other
+  /// revspec). `None` when the file did not exist in that commit.
+  pub fn file_at(&self, path: &str, revision: &str) -> Result<Option<String>> {
+    let repo_path = self.repo_path(path, Access::Read)?;
+    let commit = self.repo.revparse_single(revision)?.peel_to_commit()?;
+    let entry = match commit.tree()?.get_path(&repo_path) {
+      Ok(entry) => entry,
+      Err(err) if err.code() == ErrorCode::NotFound => return Ok(None),
+      Err(err) => return Err(err.into()),
+    };
+    let blob = entry.to_object(&self.repo)?.peel_to_blob()?;
+    let contents = String::from_utf8(blob.content().to_vec()).map_err(|_| {
+      Error::Anyhow(anyhow::anyhow!("'{path}' is not a text file in {revision}"))
+    })?;
+    Ok(Some(contents))
+  }
+
+  /// Whether `commit` changed anything under `scope` compared to its first
+  /// parent.
+  fn touches(&self, commit: &Commit<'_>, scope: &Path) -> Result<bool> {
+    let entry_id = |commit: &Commit<'_>| -> Result<Option<git2::Oid>> {
+      match commit.tree()?.get_path(scope) {
+        Ok(entry) => Ok(Some(entry.id())),
+        Err(err) if err.code() == ErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+      }
+    };
+    let current = entry_id(commit)?;
+    let previous = match commit.parents().next() {
+      Some(parent) => entry_id(&parent)?,
+      None => None,
+    };
+    Ok(current != previous)
+  }
+
+  fn head_commit(&self) -> Result<Option<Commit<'_>>> {
+    match self.repo.head() {
+      Ok(head) => Ok(Some(head.peel_to_commit()?)),
+      Err(err) if matches!(err.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => Ok(None),
+      Err(err) => Err(err.into()),
+    }
+  }
+
+  fn branch_name(&self) -> Result<Option<String>> {
+    if self.repo.head_detached()? {
+      return Ok(None);
+    }
+    // Read HEAD itself rather than resolving it, which fails before the
+    // first commit.
+    let head = self.repo.find_reference("HEAD")?;
+    Ok(head
+      .symbolic_target()
+      .map(|target| target.trim_start_matches("refs/heads/").to_string()))
+  }
+
+  /// A path from the frontend, checked against the project sandbox and made
+  /// relative to the work tree. With `Access::Write`, paths in `.git` and
+  /// the app data folder are refused.
+  fn repo_path(&self, path: &str, access: Access) -> Result<PathBuf> {
+    let resolved = resolve_project_path(&self.project_root, path, access)?;
+    resolved
+      .strip_prefix(&self.workdir)
+      .map(Path::to_path_buf)
+      .map_err(|_| Error::Anyhow(anyhow::anyhow!("'{path}' is outside the git work tree")))
+  }
+
+  /// Whether a work-tree path is inside the project's app data folder.
+  fn is_app_data(&self, repo_path: &Path) -> bool {
+    repo_path
+      .strip_prefix(&self.prefix)
+      .ok()
+      .and_then(|relative| relative.components().next())
+      == Some(Component::Normal(APP_DIR.as_ref()))
+  }
+
+  /// A work-tree path as a project path, or `None` if it is outside the
+  /// project or app data.
+  fn project_path(&self, repo_path: &Path) -> Option<String> {
+    let relative = repo_path.strip_prefix(&self.prefix).ok()?;
+    match relative.components().next() {
+      Some(Component::Normal(first)) if first != APP_DIR => {}
+      _ => return None,
+    }
+    Some(relative.to_string_lossy().replace('\\', "/"))
+  }
+}
+
+/// Git pathspecs always use `/`.
+fn pathspec(path: &Path) -> String {
+  path.to_string_lossy().replace('\\', "/")
+}
+
+fn index_change(status: Status) -> Option<GitChange> {
+  if status.contains(Status::INDEX_NEW) {
+    Some(GitChange::Added)
+  } else if status.contains(Status::INDEX_MODIFIED) {
+    Some(GitChange::Modified)
+  } else if status.contains(Status::INDEX_DELETED) {
+    Some(GitChange::Deleted)
+  } else if status.contains(Status::INDEX_RENAMED) {
+    Some(GitChange::Renamed)
+  } else if status.contains(Status::INDEX_TYPECHANGE) {
+    Some(GitChange::TypeChange)
+  } else {
+    None
+  }
+}
+
+fn worktree_change(status: Status) -> Option<GitChange> {
+  if status.contains(Status::WT_NEW) {
+    Some(GitChange::Untracked)
+  } else if status.contains(Status::WT_MODIFIED) {
+    Some(GitChange::Modified)
+  } else if status.contains(Status::WT_DELETED) {
+    Some(GitChange::Deleted)
+  } else if status.contains(Status::WT_RENAMED) {
+    Some(GitChange::Renamed)
+  } else if status.contains(Status::WT_TYPECHANGE) {
+    Some(GitChange::TypeChange)
+  } else {
+    None
+  }
+}
+
+fn delta_change(delta: Delta) -> GitChange {
+  match delta {
+    Delta::Added | Delta::Copied => GitChange::Added,
+    Delta::Deleted => GitChange::Deleted,
+    Delta::Renamed => GitChange::Renamed,
+    Delta::Typechange => GitChange::TypeChange,
+    Delta::Untracked => GitChange::Untracked,
+    Delta::Conflicted => GitChange::Conflicted,
+    _ => GitChange::Modified,
+  }
+}
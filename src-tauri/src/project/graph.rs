@@ -0,0 +1,546 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::links::LinkDb;
+use crate::util::error::{Error, Result};
+
+use super::link_index::refresh_link_index;
+use super::links::{anchor_heading, markdown_stem, project_files};
+use super::markdown::{document_tags, heading_slugs, parse_codex_blocks, parse_headings, slugify, split_frontmatter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+  /// A markdown file; its id is its project-relative path.
+  File,
+  /// A heading in a file: `heading:<path>#<slug>`.
+  Heading,
+  /// A frontmatter or inline tag: `tag:#<tag>`.
+  Tag,
+  /// A named codex or stat block: `entity:<lowercased name>`.
+  Entity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+  /// A wiki or markdown link, to a file or to one of its headings.
+  Link,
+  /// A file to each of its headings.
+  Heading,
+  /// A file to each tag it carries.
+  Tag,
+  /// Two tags carried by the same files; the weight is how many.
+  CoOccurrence,
+  /// A file to each codex entity it describes.
+  Defines,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphNode {
+  pub id: String,
+  pub kind: NodeKind,
+  /// File name without extension, heading text, `#tag` or entity name.
+  pub name: String,
+  /// File the node belongs to; for an entity, the first file describing it.
+  pub path: Option<String>,
+  /// 1-based line of a heading or entity in `path`.
+  pub line: Option<usize>,
+  /// Top-level folder of the file, or `root`, `_tags`, `_entities`.
+  pub group: String,
+  /// Number of edges other than file-to-heading ones.
+  pub degree: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphEdge {
+  pub source: String,
+  pub target: String,
+  pub kind: EdgeKind,
+  /// Links between the same pair, or files shared by two tags.
+  pub weight: usize,
+}
+
+/// Part of the graph: the nodes a query returned and the edges among them.
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphView {
+  pub nodes: Vec<GraphNode>,
+  pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Neighbour {
+  #[serde(flatten)]
+  pub node: GraphNode,
+  /// Hops from the starting node.
+  pub distance: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Neighbourhood {
+  pub nodes: Vec<Neighbour>,
+  pub edges: Vec<GraphEdge>,
+}
+
+/// The graph in the shape `react-force-graph` takes, as drawn by the lore
+/// map. Heading nodes are folded into their files.
+#[derive(Debug, Clone, Serialize)]
+pub struct ForceGraph {
+  pub nodes: Vec<ForceNode>,
+  pub links: Vec<ForceLink>,
+}
+
+/// Everything the lore map shows, from one build of the graph.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoreMap {
+  #[serde(flatten)]
+  pub graph: ForceGraph,
+  /// The most connected nodes, most connected first.
+  pub hubs: Vec<GraphNode>,
+  pub orphans: Vec<GraphNode>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ForceNode {
+  pub id: String,
+  pub name: String,
+  pub group: String,
+  pub kind: NodeKind,
+  pub path: Option<String>,
+  /// Drawn size, growing with the number of connections.
+  pub val: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ForceLink {
+  pub source: String,
+  pub target: String,
+  #[serde(rename = "type")]
+  pub kind: EdgeKind,
+}
+
+/// Files, headings, tags and codex entities of a project and the links,
+/// tags and definitions connecting them. Queries treat edges as undirected.
+#[derive(Debug, Clone, Serialize)]
+pub struct KnowledgeGraph {
+  pub nodes: Vec<GraphNode>,
+  pub edges: Vec<GraphEdge>,
+  #[serde(skip)]
+  index: HashMap<String, usize>,
+  /// Edge indices touching each node.
+  #[serde(skip)]
+  adjacency: Vec<Vec<usize>>,
+}
+
+impl KnowledgeGraph {
+  /// Build the graph from the markdown files on disk. Links come from the
+  /// link index, which is refreshed first.
+  pub fn build(project_root: &str) -> Result<Self> {
+    let root = Path::new(project_root);
+    refresh_link_index(project_root)?;
+    let links = LinkDb::open_for_project(project_root)?.resolved()?;
+
+    let mut builder = Builder::default();
+    let mut file_tags: Vec<Vec<usize>> = Vec::new();
+    for path in project_files(project_root)?.iter().filter(|f| markdown_stem(f).is_some()) {
+      let contents = match fs::read_to_string(root.join(path)) {
+        Ok(contents) => contents,
+        Err(err) => {
+          eprintln!("[Graph] Skipping {path}: {err}");
+          continue;
+        }
+      };
+      let file = builder.node(path.clone(), || GraphNode {
+        id: path.clone(),
+        kind: NodeKind::File,
+        name: file_stem(path),
+        path: Some(path.clone()),
+        line: None,
+        group: group(path),
+        degree: 0,
+      });
+
+      let (_, body) = split_frontmatter(&contents);
+      let body_line = contents[..contents.len() - body.len()].lines().count();
+
+      let headings = parse_headings(body);
+      let slugs = heading_slugs(&headings);
+      for (heading, slug) in headings.into_iter().zip(slugs) {
+        let id = format!("heading:{path}#{slug}");
+        let node = builder.node(id.clone(), || GraphNode {
+          id,
+          kind: NodeKind::Heading,
+          name: heading.text,
+          path: Some(path.clone()),
+          line: Some(body_line + heading.line),
+          group: group(path),
+          degree: 0,
+        });
+        builder.edge(file, node, EdgeKind::Heading);
+      }
+
+      let mut tag_nodes = Vec::new();
+      for tag in document_tags(&contents) {
+        let id = format!("tag:#{tag}");
+        let node = builder.node(id.clone(), || GraphNode {
+          id,
+          kind: NodeKind::Tag,
+          name: format!("#{tag}"),
+          path: None,
+          line: None,
+          group: "_tags".to_string(),
+          degree: 0,
+        });
+        if !tag_nodes.contains(&node) {
+          tag_nodes.push(node);
+          builder.edge(file, node, EdgeKind::Tag);
+        }
+      }
+      file_tags.push(tag_nodes);
+
+      for block in parse_codex_blocks(body) {
+        let id = format!("entity:{}", block.name.to_lowercase());
+        let node = builder.node(id.clone(), || GraphNode {
+          id,
+          kind: NodeKind::Entity,
+          name: block.name,
+          path: Some(path.clone()),
+          line: Some(body_line + block.line),
+          group: "_entities".to_string(),
+          degree: 0,
+        });
+        builder.edge(file, node, EdgeKind::Defines);
+      }
+    }
+
+    for link in &links {
+      let (Some(&source), Some(target)) = (builder.index.get(&link.source), link.resolved.as_deref()) else {
+        continue;
+      };
+      // Links within a file say nothing about how files relate.
+      if target == link.source {
+        continue;
+      }
+      let heading = link
+        .anchor
+        .as_deref()
+        .and_then(|anchor| anchor_heading(link.kind, anchor))
+        .and_then(|heading| builder.index.get(&format!("heading:{target}#{}", slugify(&heading))));
+      if let Some(&node) = heading.or_else(|| builder.index.get(target)) {
+        builder.edge(source, node, EdgeKind::Link);
+      }
+    }
+
+    for tags in &mut file_tags {
+      tags.sort_unstable();
+      for (i, a) in tags.iter().enumerate() {
+        for b in &tags[i + 1..] {
+          builder.edge(*a, *b, EdgeKind::CoOccurrence);
+        }
+      }
+    }
+
+    Ok(builder.finish())
+  }
+
+  /// The node a user-facing name refers to: a node id, a file path or name
+  /// (`Combat`, `rules/combat.md`), `File#Heading`, `#tag` or an entity
+  /// name, all compared case-insensitively.
+  pub fn find(&self, concept: &str) -> Result<&GraphNode> {
+    self
+      .locate(concept)
+      .map(|idx| &self.nodes[idx])
+      .ok_or_else(|| Error::Anyhow(anyhow::anyhow!("No file, heading, tag or entity named '{concept}'")))
+  }
+
+  /// Nodes within `hops` edges of `concept`, nearest first.
+  pub fn neighbours(&self, concept: &str, hops: usize) -> Result<Neighbourhood> {
+    let start = self.index[&self.find(concept)?.id];
+    let mut distance: HashMap<usize, usize> = HashMap::from([(start, 0)]);
+    let mut order = vec![start];
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+      let next = distance[&node] + 1;
+      if next > hops {
+        continue;
+      }
+      for other in self.neighbour_ids(node) {
+        if let Entry::Vacant(entry) = distance.entry(other) {
+          entry.insert(next);
+          order.push(other);
+          queue.push_back(other);
+        }
+      }
+    }
+
+    Ok(Neighbourhood {
+      nodes: order
+        .iter()
+        .map(|idx| Neighbour {
+          node: self.nodes[*idx].clone(),
+          distance: distance[idx],
+        })
+        .collect(),
+      edges: self.edges_among(|idx| distance.contains_key(&idx)),
+    })
+  }
+
+  /// The fewest hops from one concept to another, or `None` if they are not
+  /// connected.
+  pub fn shortest_path(&self, from: &str, to: &str) -> Result<Option<GraphView>> {
+    let start = self.index[&self.find(from)?.id];
+    let goal = self.index[&self.find(to)?.id];
+    // Node -> edge it was reached through.
+    let mut reached: HashMap<usize, Option<usize>> = HashMap::from([(start, None)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+      if node == goal {
+        break;
+      }
+      for &edge in &self.adjacency[node] {
+        let other = self.other_end(edge, node);
+        if let Entry::Vacant(entry) = reached.entry(other) {
+          entry.insert(Some(edge));
+          queue.push_back(other);
+        }
+      }
+    }
+    if !reached.contains_key(&goal) {
+      return Ok(None);
+    }
+
+    let mut nodes = vec![self.nodes[goal].clone()];
+    let mut edges = Vec::new();
+    let mut node = goal;
+    while let Some(Some(edge)) = reached.get(&node) {
+      node = self.other_end(*edge, node);
+      edges.push(self.edges[*edge].clone());
+      nodes.push(self.nodes[node].clone());
+    }
+    nodes.reverse();
+    edges.reverse();
+    Ok(Some(GraphView { nodes, edges }))
+  }
+
+  /// Files with no links, tags or entities, in either direction.
+  pub fn orphans(&self) -> Vec<GraphNode> {
+    self
+      .nodes
+      .iter()
+      .filter(|n| n.kind == NodeKind::File && n.degree == 0)
+      .cloned()
+      .collect()
+  }
+
+  /// The `limit` most connected nodes, most connected first.
+  pub fn hubs(&self, limit: usize) -> Vec<GraphNode> {
+    let mut hubs: Vec<&GraphNode> = self.nodes.iter().filter(|n| n.degree > 0).collect();
+    hubs.sort_by(|a, b| b.degree.cmp(&a.degree).then_with(|| a.id.cmp(&b.id)));
+    hubs.into_iter().take(limit).cloned().collect()
+  }
+
+  /// Node ids of each connected component, largest first. Headings belong
+  /// to their file's component.
+  pub fn components(&self) -> Vec<Vec<String>> {
+    let mut component = vec![usize::MAX; self.nodes.len()];
+    let mut components: Vec<Vec<String>> = Vec::new();
+    for start in 0..self.nodes.len() {
+      if component[start] != usize::MAX {
+        continue;
+      }
+      let id = components.len();
+      component[start] = id;
+      let mut members = Vec::new();
+      let mut queue = VecDeque::from([start]);
+      while let Some(node) = queue.pop_front() {
+        members.push(self.nodes[node].id.clone());
+        for other in self.neighbour_ids(node) {
+          if component[other] == usize::MAX {
+            component[other] = id;
+            queue.push_back(other);
+          }
+        }
+      }
+      components.push(members);
+    }
+    components.sort_by_key(|c| std::cmp::Reverse(c.len()));
+    components
+  }
+
+  /// The graph for the lore map. Links to a heading are drawn to its file.
+  pub fn force_graph(&self) -> ForceGraph {
+    let mut seen = HashMap::new();
+    let mut links = Vec::new();
+    for edge in &self.edges {
+      if edge.kind == EdgeKind::Heading {
+        continue;
+      }
+      let source = self.drawn_id(&edge.source);
+      let target = self.drawn_id(&edge.target);
+      if source != target && seen.insert((source, target, edge.kind), ()).is_none() {
+        links.push(ForceLink {
+          source: source.to_string(),
+          target: target.to_string(),
+          kind: edge.kind,
+        });
+      }
+    }
+
+    ForceGraph {
+      nodes: self
+        .nodes
+        .iter()
+        .filter(|n| n.kind != NodeKind::Heading)
+        .map(|n| ForceNode {
+          id: n.id.clone(),
+          name: n.name.clone(),
+          group: n.group.clone(),
+          kind: n.kind,
+          path: n.path.clone(),
+          val: 1.0 + ((n.degree + 1) as f64).ln() * 2.0,
+        })
+        .collect(),
+      links,
+    }
+  }
+
+  fn locate(&self, concept: &str) -> Option<usize> {
+    let concept = concept.trim();
+    if let Some(idx) = self.index.get(concept) {
+      return Some(*idx);
+    }
+    let lowered = concept.to_lowercase();
+    if let Some(tag) = lowered.strip_prefix('#') {
+      return self.index.get(&format!("tag:#{tag}")).copied();
+    }
+    if let Some(idx) = self.find_file(&lowered) {
+      return Some(idx);
+    }
+    if let Some(idx) = self.index.get(&format!("entity:{lowered}")) {
+      return Some(*idx);
+    }
+    if let Some((file, heading)) = concept.split_once('#') {
+      let file = self.find_file(&file.trim().to_lowercase())?;
+      return self
+        .index
+        .get(&format!("heading:{}#{}", self.nodes[file].id, slugify(heading)))
+        .copied();
+    }
+    self.index.get(&format!("tag:#{lowered}")).copied()
+  }
+
+  /// A file by path or by name, preferring the shortest path.
+  fn find_file(&self, lowered: &str) -> Option<usize> {
+    self
+      .nodes
+      .iter()
+      .enumerate()
+      .filter(|(_, n)| n.kind == NodeKind::File)
+      .filter(|(_, n)| {
+        let path = n.id.to_lowercase();
+        path == lowered || markdown_stem(&path) == Some(lowered) || n.name.to_lowercase() == lowered
+      })
+      .min_by_key(|(_, n)| (n.id.matches('/').count(), n.id.clone()))
+      .map(|(idx, _)| idx)
+  }
+
+  fn neighbour_ids(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+    self.adjacency[node].iter().map(move |edge| self.other_end(*edge, node))
+  }
+
+  fn other_end(&self, edge: usize, node: usize) -> usize {
+    let edge = &self.edges[edge];
+    let source = self.index[&edge.source];
+    if source == node {
+      self.index[&edge.target]
+    } else {
+      source
+    }
+  }
+
+  fn edges_among(&self, included: impl Fn(usize) -> bool) -> Vec<GraphEdge> {
+    self
+      .edges
+      .iter()
+      .filter(|e| included(self.index[&e.source]) && included(self.index[&e.target]))
+      .cloned()
+      .collect()
+  }
+
+  /// The node an edge end is drawn at on the lore map.
+  fn drawn_id<'a>(&'a self, id: &'a str) -> &'a str {
+    let node = &self.nodes[self.index[id]];
+    match (node.kind, &node.path) {
+      (NodeKind::Heading, Some(path)) => path,
+      _ => id,
+    }
+  }
+}
+
+#[derive(Default)]
+struct Builder {
+  nodes: Vec<GraphNode>,
+  index: HashMap<String, usize>,
+  edges: HashMap<(usize, usize, EdgeKind), usize>,
+}
+
+impl Builder {
+  fn node(&mut self, id: String, make: impl FnOnce() -> GraphNode) -> usize {
+    if let Some(idx) = self.index.get(&id) {
+      return *idx;
+    }
+    self.nodes.push(make());
+    self.index.insert(id, self.nodes.len() - 1);
+    self.nodes.len() - 1
+  }
+
+  fn edge(&mut self, source: usize, target: usize, kind: EdgeKind) {
+    *self.edges.entry((source, target, kind)).or_insert(0) += 1;
+  }
+
+  fn finish(mut self) -> KnowledgeGraph {
+    let mut keys: Vec<_> = self.edges.into_iter().collect();
+    keys.sort_unstable();
+
+    let mut adjacency = vec![Vec::new(); self.nodes.len()];
+    let mut edges = Vec::with_capacity(keys.len());
+    for ((source, target, kind), weight) in keys {
+      adjacency[source].push(edges.len());
+      adjacency[target].push(edges.len());
+      if kind != EdgeKind::Heading {
+        self.nodes[source].degree += 1;
+        self.nodes[target].degree += 1;
+      }
+      edges.push(GraphEdge {
+        source: self.nodes[source].id.clone(),
+        target: self.nodes[target].id.clone(),
+        kind,
+        weight,
+      });
+    }
+
+    KnowledgeGraph {
+      nodes: self.nodes,
+      edges,
+      index: self.index,
+      adjacency,
+    }
+  }
+}
+
+fn file_stem(path: &str) -> String {
+  let name = path.rsplit('/').next().unwrap_or(path);
+  markdown_stem(name).unwrap_or(name).to_string()
+}
+
+/// Top-level folder, as the lore map colours nodes by it.
+fn group(path: &str) -> String {
+  match path.split_once('/') {
+    Some((folder, _)) => folder.to_string(),
+    None => "root".to_string(),
+  }
+}
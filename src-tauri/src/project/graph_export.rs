@@ -0,0 +1,269 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use serde::Deserialize;
+use serde_json::json;
+
+use super::graph::{EdgeKind, ForceGraph, ForceLink, ForceNode, KnowledgeGraph, NodeKind};
+
+/// Obsidian canvas grid: cell size and the card size within it.
+const CANVAS_CELL: (i64, i64) = (320, 140);
+const CANVAS_CARD: (i64, i64) = (260, 80);
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphFormat {
+  Graphml,
+  Dot,
+  /// Obsidian `.canvas` JSON.
+  Canvas,
+}
+
+/// Which part of the graph to export. Empty lists do not filter.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GraphFilter {
+  /// Keep files in these folders or below them.
+  #[serde(default)]
+  pub folders: Vec<String>,
+  /// Keep files carrying at least one of these tags, with or without `#`.
+  #[serde(default)]
+  pub tags: Vec<String>,
+  /// Keep only these kinds of connection.
+  #[serde(default)]
+  pub edge_kinds: Vec<EdgeKind>,
+}
+
+/// The lore map graph, filtered and written out in `format`.
+pub fn export_graph(graph: &KnowledgeGraph, format: GraphFormat, filter: &GraphFilter) -> String {
+  let graph = filtered(graph.force_graph(), filter);
+  match format {
+    GraphFormat::Graphml => to_graphml(&graph),
+    GraphFormat::Dot => to_dot(&graph),
+    GraphFormat::Canvas => to_canvas(&graph),
+  }
+}
+
+/// Files passing the folder and tag filters, the connections of allowed
+/// kinds between what is left, and the tags and entities those connect.
+fn filtered(graph: ForceGraph, filter: &GraphFilter) -> ForceGraph {
+  let folders: Vec<String> = filter
+    .folders
+    .iter()
+    .map(|f| f.trim().trim_matches('/').replace('\\', "/"))
+    .filter(|f| !f.is_empty())
+    .collect();
+  let tags: HashSet<String> = filter
+    .tags
+    .iter()
+    .map(|t| format!("tag:#{}", t.trim().trim_start_matches('#').to_lowercase()))
+    .collect();
+  let kinds: HashMap<&str, NodeKind> = graph.nodes.iter().map(|n| (n.id.as_str(), n.kind)).collect();
+
+  let tagged: HashSet<&str> = graph
+    .links
+    .iter()
+    .filter(|l| l.kind == EdgeKind::Tag && tags.contains(&l.target))
+    .map(|l| l.source.as_str())
+    .collect();
+  let files: HashSet<&str> = graph
+    .nodes
+    .iter()
+    .filter(|n| n.kind == NodeKind::File)
+    .filter(|n| folders.is_empty() || folders.iter().any(|f| n.id.starts_with(&format!("{f}/"))))
+    .filter(|n| tags.is_empty() || tagged.contains(n.id.as_str()))
+    .map(|n| n.id.as_str())
+    .collect();
+
+  // Tags and entities of the kept files are selected whatever the edge
+  // kinds, so that tag-to-tag co-occurrences survive without `tag` edges.
+  let is_file = |id: &str| kinds.get(id) == Some(&NodeKind::File);
+  let mut candidates: HashSet<&str> = files.clone();
+  for link in &graph.links {
+    if files.contains(link.source.as_str()) && !is_file(&link.target) {
+      candidates.insert(&link.target);
+    }
+  }
+
+  let links: Vec<ForceLink> = graph
+    .links
+    .iter()
+    .filter(|l| filter.edge_kinds.is_empty() || filter.edge_kinds.contains(&l.kind))
+    .filter(|l| candidates.contains(l.source.as_str()) && candidates.contains(l.target.as_str()))
+    .cloned()
+    .collect();
+  // Files are always kept; tags and entities only with a connection left.
+  let connected: HashSet<&str> = links
+    .iter()
+    .flat_map(|l| [l.source.as_str(), l.target.as_str()])
+    .collect();
+  let nodes: Vec<ForceNode> = graph
+    .nodes
+    .iter()
+    .filter(|n| files.contains(n.id.as_str()) || connected.contains(n.id.as_str()))
+    .cloned()
+    .collect();
+  ForceGraph { nodes, links }
+}
+
+fn to_graphml(graph: &ForceGraph) -> String {
+  let mut out = String::from(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+     <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n\
+     \x20 <key id=\"name\" for=\"node\" attr.name=\"name\" attr.type=\"string\"/>\n\
+     \x20 <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n\
+     \x20 <key id=\"group\" for=\"node\" attr.name=\"group\" attr.type=\"string\"/>\n\
+     \x20 <key id=\"path\" for=\"node\" attr.name=\"path\" attr.type=\"string\"/>\n\
+     \x20 <key id=\"type\" for=\"edge\" attr.name=\"type\" attr.type=\"string\"/>\n\
+     \x20 <graph id=\"lore\" edgedefault=\"directed\">\n",
+  );
+  for node in &graph.nodes {
+    let _ = writeln!(out, "    <node id=\"{}\">", xml_escape(&node.id));
+    let _ = writeln!(out, "      <data key=\"name\">{}</data>", xml_escape(&node.name));
+    let _ = writeln!(out, "      <data key=\"kind\">{}</data>", node_kind(node.kind));
+    let _ = writeln!(out, "      <data key=\"group\">{}</data>", xml_escape(&node.group));
+    if let Some(path) = &node.path {
+      let _ = writeln!(out, "      <data key=\"path\">{}</data>", xml_escape(path));
+    }
+    out.push_str("    </node>\n");
+  }
+  for (idx, link) in graph.links.iter().enumerate() {
+    // Co-occurrence has no direction; the other kinds point from a file.
+    let undirected = if link.kind == EdgeKind::CoOccurrence { " directed=\"false\"" } else { "" };
+    let _ = writeln!(
+      out,
+      "    <edge id=\"e{idx}\" source=\"{}\" target=\"{}\"{undirected}>\n      <data key=\"type\">{}</data>\n    </edge>",
+      xml_escape(&link.source),
+      xml_escape(&link.target),
+      edge_kind(link.kind)
+    );
+  }
+  out.push_str("  </graph>\n</graphml>\n");
+  out
+}
+
+fn to_dot(graph: &ForceGraph) -> String {
+  let mut out = String::from("digraph lore {\n  node [shape=ellipse];\n");
+  for node in &graph.nodes {
+    let shape = match node.kind {
+      NodeKind::File | NodeKind::Heading => "ellipse",
+      NodeKind::Tag => "note",
+      NodeKind::Entity => "box",
+    };
+    let _ = writeln!(
+      out,
+      "  {} [label={}, shape={shape}, group={}];",
+      dot_quote(&node.id),
+      dot_quote(&node.name),
+      dot_quote(&node.group)
+    );
+  }
+  for link in &graph.links {
+    let style = match link.kind {
+      EdgeKind::Link | EdgeKind::Heading => "solid",
+      EdgeKind::Defines => "bold",
+      EdgeKind::Tag | EdgeKind::CoOccurrence => "dashed",
+    };
+    let arrow = if link.kind == EdgeKind::CoOccurrence { ", dir=none" } else { "" };
+    let _ = writeln!(
+      out,
+      "  {} -> {} [label={}, style={style}{arrow}];",
+      dot_quote(&link.source),
+      dot_quote(&link.target),
+      dot_quote(edge_kind(link.kind))
+    );
+  }
+  out.push_str("}\n");
+  out
+}
+
+/// Files become file cards, so the canvas opens them; tags and entities
+/// become text cards. Cards are laid out on a grid, grouped by folder.
+fn to_canvas(graph: &ForceGraph) -> String {
+  let mut order: Vec<&ForceNode> = graph.nodes.iter().collect();
+  order.sort_by(|a, b| (&a.group, &a.name).cmp(&(&b.group, &b.name)));
+  let columns = ((order.len() as f64).sqrt().ceil() as i64).max(1);
+
+  let ids: HashMap<&str, String> = order
+    .iter()
+    .enumerate()
+    .map(|(idx, node)| (node.id.as_str(), format!("node-{idx}")))
+    .collect();
+  let nodes: Vec<serde_json::Value> = order
+    .iter()
+    .enumerate()
+    .map(|(idx, node)| {
+      let idx = idx as i64;
+      let mut card = json!({
+        "id": ids[node.id.as_str()],
+        "x": (idx % columns) * CANVAS_CELL.0,
+        "y": (idx / columns) * CANVAS_CELL.1,
+        "width": CANVAS_CARD.0,
+        "height": CANVAS_CARD.1,
+      });
+      match (node.kind, &node.path) {
+        (NodeKind::File, Some(path)) => {
+          card["type"] = json!("file");
+          card["file"] = json!(path);
+        }
+        (kind, _) => {
+          card["type"] = json!("text");
+          card["text"] = json!(node.name);
+          // Canvas preset colours: 6 is purple, 5 cyan.
+          card["color"] = json!(if kind == NodeKind::Tag { "6" } else { "5" });
+        }
+      }
+      card
+    })
+    .collect();
+  let edges: Vec<serde_json::Value> = graph
+    .links
+    .iter()
+    .enumerate()
+    .map(|(idx, link)| {
+      let mut edge = json!({
+        "id": format!("edge-{idx}"),
+        "fromNode": ids[link.source.as_str()],
+        "toNode": ids[link.target.as_str()],
+      });
+      if link.kind != EdgeKind::Link {
+        edge["label"] = json!(edge_kind(link.kind));
+        edge["toEnd"] = json!("none");
+      }
+      edge
+    })
+    .collect();
+
+  let canvas = json!({ "nodes": nodes, "edges": edges });
+  serde_json::to_string_pretty(&canvas).unwrap_or_default()
+}
+
+fn node_kind(kind: NodeKind) -> &'static str {
+  match kind {
+    NodeKind::File => "file",
+    NodeKind::Heading => "heading",
+    NodeKind::Tag => "tag",
+    NodeKind::Entity => "entity",
+  }
+}
+
+fn edge_kind(kind: EdgeKind) -> &'static str {
+  match kind {
+    EdgeKind::Link => "link",
+    EdgeKind::Heading => "heading",
+    EdgeKind::Tag => "tag",
+    EdgeKind::CoOccurrence => "co_occurrence",
+    EdgeKind::Defines => "defines",
+  }
+}
+
+fn xml_escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+fn dot_quote(text: &str) -> String {
+  format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
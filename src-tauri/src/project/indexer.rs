@@ -7,6 +7,8 @@ use crate::ai::llm_client::LlmClient;
 use crate::db::embeddings::{EmbeddingDb, FileChunkInput};
 use crate::util::error::Result;
 
+use super::link_index;
+
 fn is_markdown(path: &Path) -> bool {
   if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
     matches!(ext.to_lowercase().as_str(), "md" | "markdown" | "mdx")
@@ -27,7 +29,12 @@ pub async fn index_project(project_root: &str, client: &(dyn LlmClient + Send +
   println!("[Indexer] Root path exists: {}, is_dir: {}", root.exists(), root.is_dir());
 
   if root.is_dir() {
-    for entry in WalkDir::new(&root).into_iter().filter_map(|e| e.ok()) {
+    // Hidden folders hold app data such as `.codexlotus/trash`, whose
+    // deleted notes must not be indexed again.
+    let walker = WalkDir::new(&root)
+      .into_iter()
+      .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'));
+    for entry in walker.filter_map(|e| e.ok()) {
       let path = entry.path();
       if path.is_file() && is_markdown(path) {
         let rel = path
@@ -44,6 +51,13 @@ pub async fn index_project(project_root: &str, client: &(dyn LlmClient + Send +
 
   println!("[Indexer] Total files found: {}", files.len());
 
+  // The link index needs no embeddings; keep it current even if embedding
+  // fails below.
+  match link_index::refresh_link_index(project_root) {
+    Ok(updated) => println!("[Indexer] Link index: {} files updated", updated),
+    Err(err) => eprintln!("[Indexer] Failed to update the link index: {err}"),
+  }
+
   if files.is_empty() {
     println!("[Indexer] No markdown files found, skipping embedding");
     return Ok(());
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::util::error::{Error, Result};
+
+use super::links::{
+  anchor_heading, column, link_text, markdown_stem, markdown_target_path, parse_links, percent_decode,
+  project_files, Link, LinkKind, LinkResolver,
+};
+use super::markdown::{heading_slugs, parse_headings, slugify};
+
+/// Suggestions scoring below this similarity are not offered.
+const MIN_SUGGESTION_SCORE: f64 = 0.5;
+const MAX_SUGGESTIONS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkProblem {
+  /// No file matches the link target.
+  MissingFile,
+  /// The file exists but has no heading matching the `#anchor`.
+  MissingHeading,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BrokenLink {
+  /// File containing the link.
+  pub path: String,
+  /// 1-based line number.
+  pub line: usize,
+  /// 1-based column of the link target, counted in characters.
+  pub column: usize,
+  pub kind: LinkKind,
+  /// Target and anchor as written, e.g. `Combat#Flanking`.
+  pub link: String,
+  pub problem: LinkProblem,
+  /// Replacement targets in the link's own style, best match first.
+  pub suggestions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkReport {
+  pub broken: Vec<BrokenLink>,
+  pub files_checked: usize,
+  pub links_checked: usize,
+}
+
+/// Check every wiki link, markdown link and image reference in the project's
+/// markdown files against the files on disk and the headings of the files
+/// they point to. Links with a URL scheme are not checked.
+pub fn check_links(project_root: &str) -> Result<LinkReport> {
+  let root = Path::new(project_root);
+  if !root.is_dir() {
+    return Err(Error::Anyhow(anyhow::anyhow!(
+      "Project root is not a directory: {project_root}"
+    )));
+  }
+
+  let files = project_files(project_root)?;
+  let resolver = LinkResolver::new(files.clone());
+  let mut documents: Vec<(String, String)> = files
+    .iter()
+    .filter(|f| markdown_stem(f).is_some())
+    .filter_map(|f| match fs::read_to_string(root.join(f)) {
+      Ok(contents) => Some((f.clone(), contents)),
+      Err(err) => {
+        eprintln!("[LinkCheck] Skipping {f}: {err}");
+        None
+      }
+    })
+    .collect();
+  documents.sort_by(|a, b| a.0.cmp(&b.0));
+  let anchors: HashMap<&str, Vec<Anchor>> = documents
+    .iter()
+    .map(|(path, contents)| (path.as_str(), heading_anchors(contents)))
+    .collect();
+
+  let mut report = LinkReport {
+    broken: Vec::new(),
+    files_checked: documents.len(),
+    links_checked: 0,
+  };
+  for (source, contents) in &documents {
+    for link in parse_links(contents) {
+      report.links_checked += 1;
+      let target = if link.target.is_empty() {
+        Some(source.as_str())
+      } else {
+        resolver.resolve(source, &link)
+      };
+
+      let problem = match target {
+        None if is_folder_link(root, source, &link) => None,
+        None => Some((LinkProblem::MissingFile, suggest_files(source, &link, &resolver))),
+        Some(target) => match (&link.anchor, anchors.get(target)) {
+          (Some(anchor), Some(headings)) if !anchor_matches(link.kind, anchor, headings) => Some((
+            LinkProblem::MissingHeading,
+            suggest_headings(&link, anchor, headings),
+          )),
+          _ => None,
+        },
+      };
+
+      if let Some((problem, suggestions)) = problem {
+        report.broken.push(BrokenLink {
+          path: source.clone(),
+          line: link.line,
+          column: column(contents, link.start),
+          kind: link.kind,
+          link: written(&link),
+          problem,
+          suggestions,
+        });
+      }
+    }
+  }
+
+  Ok(report)
+}
+
+/// A heading's text and its anchor slug.
+struct Anchor {
+  text: String,
+  slug: String,
+}
+
+fn heading_anchors(contents: &str) -> Vec<Anchor> {
+  let headings = parse_headings(contents);
+  let slugs = heading_slugs(&headings);
+  headings
+    .into_iter()
+    .zip(slugs)
+    .map(|(heading, slug)| Anchor {
+      text: heading.text,
+      slug,
+    })
+    .collect()
+}
+
+/// Headings match by text, ignoring case, or by slug. Block references are
+/// not checked.
+fn anchor_matches(kind: LinkKind, anchor: &str, headings: &[Anchor]) -> bool {
+  let Some(anchor) = anchor_heading(kind, anchor) else {
+    return true;
+  };
+  let slug = slugify(&anchor);
+  headings
+    .iter()
+    .any(|h| h.text.eq_ignore_ascii_case(&anchor) || h.slug == slug || slugify(&h.text) == slug)
+}
+
+/// Markdown links may point at a folder, which is not broken.
+fn is_folder_link(root: &Path, source: &str, link: &Link) -> bool {
+  link.kind == LinkKind::Markdown
+    && markdown_target_path(source, &link.target).is_some_and(|path| root.join(path).is_dir())
+}
+
+/// Files whose names are closest to the missing target, as link targets.
+/// Markdown targets are matched against markdown files and other targets
+/// (images) against other files.
+fn suggest_files(source: &str, link: &Link, resolver: &LinkResolver) -> Vec<String> {
+  let wanted = percent_decode(&link.target);
+  let wanted_name = name_key(&wanted);
+  let wants_markdown = markdown_stem(&wanted).is_some() || !file_name(&wanted).contains('.');
+
+  let mut scored: Vec<(f64, &str)> = resolver
+    .files()
+    .iter()
+    .filter(|file| (markdown_stem(file).is_some()) == wants_markdown)
+    .map(|file| (strsim::normalized_damerau_levenshtein(&wanted_name, &name_key(file)), file.as_str()))
+    .filter(|(score, _)| *score >= MIN_SUGGESTION_SCORE)
+    .collect();
+  scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+
+  scored
+    .into_iter()
+    .take(MAX_SUGGESTIONS)
+    .map(|(_, file)| with_anchor(link_text(link, source, file, resolver), &link.anchor))
+    .collect()
+}
+
+/// Headings of the target file closest to the missing anchor, as the full
+/// link target (`Combat#Flanking Rules`, `combat.md#flanking-rules`).
+fn suggest_headings(link: &Link, anchor: &str, headings: &[Anchor]) -> Vec<String> {
+  let wanted = slugify(&percent_decode(anchor.rsplit('#').next().unwrap_or(anchor)));
+  let mut scored: Vec<(f64, &Anchor)> = headings
+    .iter()
+    .map(|h| (strsim::normalized_damerau_levenshtein(&wanted, &h.slug), h))
+    .filter(|(score, _)| *score >= MIN_SUGGESTION_SCORE)
+    .collect();
+  scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+  scored
+    .into_iter()
+    .take(MAX_SUGGESTIONS)
+    .map(|(_, heading)| {
+      let fragment = match link.kind {
+        LinkKind::Wiki => heading.text.clone(),
+        LinkKind::Markdown => heading.slug.clone(),
+      };
+      format!("{}#{fragment}", link.target)
+    })
+    .collect()
+}
+
+/// Lowercase file name without a markdown extension, for fuzzy matching.
+fn name_key(path: &str) -> String {
+  let name = file_name(path).to_lowercase();
+  markdown_stem(&name).map(str::to_string).unwrap_or(name)
+}
+
+fn file_name(path: &str) -> &str {
+  path.rsplit('/').next().unwrap_or(path)
+}
+
+fn with_anchor(target: String, anchor: &Option<String>) -> String {
+  match anchor {
+    Some(anchor) => format!("{target}#{anchor}"),
+    None => target,
+  }
+}
+
+fn written(link: &Link) -> String {
+  with_anchor(link.target.clone(), &link.anchor)
+}
@@ -0,0 +1,134 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use crate::db::links::{FileLinks, FileStamp, IndexedLink, LinkDb};
+use crate::util::error::Result;
+
+use super::links::{
+  anchor_heading, column, markdown_stem, markdown_target_path, parse_links, project_files, LinkKind, LinkResolver,
+};
+use super::markdown::slugify;
+
+/// Bring the link index in the project database up to date with the files
+/// on disk. Only files whose size or modification time changed are read
+/// again; when files were added or removed, every stored link is resolved
+/// again. Returns the number of files read.
+pub fn refresh_link_index(project_root: &str) -> Result<usize> {
+  let root = Path::new(project_root);
+  let files = project_files(project_root)?;
+  let mut db = LinkDb::open_for_project(project_root)?;
+  let stored = db.stamps()?;
+
+  let present: HashSet<&str> = files.iter().map(String::as_str).collect();
+  let removed: Vec<String> = stored
+    .keys()
+    .filter(|path| !present.contains(path.as_str()))
+    .cloned()
+    .collect();
+  let added = files.iter().any(|f| !stored.contains_key(f));
+  let changed: Vec<(&String, FileStamp)> = files
+    .iter()
+    .filter_map(|f| {
+      let stamp = stamp(&root.join(f))?;
+      (stored.get(f) != Some(&stamp)).then_some((f, stamp))
+    })
+    .collect();
+  if removed.is_empty() && changed.is_empty() {
+    return Ok(0);
+  }
+
+  let resolver = LinkResolver::new(files.clone());
+  let mut updates = Vec::with_capacity(changed.len());
+  for (path, stamp) in &changed {
+    let links = if markdown_stem(path).is_some() {
+      match fs::read_to_string(root.join(path)) {
+        Ok(contents) => file_links(root, path, &contents, &resolver),
+        Err(err) => {
+          eprintln!("[LinkIndex] Skipping {path}: {err}");
+          continue;
+        }
+      }
+    } else {
+      Vec::new()
+    };
+    updates.push(FileLinks {
+      path: path.to_string(),
+      stamp: *stamp,
+      links,
+    });
+  }
+
+  // Links in unchanged files may now point at a file that was added, or at
+  // one that is gone.
+  let mut resolutions = Vec::new();
+  if added || !removed.is_empty() {
+    let reread: HashSet<&str> = changed.iter().map(|(path, _)| path.as_str()).collect();
+    for (id, source, kind, target) in db.targets()? {
+      if !reread.contains(source.as_str()) && present.contains(source.as_str()) {
+        resolutions.push((id, resolve(root, &resolver, &source, kind, &target)));
+      }
+    }
+  }
+
+  db.update(&removed, &updates, &resolutions)?;
+  Ok(updates.len())
+}
+
+fn file_links(root: &Path, source: &str, contents: &str, resolver: &LinkResolver) -> Vec<(IndexedLink, Option<String>)> {
+  parse_links(contents)
+    .into_iter()
+    .map(|link| {
+      let line_start = contents[..link.start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+      let line_end = contents[link.start..]
+        .find('\n')
+        .map(|i| link.start + i)
+        .unwrap_or(contents.len());
+      let anchor_slug = link
+        .anchor
+        .as_deref()
+        .and_then(|anchor| anchor_heading(link.kind, anchor))
+        .map(|heading| slugify(&heading));
+      let indexed = IndexedLink {
+        source: source.to_string(),
+        line: link.line,
+        column: column(contents, link.start),
+        kind: link.kind,
+        resolved: resolve(root, resolver, source, link.kind, &link.target),
+        target: link.target,
+        anchor: link.anchor,
+        context: contents[line_start..line_end].trim().to_string(),
+      };
+      (indexed, anchor_slug)
+    })
+    .collect()
+}
+
+/// The file a link points to. A link without a target points at its own
+/// file, and a markdown link may point at a folder.
+fn resolve(root: &Path, resolver: &LinkResolver, source: &str, kind: LinkKind, target: &str) -> Option<String> {
+  if target.is_empty() {
+    return Some(source.to_string());
+  }
+  if let Some(path) = resolver.resolve_target(source, kind, target) {
+    return Some(path.to_string());
+  }
+  match kind {
+    LinkKind::Markdown => markdown_target_path(source, target).filter(|path| root.join(path).is_dir()),
+    LinkKind::Wiki => None,
+  }
+}
+
+fn stamp(path: &Path) -> Option<FileStamp> {
+  let metadata = fs::metadata(path).ok()?;
+  Some(FileStamp {
+    size: metadata.len(),
+    modified: metadata
+      .modified()
+      .ok()
+      .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+      .map(|d| d.as_millis() as i64)
+      .unwrap_or(0),
+  })
+}
@@ -0,0 +1,453 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+use walkdir::WalkDir;
+
+use crate::util::error::Result;
+
+use super::markdown::FenceTracker;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+  /// `[[Target]]`, `[[Target#Heading|Label]]` or the `![[Target]]` embed.
+  Wiki,
+  /// `[label](path.md#anchor)`, `![alt](image.png)` or a reference
+  /// definition `[label]: path.md`.
+  Markdown,
+}
+
+/// A link to another project file found in a markdown document. Links with a
+/// URL scheme (`https:`, `mailto:`) are not reported.
+#[derive(Debug, Clone)]
+pub struct Link {
+  pub kind: LinkKind,
+  /// Link target as written, without the anchor. Empty for links to a
+  /// heading in the same document (`[[#Rules]]`, `[see](#rules)`).
+  pub target: String,
+  /// Heading anchor after `#`, if any.
+  pub anchor: Option<String>,
+  /// Byte range of `target` in the document.
+  pub start: usize,
+  pub end: usize,
+  /// 1-based line number.
+  pub line: usize,
+  /// Markdown destination written as `<path with spaces>`.
+  pub angle_brackets: bool,
+}
+
+/// Every wiki and markdown link in `content`, in document order. Fenced code
+/// blocks and inline code spans are skipped.
+pub fn parse_links(content: &str) -> Vec<Link> {
+  let mut links = Vec::new();
+  let mut fence = FenceTracker::default();
+  let mut offset = 0;
+
+  for (idx, raw) in content.split_inclusive('\n').enumerate() {
+    let line = raw.trim_end_matches(['\n', '\r']);
+    if !fence.update(line) {
+      scan_line(line, offset, idx + 1, &mut links);
+    }
+    offset += raw.len();
+  }
+
+  links
+}
+
+fn scan_line(line: &str, offset: usize, line_no: usize, links: &mut Vec<Link>) {
+  if let Some(link) = reference_definition(line, offset, line_no) {
+    links.push(link);
+    return;
+  }
+
+  let bytes = line.as_bytes();
+  let mut i = 0;
+  while i < bytes.len() {
+    match bytes[i] {
+      b'`' => i = skip_code_span(line, i),
+      b'[' if line[i..].starts_with("[[") => match line[i + 2..].find("]]") {
+        Some(len) => {
+          let inner = &line[i + 2..i + 2 + len];
+          let written = inner.split('|').next().unwrap_or_default();
+          if !written.trim().is_empty() {
+            links.push(split_anchor(LinkKind::Wiki, written, offset + i + 2, line_no, false));
+          }
+          i += len + 4;
+        }
+        None => i += 2,
+      },
+      b'[' => match inline_destination(line, i) {
+        Some((start, dest, angle_brackets, next)) => {
+          if !dest.is_empty() && !is_external(dest) {
+            links.push(split_anchor(LinkKind::Markdown, dest, offset + start, line_no, angle_brackets));
+          }
+          i = next;
+        }
+        None => i += 1,
+      },
+      _ => i += 1,
+    }
+  }
+}
+
+/// Index just past the code span opened at `start`, or past the backticks if
+/// the span is never closed.
+fn skip_code_span(line: &str, start: usize) -> usize {
+  let run = line[start..].bytes().take_while(|b| *b == b'`').count();
+  let marker = &line[start..start + run];
+  let mut search = start + run;
+  while let Some(found) = line[search..].find(marker) {
+    let at = search + found;
+    let len = line[at..].bytes().take_while(|b| *b == b'`').count();
+    if len == run {
+      return at + len;
+    }
+    search = at + len;
+  }
+  start + run
+}
+
+/// For `[label](dest "title")` starting at `open`: the byte offset of
+/// `dest`, `dest` itself, whether it was in angle brackets, and the index
+/// after the closing parenthesis.
+fn inline_destination(line: &str, open: usize) -> Option<(usize, &str, bool, usize)> {
+  let mut depth = 0usize;
+  let mut close = None;
+  for (idx, c) in line[open..].char_indices() {
+    match c {
+      '[' => depth += 1,
+      ']' => {
+        depth -= 1;
+        if depth == 0 {
+          close = Some(open + idx);
+          break;
+        }
+      }
+      _ => {}
+    }
+  }
+  let close = close?;
+  if !line[close + 1..].starts_with('(') {
+    return None;
+  }
+
+  let mut start = close + 2;
+  start += line[start..].len() - line[start..].trim_start().len();
+  if line[start..].starts_with('<') {
+    let len = line[start + 1..].find('>')?;
+    let end = start + 1 + len;
+    let after = end + 1 + line[end + 1..].find(')')? + 1;
+    return Some((start + 1, &line[start + 1..end], true, after));
+  }
+
+  let mut parens = 0usize;
+  let mut end = line.len();
+  for (idx, c) in line[start..].char_indices() {
+    match c {
+      '(' => parens += 1,
+      ')' if parens == 0 => {
+        end = start + idx;
+        break;
+      }
+      ')' => parens -= 1,
+      c if c.is_whitespace() => {
+        end = start + idx;
+        break;
+      }
+      _ => {}
+    }
+  }
+  let after = end + line[end..].find(')')? + 1;
+  Some((start, &line[start..end], false, after))
+}
+
+/// `[label]: destination` on a line of its own.
+fn reference_definition(line: &str, offset: usize, line_no: usize) -> Option<Link> {
+  let trimmed = line.trim_start();
+  let indent = line.len() - trimmed.len();
+  if indent > 3 || !trimmed.starts_with('[') || trimmed.starts_with("[[") {
+    return None;
+  }
+  let close = trimmed.find("]:")?;
+  let rest = &trimmed[close + 2..];
+  let start = indent + close + 2 + (rest.len() - rest.trim_start().len());
+  let dest_text = &line[start..];
+  let (start, dest, angle_brackets) = match dest_text.strip_prefix('<') {
+    Some(inner) => (start + 1, &inner[..inner.find('>')?], true),
+    None => (start, dest_text.split_whitespace().next()?, false),
+  };
+  if dest.is_empty() || is_external(dest) {
+    return None;
+  }
+  Some(split_anchor(LinkKind::Markdown, dest, offset + start, line_no, angle_brackets))
+}
+
+/// The link for `written`, a target with an optional `#anchor` starting at
+/// byte `start`. The anchor is left out of the target range.
+fn split_anchor(kind: LinkKind, written: &str, start: usize, line: usize, angle_brackets: bool) -> Link {
+  let (target, anchor) = match written.split_once('#') {
+    Some((target, anchor)) => (target, Some(anchor.trim().to_string())),
+    None => (written, None),
+  };
+  // Wiki targets may be padded: `[[ Combat ]]`.
+  let lead = target.len() - target.trim_start().len();
+  let target = if kind == LinkKind::Wiki { target.trim() } else { target };
+  Link {
+    kind,
+    target: target.to_string(),
+    anchor,
+    start: start + lead,
+    end: start + lead + target.len(),
+    line,
+    angle_brackets,
+  }
+}
+
+/// Whether a markdown destination has a URL scheme (`https:`, `mailto:`).
+/// Windows drive letters are one character and not mistaken for schemes.
+fn is_external(dest: &str) -> bool {
+  match dest.find(':') {
+    Some(colon) if colon > 1 => dest[..colon]
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')),
+    _ => false,
+  }
+}
+
+/// Project files by path, for resolving link targets.
+#[derive(Debug, Default)]
+pub struct LinkResolver {
+  files: Vec<String>,
+  /// Lowercase file stem (`combat` for `rules/Combat.md`) and file name to
+  /// indices into `files`.
+  by_name: HashMap<String, Vec<usize>>,
+}
+
+impl LinkResolver {
+  /// `files` are project-relative paths with `/` separators.
+  pub fn new(mut files: Vec<String>) -> Self {
+    // Shorter paths win when several files share a name.
+    files.sort_by(|a, b| (a.matches('/').count(), a).cmp(&(b.matches('/').count(), b)));
+    let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+    for (idx, file) in files.iter().enumerate() {
+      let name = file.rsplit('/').next().unwrap_or(file).to_lowercase();
+      if let Some(stem) = markdown_stem(&name) {
+        by_name.entry(stem.to_string()).or_default().push(idx);
+      }
+      by_name.entry(name).or_default().push(idx);
+    }
+    Self { files, by_name }
+  }
+
+  pub fn files(&self) -> &[String] {
+    &self.files
+  }
+
+  fn lookup(&self, path: &str) -> Option<&str> {
+    let ids = self.by_name.get(&file_name(path).to_lowercase())?;
+    ids
+      .iter()
+      .map(|idx| self.files[*idx].as_str())
+      .find(|file| *file == path)
+  }
+
+  /// The file a link in `source` points to. Wiki targets match a file name,
+  /// with or without a markdown extension, or a path suffix such as
+  /// `rules/Combat`; markdown targets are paths relative to `source`, or to
+  /// the project root when they start with `/`.
+  pub fn resolve(&self, source: &str, link: &Link) -> Option<&str> {
+    self.resolve_target(source, link.kind, &link.target)
+  }
+
+  /// [`resolve`](Self::resolve) for a link target stored without its
+  /// position.
+  pub fn resolve_target(&self, source: &str, kind: LinkKind, target: &str) -> Option<&str> {
+    if target.is_empty() {
+      return None;
+    }
+    match kind {
+      LinkKind::Wiki => self.resolve_wiki(target),
+      LinkKind::Markdown => self.lookup(&markdown_target_path(source, target)?),
+    }
+  }
+
+  /// The file a wiki link target such as `Combat` or `rules/Combat.md`
+  /// names.
+  pub fn resolve_wiki(&self, target: &str) -> Option<&str> {
+    let target = target.trim_start_matches('/').replace('\\', "/");
+    let name = file_name(&target).to_lowercase();
+    let candidates = self.by_name.get(&name)?;
+    let lowered = target.to_lowercase();
+    candidates
+      .iter()
+      .map(|idx| self.files[*idx].as_str())
+      .find(|file| {
+        let file = file.to_lowercase();
+        let stem = markdown_stem(&file).unwrap_or(&file);
+        [file.as_str(), stem]
+          .iter()
+          .any(|candidate| *candidate == lowered || candidate.ends_with(&format!("/{lowered}")))
+      })
+  }
+}
+
+/// Every file in the project, relative with `/` separators. Hidden files and
+/// folders, including `.git` and `.codexlotus`, are skipped.
+pub fn project_files(project_root: &str) -> Result<Vec<String>> {
+  let root = fs::canonicalize(project_root)?;
+  let mut files = Vec::new();
+  let walker = WalkDir::new(&root)
+    .into_iter()
+    .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'));
+  for entry in walker {
+    let entry = match entry {
+      Ok(entry) => entry,
+      Err(err) => {
+        eprintln!("[Links] Skipping entry: {err}");
+        continue;
+      }
+    };
+    if let (true, Ok(relative)) = (entry.file_type().is_file(), entry.path().strip_prefix(&root)) {
+      files.push(relative.to_string_lossy().replace('\\', "/"));
+    }
+  }
+  Ok(files)
+}
+
+/// Project-relative path a markdown link in `source` points to, with `.`
+/// and `..` segments resolved and percent escapes decoded. `None` if the
+/// link climbs out of the project.
+pub fn markdown_target_path(source: &str, target: &str) -> Option<String> {
+  let decoded = percent_decode(target);
+  let mut parts: Vec<&str> = if decoded.starts_with('/') {
+    Vec::new()
+  } else {
+    source.split('/').collect()
+  };
+  if !decoded.starts_with('/') {
+    parts.pop();
+  }
+  for segment in decoded.split('/') {
+    match segment {
+      "" | "." => {}
+      ".." => {
+        parts.pop()?;
+      }
+      segment => parts.push(segment),
+    }
+  }
+  Some(parts.join("/"))
+}
+
+/// Target text that makes `link`, found in `source`, point at `target`, in
+/// the link's own style. Wiki links stay bare names (`[[Combat]]`) unless the
+/// name would be ambiguous, and keep a path if they had one; markdown links
+/// stay relative, or root-relative if they started with `/`.
+pub fn link_text(link: &Link, source: &str, target: &str, resolver: &LinkResolver) -> String {
+  match link.kind {
+    LinkKind::Wiki => wiki_text(link, target, resolver),
+    LinkKind::Markdown => {
+      let path = if link.target.starts_with('/') {
+        format!("/{target}")
+      } else {
+        relative_link(source, target)
+      };
+      if link.angle_brackets {
+        path
+      } else {
+        encode_destination(&path)
+      }
+    }
+  }
+}
+
+fn wiki_text(link: &Link, new_target: &str, resolver: &LinkResolver) -> String {
+  let written_name = link.target.rsplit('/').next().unwrap_or(&link.target);
+  let keep_extension = markdown_stem(new_target).is_none() || markdown_stem(written_name).is_some();
+  let path = if keep_extension {
+    new_target
+  } else {
+    markdown_stem(new_target).unwrap_or(new_target)
+  };
+  let name = path.rsplit('/').next().unwrap_or(path);
+
+  if !link.target.contains('/') && resolver.resolve_wiki(name) == Some(new_target) {
+    name.to_string()
+  } else {
+    path.to_string()
+  }
+}
+
+/// Relative path from the folder of `source` to `target`, both
+/// project-relative, written the way a markdown link expects.
+fn relative_link(source: &str, target: &str) -> String {
+  let from: Vec<&str> = source.split('/').collect();
+  let from = &from[..from.len() - 1];
+  let to: Vec<&str> = target.split('/').collect();
+  let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+  let mut parts: Vec<&str> = vec![".."; from.len() - common];
+  parts.extend(&to[common..]);
+  parts.join("/")
+}
+
+/// Escape the characters that would end a markdown destination.
+fn encode_destination(path: &str) -> String {
+  path.replace('%', "%25").replace(' ', "%20").replace('(', "%28").replace(')', "%29")
+}
+
+/// A markdown file name or path without its extension (`rules/combat` for
+/// `rules/combat.md`).
+pub fn markdown_stem(name: &str) -> Option<&str> {
+  let (stem, ext) = name.rsplit_once('.')?;
+  let is_markdown = Path::new(name)
+    .extension()
+    .is_some_and(|e| matches!(e.to_string_lossy().to_lowercase().as_str(), "md" | "markdown" | "mdx"));
+  (is_markdown && !ext.is_empty() && !stem.is_empty()).then_some(stem)
+}
+
+fn file_name(path: &str) -> &str {
+  path.rsplit('/').next().unwrap_or(path)
+}
+
+/// The heading a link anchor names, or `None` for block references
+/// (`#^id`). Wiki anchors name a heading (`[[Combat#Flanking Rules]]`; with
+/// nested headings, the last one counts); markdown anchors are usually
+/// percent-encoded slugs.
+pub fn anchor_heading(kind: LinkKind, anchor: &str) -> Option<String> {
+  let heading = match kind {
+    LinkKind::Wiki => anchor.rsplit('#').next().unwrap_or(anchor).trim().to_string(),
+    LinkKind::Markdown => percent_decode(anchor),
+  };
+  (!heading.is_empty() && !heading.starts_with('^')).then_some(heading)
+}
+
+/// 1-based column of byte `offset` in `contents`, counted in characters.
+pub fn column(contents: &str, offset: usize) -> usize {
+  let line_start = contents[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+  contents[line_start..offset].chars().count() + 1
+}
+
+pub fn percent_decode(text: &str) -> String {
+  let bytes = text.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    let hex = (bytes[i] == b'%')
+      .then(|| text.get(i + 1..i + 3))
+      .flatten()
+      .and_then(|h| u8::from_str_radix(h, 16).ok());
+    match hex {
+      Some(byte) => {
+        decoded.push(byte);
+        i += 3;
+      }
+      None => {
+        decoded.push(bytes[i]);
+        i += 1;
+      }
+    }
+  }
+  String::from_utf8_lossy(&decoded).into_owned()
+}
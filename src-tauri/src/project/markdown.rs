@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct Heading {
+  pub text: String,
+  /// 1 for `#`, up to 6 for `######`.
+  pub level: usize,
+  /// 1-based line number of the heading line.
+  pub line: usize,
+}
+
+/// A heading plus everything under it, up to the next heading of the same or
+/// a higher level.
+#[derive(Debug, Clone)]
+pub struct Section {
+  pub heading: Heading,
+  /// Byte range of the section in the document, starting at the heading line.
+  pub start: usize,
+  pub end: usize,
+}
+
+/// Metadata from a YAML frontmatter block at the top of a document.
+#[derive(Debug, Clone, Default)]
+pub struct Frontmatter {
+  pub title: Option<String>,
+  /// From `tags:` (or `tag:`), written as a list or as one string separated
+  /// by commas or spaces. A leading `#` is dropped.
+  pub tags: Vec<String>,
+  /// Other names for the document's subject, from `aliases:` (or `alias:`),
+  /// written as a list or as one string separated by commas.
+  pub aliases: Vec<String>,
+}
+
+/// Split off a frontmatter block delimited by `---` lines. Returns the parsed
+/// frontmatter, if there is a block, and the body after it. Malformed YAML
+/// yields empty frontmatter rather than an error, since the document itself
+/// is still fine.
+pub fn split_frontmatter(content: &str) -> (Option<Frontmatter>, &str) {
+  let Some(rest) = content
+    .strip_prefix("---")
+    .and_then(|r| r.strip_prefix('\n').or_else(|| r.strip_prefix("\r\n")))
+  else {
+    return (None, content);
+  };
+
+  let mut offset = 0;
+  for line in rest.split_inclusive('\n') {
+    let trimmed = line.trim_end_matches(['\n', '\r']);
+    if trimmed == "---" || trimmed == "..." {
+      let yaml = &rest[..offset];
+      let body = &rest[offset + line.len()..];
+      return (Some(parse_frontmatter(yaml)), body);
+    }
+    offset += line.len();
+  }
+  (None, content)
+}
+
+fn parse_frontmatter(yaml: &str) -> Frontmatter {
+  let value: serde_yaml::Value = match serde_yaml::from_str(yaml) {
+    Ok(value) => value,
+    Err(_) => return Frontmatter::default(),
+  };
+  let title = value.get("title").and_then(|t| t.as_str()).map(|t| t.trim().to_string());
+  let tags = string_list(value.get("tags").or_else(|| value.get("tag")), &[',', ' '])
+    .iter()
+    .map(|tag| tag.trim_start_matches('#').to_string())
+    .filter(|tag| !tag.is_empty())
+    .collect();
+  let aliases = string_list(value.get("aliases").or_else(|| value.get("alias")), &[',']);
+
+  Frontmatter {
+    title: title.filter(|t| !t.is_empty()),
+    tags,
+    aliases,
+  }
+}
+
+/// A YAML list of strings, or one string split at `separators`. Items are
+/// trimmed and empty ones dropped.
+fn string_list(value: Option<&serde_yaml::Value>, separators: &[char]) -> Vec<String> {
+  let items: Vec<String> = match value {
+    Some(serde_yaml::Value::Sequence(items)) => items.iter().filter_map(yaml_string).collect(),
+    Some(serde_yaml::Value::String(list)) => list.split(separators).map(str::to_string).collect(),
+    _ => Vec::new(),
+  };
+  items
+    .into_iter()
+    .map(|item| item.trim().to_string())
+    .filter(|item| !item.is_empty())
+    .collect()
+}
+
+/// A named ```` ```codex ```` or ```` ```statblock ```` block: a creature,
+/// item or other entity described in YAML.
+#[derive(Debug, Clone)]
+pub struct CodexBlock {
+  /// The block's `name` field.
+  pub name: String,
+  /// 1-based line number of the opening fence.
+  pub line: usize,
+}
+
+/// Every tag of a document, frontmatter tags first, then inline ones:
+/// lowercased, without `#` and without duplicates.
+pub fn document_tags(content: &str) -> Vec<String> {
+  let (frontmatter, body) = split_frontmatter(content);
+  let mut tags: Vec<String> = Vec::new();
+  let frontmatter_tags = frontmatter.map(|f| f.tags).unwrap_or_default();
+  for tag in frontmatter_tags.iter().map(|tag| tag.to_lowercase()).chain(parse_inline_tags(body)) {
+    if !tags.contains(&tag) {
+      tags.push(tag);
+    }
+  }
+  tags
+}
+
+/// Inline `#tags` outside code blocks, lowercased and without the `#`. A tag
+/// starts with a letter and follows whitespace or the start of a line, so
+/// headings, URL fragments and `#fff` colours in words are not tags.
+pub fn parse_inline_tags(content: &str) -> Vec<String> {
+  let mut tags = Vec::new();
+  let mut fence = FenceTracker::default();
+  for line in content.lines() {
+    if fence.update(line) {
+      continue;
+    }
+    let mut previous = ' ';
+    for (idx, c) in line.char_indices() {
+      if c == '#' && previous.is_whitespace() {
+        let tag: String = line[idx + 1..]
+          .chars()
+          .take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/'))
+          .collect();
+        if tag.starts_with(char::is_alphabetic) {
+          let tag = tag.to_lowercase();
+          if !tags.contains(&tag) {
+            tags.push(tag);
+          }
+        }
+      }
+      previous = c;
+    }
+  }
+  tags
+}
+
+/// Named codex and stat blocks, in document order. Blocks whose YAML does not
+/// parse or has no `name` are skipped.
+pub fn parse_codex_blocks(content: &str) -> Vec<CodexBlock> {
+  let mut blocks = Vec::new();
+  let mut fence = FenceTracker::default();
+  // Opening line and YAML of the codex block being read.
+  let mut current: Option<(usize, String)> = None;
+
+  for (idx, line) in content.lines().enumerate() {
+    let was_open = fence.open.is_some();
+    fence.update(line);
+    match (was_open, fence.open.is_some()) {
+      (false, true) => {
+        let info = line.trim_start().trim_start_matches(['`', '~']).trim();
+        let lang = info.split_whitespace().next().unwrap_or("");
+        if matches!(lang, "codex" | "statblock") {
+          current = Some((idx + 1, String::new()));
+        }
+      }
+      (true, true) => {
+        if let Some((_, yaml)) = current.as_mut() {
+          yaml.push_str(line);
+          yaml.push('\n');
+        }
+      }
+      (true, false) => {
+        if let Some((line, yaml)) = current.take() {
+          let name = serde_yaml::from_str::<serde_yaml::Value>(&yaml)
+            .ok()
+            .and_then(|value| value.get("name").and_then(yaml_string));
+          if let Some(name) = name.filter(|n| !n.is_empty()) {
+            blocks.push(CodexBlock { name, line });
+          }
+        }
+      }
+      (false, false) => {}
+    }
+  }
+  blocks
+}
+
+fn yaml_string(value: &serde_yaml::Value) -> Option<String> {
+  match value {
+    serde_yaml::Value::String(s) => Some(s.trim().to_string()),
+    serde_yaml::Value::Number(n) => Some(n.to_string()),
+    _ => None,
+  }
+}
+
+/// Parse ATX headings (`# Title`), ignoring anything inside fenced code blocks.
+pub fn parse_headings(content: &str) -> Vec<Heading> {
+  scan_headings(content).into_iter().map(|(heading, _)| heading).collect()
+}
+
+/// Locate the section whose heading is `name`, compared case-insensitively
+/// and also by its slug (`combat.md#flanking-rules` style anchors).
+pub fn find_section(content: &str, name: &str) -> Result<Section, String> {
+  let wanted = name.trim().trim_start_matches('#').trim();
+  let headings = scan_headings(content);
+  let matches: Vec<usize> = headings
+    .iter()
+    .enumerate()
+    .filter(|(_, (h, _))| h.text.eq_ignore_ascii_case(wanted) || slugify(&h.text) == slugify(wanted))
+    .map(|(idx, _)| idx)
+    .collect();
+
+  let idx = match matches.as_slice() {
+    [] => return Err(format!("No heading named '{wanted}' in the file")),
+    [idx] => *idx,
+    many => {
+      let lines: Vec<String> = many.iter().map(|i| headings[*i].0.line.to_string()).collect();
+      return Err(format!(
+        "'{wanted}' matches several headings (lines {}); rename one to target it",
+        lines.join(", ")
+      ));
+    }
+  };
+
+  let (heading, start) = headings[idx].clone();
+  let end = headings[idx + 1..]
+    .iter()
+    .find(|(h, _)| h.level <= heading.level)
+    .map(|(_, offset)| *offset)
+    .unwrap_or(content.len());
+
+  Ok(Section { heading, start, end })
+}
+
+/// Headings with the byte offset of their line.
+fn scan_headings(content: &str) -> Vec<(Heading, usize)> {
+  let mut headings = Vec::new();
+  let mut fence = FenceTracker::default();
+  let mut offset = 0;
+
+  for (idx, raw) in content.split_inclusive('\n').enumerate() {
+    let line = raw.trim_end_matches(['\n', '\r']);
+    if !fence.update(line) {
+      if let Some((level, text)) = parse_heading_line(line) {
+        headings.push((
+          Heading {
+            text,
+            level,
+            line: idx + 1,
+          },
+          offset,
+        ));
+      }
+    }
+    offset += raw.len();
+  }
+
+  headings
+}
+
+/// GitHub-style heading anchor: `Flanking Rules` becomes `flanking-rules`.
+pub fn slugify(text: &str) -> String {
+  text
+    .trim()
+    .to_lowercase()
+    .chars()
+    .filter_map(|c| match c {
+      c if c.is_alphanumeric() => Some(c),
+      ' ' | '-' | '_' => Some('-'),
+      _ => None,
+    })
+    .collect()
+}
+
+/// Anchor slug of each heading, numbered like GitHub does for repeated
+/// headings (`rules`, `rules-1`, ...).
+pub fn heading_slugs(headings: &[Heading]) -> Vec<String> {
+  let mut seen: HashMap<String, usize> = HashMap::new();
+  headings
+    .iter()
+    .map(|heading| {
+      let base = slugify(&heading.text);
+      let count = seen.entry(base.clone()).or_insert(0);
+      let slug = if *count == 0 { base } else { format!("{base}-{count}") };
+      *count += 1;
+      slug
+    })
+    .collect()
+}
+
+/// Returns the heading level and text if `line` is an ATX heading.
+pub fn parse_heading_line(line: &str) -> Option<(usize, String)> {
+  let trimmed = line.trim_start();
+  if line.len() - trimmed.len() > 3 {
+    return None;
+  }
+  let level = trimmed.chars().take_while(|c| *c == '#').count();
+  if level == 0 || level > 6 {
+    return None;
+  }
+  let rest = &trimmed[level..];
+  if !rest.is_empty() && !rest.starts_with(' ') && !rest.starts_with('\t') {
+    return None;
+  }
+  let text = rest.trim().trim_end_matches('#').trim_end().to_string();
+  Some((level, text))
+}
+
+/// Tracks whether we are inside a ``` or ~~~ fenced code block while
+/// iterating lines.
+#[derive(Default)]
+pub struct FenceTracker {
+  open: Option<(char, usize)>,
+}
+
+impl FenceTracker {
+  /// Feed the next line. Returns true if the line is part of a code block
+  /// (including the fence lines themselves).
+  pub fn update(&mut self, line: &str) -> bool {
+    let trimmed = line.trim_start();
+    let marker = trimmed.chars().next();
+    let run = match marker {
+      Some(c @ ('`' | '~')) => Some((c, trimmed.chars().take_while(|x| *x == c).count())),
+      _ => None,
+    };
+
+    match (self.open, run) {
+      (Some((open_char, open_len)), Some((c, len))) if c == open_char && len >= open_len => {
+        if trimmed[len * c.len_utf8()..].trim().is_empty() {
+          self.open = None;
+        }
+        true
+      }
+      (Some(_), _) => true,
+      (None, Some((c, len))) if len >= 3 => {
+        self.open = Some((c, len));
+        true
+      }
+      (None, _) => false,
+    }
+  }
+}
@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use aho_corasick::AhoCorasick;
+use serde::Serialize;
+
+use crate::util::error::{Error, Result};
+
+use super::links::{column, markdown_stem, project_files, LinkResolver};
+use super::markdown::{document_tags, parse_codex_blocks, slugify, split_frontmatter, FenceTracker};
+
+/// Names shorter than this, once normalized, are too likely to be ordinary
+/// words to suggest.
+const MIN_NAME_LEN: usize = 3;
+
+/// Something a document can mention: a file, under its name, title or
+/// aliases, or a codex entity described in it.
+#[derive(Debug, Clone, Serialize)]
+pub struct MentionTarget {
+  /// File the mention should link to.
+  pub path: String,
+  /// Canonical name: the title, file name or entity name.
+  pub name: String,
+  /// Tag to suggest, with `#`.
+  pub tag: String,
+  /// Wiki link target for the file: its name, or its path when the name is
+  /// ambiguous.
+  pub link: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Mention {
+  /// 1-based line number.
+  pub line: usize,
+  /// 1-based column, counted in characters.
+  pub column: usize,
+  /// Length of the mention in characters.
+  pub length: usize,
+  /// The mention as written, e.g. `Wizard's Guild`.
+  pub text: String,
+  pub target: MentionTarget,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileMentions {
+  pub path: String,
+  /// Tags the file already carries, with `#`.
+  pub current_tags: Vec<String>,
+  /// Tags of mentioned targets the file does not carry yet, with `#`.
+  pub suggested_tags: Vec<String>,
+  pub mentions: Vec<Mention>,
+}
+
+/// Finds names of project files and entities in text. Matching ignores case
+/// and punctuation between words, accepts possessives (`Guild's`) and simple
+/// plurals (`ghouls`, `liches`, `harpies`), and only counts whole words.
+pub struct MentionDetector {
+  targets: Vec<MentionTarget>,
+  automaton: AhoCorasick,
+  /// Target of each automaton pattern.
+  pattern_targets: Vec<usize>,
+}
+
+impl MentionDetector {
+  /// `names` pairs each target with the names it goes by. When two targets
+  /// share a name, the first keeps it.
+  pub fn new(names: Vec<(MentionTarget, Vec<String>)>) -> Result<Self> {
+    let mut targets = Vec::new();
+    let mut patterns: Vec<String> = Vec::new();
+    let mut pattern_targets = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for (target, target_names) in names {
+      let idx = targets.len();
+      targets.push(target);
+      for name in target_names {
+        let normalized = normalize(&name).0.trim().to_string();
+        if normalized.chars().count() < MIN_NAME_LEN {
+          continue;
+        }
+        for variant in plural_forms(&normalized) {
+          if let std::collections::hash_map::Entry::Vacant(entry) = seen.entry(variant.clone()) {
+            entry.insert(idx);
+            patterns.push(variant);
+            pattern_targets.push(idx);
+          }
+        }
+      }
+    }
+    let automaton = AhoCorasick::new(&patterns)
+      .map_err(|err| Error::Anyhow(anyhow::anyhow!("Failed to build the mention matcher: {err}")))?;
+    Ok(Self {
+      targets,
+      automaton,
+      pattern_targets,
+    })
+  }
+
+  /// Mentions in a markdown document as byte ranges with their target,
+  /// longest first where names overlap. Frontmatter, code blocks, inline
+  /// code, existing links and tags are skipped.
+  pub fn find(&self, contents: &str) -> Vec<(usize, usize, &MentionTarget)> {
+    let mut found = Vec::new();
+    for (offset, segment) in searchable_segments(contents) {
+      let (text, spans) = normalize(segment);
+      let bytes = text.as_bytes();
+      let mut candidates: Vec<(usize, usize, usize)> = self
+        .automaton
+        .find_overlapping_iter(&text)
+        .filter(|m| m.start() == 0 || bytes[m.start() - 1] == b' ')
+        .filter(|m| m.end() == bytes.len() || bytes[m.end()] == b' ')
+        .map(|m| (m.start(), m.end(), self.pattern_targets[m.pattern().as_usize()]))
+        .collect();
+      candidates.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+
+      let mut covered = 0;
+      for (start, end, target) in candidates {
+        if start < covered {
+          continue;
+        }
+        covered = end;
+        found.push((
+          offset + spans[start].0,
+          offset + spans[end - 1].1,
+          &self.targets[target],
+        ));
+      }
+    }
+    found
+  }
+}
+
+/// Mentions of project files and codex entities in every markdown file, or
+/// only in `only`. A file's mentions of itself are left out. Files without
+/// mentions are not listed.
+pub fn detect_mentions(project_root: &str, only: Option<&str>) -> Result<Vec<FileMentions>> {
+  let root = Path::new(project_root);
+  let files = project_files(project_root)?;
+  let resolver = LinkResolver::new(files.clone());
+  let documents: Vec<(String, String)> = files
+    .iter()
+    .filter(|f| markdown_stem(f).is_some())
+    .filter_map(|f| match fs::read_to_string(root.join(f)) {
+      Ok(contents) => Some((f.clone(), contents)),
+      Err(err) => {
+        eprintln!("[Mentions] Skipping {f}: {err}");
+        None
+      }
+    })
+    .collect();
+
+  let mut names = Vec::new();
+  for (path, contents) in &documents {
+    let (frontmatter, body) = split_frontmatter(contents);
+    let frontmatter = frontmatter.unwrap_or_default();
+    let stem = markdown_stem(path.rsplit('/').next().unwrap_or(path)).unwrap_or(path);
+    let link = if resolver.resolve_wiki(stem) == Some(path.as_str()) {
+      stem.to_string()
+    } else {
+      markdown_stem(path).unwrap_or(path).to_string()
+    };
+    let name = frontmatter.title.clone().unwrap_or_else(|| stem.to_string());
+    let mut file_names = vec![stem.to_string()];
+    file_names.extend(frontmatter.title);
+    file_names.extend(frontmatter.aliases);
+    names.push((
+      MentionTarget {
+        path: path.clone(),
+        tag: format!("#{}", slugify(stem)),
+        name,
+        link: link.clone(),
+      },
+      file_names,
+    ));
+
+    for block in parse_codex_blocks(body) {
+      names.push((
+        MentionTarget {
+          path: path.clone(),
+          tag: format!("#{}", slugify(&block.name)),
+          name: block.name.clone(),
+          link: link.clone(),
+        },
+        vec![block.name],
+      ));
+    }
+  }
+  let detector = MentionDetector::new(names)?;
+
+  let mut results = Vec::new();
+  for (path, contents) in &documents {
+    if only.is_some_and(|only| only != path) {
+      continue;
+    }
+    let mentions: Vec<Mention> = detector
+      .find(contents)
+      .into_iter()
+      .filter(|(_, _, target)| target.path != *path)
+      .map(|(start, end, target)| Mention {
+        line: contents[..start].matches('\n').count() + 1,
+        column: column(contents, start),
+        length: contents[start..end].chars().count(),
+        text: contents[start..end].to_string(),
+        target: target.clone(),
+      })
+      .collect();
+    if mentions.is_empty() {
+      continue;
+    }
+
+    let current_tags: Vec<String> = document_tags(contents).iter().map(|tag| format!("#{tag}")).collect();
+    let mut suggested_tags: Vec<String> = Vec::new();
+    for mention in &mentions {
+      if !current_tags.contains(&mention.target.tag) && !suggested_tags.contains(&mention.target.tag) {
+        suggested_tags.push(mention.target.tag.clone());
+      }
+    }
+
+    results.push(FileMentions {
+      path: path.clone(),
+      current_tags,
+      suggested_tags,
+      mentions,
+    });
+  }
+  Ok(results)
+}
+
+/// Lowercase words separated by single spaces, with possessive `'s` dropped,
+/// and for each byte of it the byte range of the character it came from.
+fn normalize(text: &str) -> (String, Vec<(usize, usize)>) {
+  let mut out = String::with_capacity(text.len());
+  let mut spans = Vec::with_capacity(text.len());
+  let chars: Vec<(usize, char)> = text.char_indices().collect();
+  let mut i = 0;
+  while i < chars.len() {
+    let (start, c) = chars[i];
+    let end = chars.get(i + 1).map(|(idx, _)| *idx).unwrap_or(text.len());
+    let is_word = |idx: usize| chars.get(idx).is_some_and(|(_, c)| c.is_alphanumeric());
+    if c.is_alphanumeric() {
+      for lower in c.to_lowercase() {
+        for _ in 0..lower.len_utf8() {
+          spans.push((start, end));
+        }
+        out.push(lower);
+      }
+    } else if matches!(c, '\'' | '\u{2019}') && i > 0 && is_word(i - 1) {
+      let next_is_s = chars.get(i + 1).is_some_and(|(_, c)| matches!(c, 's' | 'S'));
+      if next_is_s && !is_word(i + 2) {
+        // `Guild's`
+        i += 2;
+        continue;
+      }
+      if !is_word(i + 1) {
+        // `Wizards'`
+        i += 1;
+        continue;
+      }
+      // `O'Brien`
+      out.push('\'');
+      spans.push((start, end));
+    } else if !out.is_empty() && !out.ends_with(' ') {
+      out.push(' ');
+      spans.push((start, end));
+    }
+    i += 1;
+  }
+  (out, spans)
+}
+
+/// A normalized name and its plural forms.
+fn plural_forms(name: &str) -> Vec<String> {
+  let mut forms = vec![name.to_string(), format!("{name}s")];
+  if ["s", "x", "z", "ch", "sh"].iter().any(|end| name.ends_with(end)) {
+    forms.push(format!("{name}es"));
+  }
+  if let Some(stem) = name.strip_suffix('y') {
+    if !stem.ends_with(['a', 'e', 'i', 'o', 'u']) {
+      forms.push(format!("{stem}ies"));
+    }
+  }
+  forms
+}
+
+/// Parts of a document mentions are looked for in, with their byte offsets:
+/// the body after any frontmatter, outside code blocks, inline code, links
+/// and tags.
+fn searchable_segments(contents: &str) -> Vec<(usize, &str)> {
+  let (_, body) = split_frontmatter(contents);
+  let mut offset = contents.len() - body.len();
+  let mut segments = Vec::new();
+  let mut fence = FenceTracker::default();
+  for raw in body.split_inclusive('\n') {
+    let line = raw.trim_end_matches(['\n', '\r']);
+    if !fence.update(line) {
+      let mut start = 0;
+      for (skip_start, skip_end) in skipped_spans(line) {
+        segments.push((offset + start, &line[start..skip_start]));
+        start = skip_end;
+      }
+      segments.push((offset + start, &line[start..]));
+    }
+    offset += raw.len();
+  }
+  segments
+}
+
+/// Byte ranges of inline code, wiki links, markdown links and `#tags` in a
+/// line, in order.
+fn skipped_spans(line: &str) -> Vec<(usize, usize)> {
+  let bytes = line.as_bytes();
+  let mut spans = Vec::new();
+  let mut i = 0;
+  while i < bytes.len() {
+    let close = match bytes[i] {
+      b'`' => {
+        let run = bytes[i..].iter().take_while(|b| **b == b'`').count();
+        let fence = &line[i..i + run];
+        line[i + run..].find(fence).map(|end| i + run + end + run)
+      }
+      b'[' if bytes.get(i + 1) == Some(&b'[') => line[i..].find("]]").map(|end| i + end + 2),
+      // `[label](dest)`, label included, since the text is already a link.
+      b'[' => line[i..]
+        .find(']')
+        .map(|close| i + close + 1)
+        .filter(|&after| bytes.get(after) == Some(&b'('))
+        .and_then(|after| line[after..].find(')').map(|end| after + end + 1)),
+      b'#' if i == 0 || bytes[i - 1].is_ascii_whitespace() => {
+        let len: usize = line[i + 1..]
+          .chars()
+          .take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '/'))
+          .map(char::len_utf8)
+          .sum();
+        (len > 0).then_some(i + 1 + len)
+      }
+      _ => None,
+    };
+    match close {
+      Some(end) => {
+        spans.push((i, end));
+        i = end;
+      }
+      None => i += 1,
+    }
+  }
+  spans
+}
@@ -0,0 +1,205 @@
+use serde::{Deserialize, Serialize};
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+/// How to settle one conflict when re-running a merge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+  /// Keep the text that is on disk (or in the editor) now.
+  Current,
+  /// Take the AI's version.
+  Proposed,
+  /// Keep the current text followed by the AI's version.
+  Both,
+}
+
+/// A region both sides changed differently since the base version.
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeConflict {
+  pub index: usize,
+  /// 1-based line of the `<<<<<<<` marker in `MergeResult::merged`.
+  pub start_line: usize,
+  pub base: String,
+  pub current: String,
+  pub proposed: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeResult {
+  /// Merged text. Unresolved conflicts are written with git-style markers.
+  pub merged: String,
+  pub conflicts: Vec<MergeConflict>,
+}
+
+const CONFLICT_START: &str = "<<<<<<< current";
+const CONFLICT_SEPARATOR: &str = "=======";
+const CONFLICT_END: &str = ">>>>>>> proposed";
+
+/// Line-based three-way merge of `current` and `proposed`, which were both
+/// derived from `base`. Changes made on only one side are taken as-is, the
+/// same change made on both sides is taken once, and overlapping different
+/// changes become conflicts.
+///
+/// `resolutions` settles conflicts by index; conflicts without one are left
+/// in the output with markers and reported.
+pub fn merge_three_way(
+  base: &str,
+  current: &str,
+  proposed: &str,
+  resolutions: &[Resolution],
+) -> MergeResult {
+  let base_lines: Vec<&str> = base.split_inclusive('\n').collect();
+  let current_lines: Vec<&str> = current.split_inclusive('\n').collect();
+  let proposed_lines: Vec<&str> = proposed.split_inclusive('\n').collect();
+
+  let mut regions = changed_regions(&base_lines, &current_lines, Side::Current);
+  regions.extend(changed_regions(&base_lines, &proposed_lines, Side::Proposed));
+  regions.sort_by_key(|r| (r.start, r.end));
+
+  let mut merged = String::with_capacity(current.len().max(proposed.len()));
+  let mut conflicts = Vec::new();
+  let mut conflict_index = 0;
+  let mut cursor = 0;
+  let mut idx = 0;
+
+  while idx < regions.len() {
+    // Group regions that overlap or touch at an insertion point, since their
+    // relative order cannot be decided line by line.
+    let group_start = regions[idx].start;
+    let mut group_end = regions[idx].end;
+    let mut next = idx + 1;
+    while next < regions.len() {
+      let r = &regions[next];
+      let touches = r.start == group_end && (group_start == group_end || r.start == r.end);
+      if r.start < group_end || touches {
+        group_end = group_end.max(r.end);
+        next += 1;
+      } else {
+        break;
+      }
+    }
+    let group = &regions[idx..next];
+
+    for line in &base_lines[cursor..group_start] {
+      merged.push_str(line);
+    }
+
+    let current_text = apply_side(&base_lines, group_start, group_end, group, Side::Current);
+    let proposed_text = apply_side(&base_lines, group_start, group_end, group, Side::Proposed);
+    let has_current = group.iter().any(|r| r.side == Side::Current);
+    let has_proposed = group.iter().any(|r| r.side == Side::Proposed);
+
+    if !has_current || !has_proposed || current_text == proposed_text {
+      merged.push_str(if has_current { &current_text } else { &proposed_text });
+    } else {
+      match resolutions.get(conflict_index) {
+        Some(Resolution::Current) => merged.push_str(&current_text),
+        Some(Resolution::Proposed) => merged.push_str(&proposed_text),
+        Some(Resolution::Both) => {
+          merged.push_str(&current_text);
+          push_line_break(&mut merged);
+          merged.push_str(&proposed_text);
+        }
+        None => {
+          push_line_break(&mut merged);
+          conflicts.push(MergeConflict {
+            index: conflict_index,
+            start_line: merged.matches('\n').count() + 1,
+            base: base_lines[group_start..group_end].concat(),
+            current: current_text.clone(),
+            proposed: proposed_text.clone(),
+          });
+          merged.push_str(CONFLICT_START);
+          merged.push('\n');
+          merged.push_str(&current_text);
+          push_line_break(&mut merged);
+          merged.push_str(CONFLICT_SEPARATOR);
+          merged.push('\n');
+          merged.push_str(&proposed_text);
+          push_line_break(&mut merged);
+          merged.push_str(CONFLICT_END);
+          merged.push('\n');
+        }
+      }
+      conflict_index += 1;
+    }
+
+    cursor = group_end;
+    idx = next;
+  }
+
+  for line in &base_lines[cursor..] {
+    merged.push_str(line);
+  }
+
+  MergeResult { merged, conflicts }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+  Current,
+  Proposed,
+}
+
+/// A run of base lines `[start, end)` replaced by `lines` on one side.
+#[derive(Debug)]
+struct Region<'a> {
+  side: Side,
+  start: usize,
+  end: usize,
+  lines: Vec<&'a str>,
+}
+
+fn changed_regions<'a>(base: &[&str], changed: &[&'a str], side: Side) -> Vec<Region<'a>> {
+  let mut regions: Vec<Region<'a>> = Vec::new();
+  for op in capture_diff_slices(Algorithm::Myers, base, changed) {
+    let (start, end, new_range) = match op {
+      DiffOp::Equal { .. } => continue,
+      DiffOp::Delete { old_index, old_len, new_index } => (old_index, old_index + old_len, new_index..new_index),
+      DiffOp::Insert { old_index, new_index, new_len } => (old_index, old_index, new_index..new_index + new_len),
+      DiffOp::Replace { old_index, old_len, new_index, new_len } => {
+        (old_index, old_index + old_len, new_index..new_index + new_len)
+      }
+    };
+    // Adjacent delete/insert ops describe one replacement.
+    match regions.last_mut() {
+      Some(last) if last.end == start => {
+        last.end = end;
+        last.lines.extend_from_slice(&changed[new_range]);
+      }
+      _ => regions.push(Region {
+        side,
+        start,
+        end,
+        lines: changed[new_range].to_vec(),
+      }),
+    }
+  }
+  regions
+}
+
+/// Base lines `[start, end)` with one side's regions from the group applied.
+fn apply_side(base: &[&str], start: usize, end: usize, group: &[Region<'_>], side: Side) -> String {
+  let mut text = String::new();
+  let mut cursor = start;
+  for region in group.iter().filter(|r| r.side == side) {
+    for line in &base[cursor..region.start] {
+      text.push_str(line);
+    }
+    for line in &region.lines {
+      text.push_str(line);
+    }
+    cursor = region.end;
+  }
+  for line in &base[cursor..end] {
+    text.push_str(line);
+  }
+  text
+}
+
+/// Text from a side may lack a final newline when it ends the file.
+fn push_line_break(text: &mut String) {
+  if !text.is_empty() && !text.ends_with('\n') {
+    text.push('\n');
+  }
+}
@@ -1 +1,21 @@
+pub mod git;
+pub mod graph;
+pub mod graph_export;
 pub mod indexer;
+pub mod link_check;
+pub mod link_index;
+pub mod links;
+pub mod markdown;
+pub mod mentions;
+pub mod merge;
+pub mod patch;
+pub mod paths;
+pub mod rename;
+pub mod replace;
+pub mod search;
+pub mod semantic_tags;
+pub mod snapshots;
+pub mod transactions;
+pub mod trash;
+pub mod tree;
+pub mod versions;
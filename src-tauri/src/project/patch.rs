@@ -0,0 +1,305 @@
+use serde::Serialize;
+
+/// One search/replace step of a patch.
+#[derive(Debug, Clone)]
+pub struct Hunk {
+  pub search: String,
+  pub replace: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HunkResult {
+  pub index: usize,
+  pub applied: bool,
+  /// True when the hunk only matched after normalizing whitespace.
+  pub fuzzy: bool,
+  pub error: Option<String>,
+  /// First lines of the search text, to help identify the hunk in the UI.
+  pub search_preview: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PatchOutcome {
+  /// The patched text. `None` unless every hunk applied.
+  pub contents: Option<String>,
+  pub hunks: Vec<HunkResult>,
+}
+
+impl PatchOutcome {
+  pub fn failed_hunks(&self) -> impl Iterator<Item = &HunkResult> {
+    self.hunks.iter().filter(|h| !h.applied)
+  }
+}
+
+/// Parse a patch written either as search/replace blocks
+///
+/// ```text
+/// <<<<<<< SEARCH
+/// old text
+/// =======
+/// new text
+/// >>>>>>> REPLACE
+/// ```
+///
+/// or as a unified diff (`@@ ... @@` hunks; line numbers are ignored and the
+/// context lines are matched instead).
+pub fn parse_patch(patch: &str) -> Result<Vec<Hunk>, String> {
+  if patch.lines().any(|l| l.trim_end() == "<<<<<<< SEARCH") {
+    parse_search_replace(patch)
+  } else if patch.lines().any(|l| l.starts_with("@@")) {
+    Ok(parse_unified_diff(patch))
+  } else {
+    Err("Patch contains no SEARCH/REPLACE blocks or unified diff hunks".to_string())
+  }
+}
+
+fn parse_search_replace(patch: &str) -> Result<Vec<Hunk>, String> {
+  enum State {
+    Outside,
+    Search,
+    Replace,
+  }
+
+  let mut hunks = Vec::new();
+  let mut state = State::Outside;
+  let mut search = Vec::new();
+  let mut replace = Vec::new();
+
+  for line in patch.lines() {
+    let marker = line.trim_end();
+    match state {
+      State::Outside => {
+        if marker == "<<<<<<< SEARCH" {
+          state = State::Search;
+        }
+      }
+      State::Search => {
+        if marker == "=======" {
+          state = State::Replace;
+        } else {
+          search.push(line);
+        }
+      }
+      State::Replace => {
+        if marker == ">>>>>>> REPLACE" {
+          hunks.push(Hunk {
+            search: join_lines(&search),
+            replace: join_lines(&replace),
+          });
+          search.clear();
+          replace.clear();
+          state = State::Outside;
+        } else {
+          replace.push(line);
+        }
+      }
+    }
+  }
+
+  match state {
+    State::Outside => Ok(hunks),
+    _ => Err(format!(
+      "Patch block {} is not terminated with '>>>>>>> REPLACE'",
+      hunks.len() + 1
+    )),
+  }
+}
+
+fn parse_unified_diff(patch: &str) -> Vec<Hunk> {
+  let mut hunks = Vec::new();
+  let mut search: Vec<&str> = Vec::new();
+  let mut replace: Vec<&str> = Vec::new();
+  let mut in_hunk = false;
+
+  let mut flush = |search: &mut Vec<&str>, replace: &mut Vec<&str>| {
+    if !search.is_empty() || !replace.is_empty() {
+      hunks.push(Hunk {
+        search: join_lines(search),
+        replace: join_lines(replace),
+      });
+    }
+    search.clear();
+    replace.clear();
+  };
+
+  let lines: Vec<&str> = patch.lines().collect();
+  for (idx, line) in lines.iter().copied().enumerate() {
+    if line.starts_with("@@") {
+      flush(&mut search, &mut replace);
+      in_hunk = true;
+      continue;
+    }
+    // Inside a hunk, `--- ` and `+++ ` are usually removed `-- ` and added
+    // `++ ` lines. They only start the next file's header when the pair is
+    // followed by a hunk header; `diff --git` always does.
+    let file_header = line.starts_with("diff ")
+      || (line.starts_with("--- ")
+        && lines.get(idx + 1).is_some_and(|next| next.starts_with("+++ "))
+        && lines.get(idx + 2).is_some_and(|next| next.starts_with("@@")));
+    if file_header {
+      in_hunk = false;
+    }
+    if !in_hunk || line.starts_with('\\') {
+      continue;
+    }
+    if let Some(removed) = line.strip_prefix('-') {
+      search.push(removed);
+    } else if let Some(added) = line.strip_prefix('+') {
+      replace.push(added);
+    } else {
+      // Context line; models often drop the leading space on blank lines.
+      let context = line.strip_prefix(' ').unwrap_or(line);
+      search.push(context);
+      replace.push(context);
+    }
+  }
+  flush(&mut search, &mut replace);
+
+  hunks
+}
+
+/// Apply hunks in order. Each hunk must match exactly one place in the text,
+/// first verbatim and then ignoring differences in whitespace. The result is
+/// only returned when every hunk applied, so callers never write a partially
+/// patched file.
+pub fn apply_hunks(original: &str, hunks: &[Hunk]) -> PatchOutcome {
+  let mut text = original.to_string();
+  let mut results = Vec::new();
+
+  for (index, hunk) in hunks.iter().enumerate() {
+    let mut result = HunkResult {
+      index,
+      applied: false,
+      fuzzy: false,
+      error: None,
+      search_preview: hunk.search.lines().take(3).collect::<Vec<_>>().join("\n"),
+    };
+
+    match apply_hunk(&text, hunk) {
+      Ok((patched, fuzzy)) => {
+        text = patched;
+        result.applied = true;
+        result.fuzzy = fuzzy;
+      }
+      Err(err) => result.error = Some(err),
+    }
+    results.push(result);
+  }
+
+  let all_applied = results.iter().all(|r| r.applied);
+  PatchOutcome {
+    contents: if all_applied { Some(text) } else { None },
+    hunks: results,
+  }
+}
+
+/// Parse and apply in one step.
+pub fn apply_patch(original: &str, patch: &str) -> Result<PatchOutcome, String> {
+  let hunks = parse_patch(patch)?;
+  Ok(apply_hunks(original, &hunks))
+}
+
+fn apply_hunk(text: &str, hunk: &Hunk) -> Result<(String, bool), String> {
+  if hunk.search.trim().is_empty() {
+    if text.trim().is_empty() {
+      return Ok((ensure_trailing_newline(&hunk.replace), false));
+    }
+    return Err("Empty search text is only allowed when creating a new file".to_string());
+  }
+
+  // 1. Verbatim match, including the trailing newline of the block.
+  let needle = ensure_trailing_newline(&hunk.search);
+  let exact: Vec<usize> = match_indices_at_line_starts(text, &needle);
+  match exact.len() {
+    1 => {
+      let start = exact[0];
+      let mut patched = String::with_capacity(text.len());
+      patched.push_str(&text[..start]);
+      patched.push_str(&ensure_trailing_newline(&hunk.replace));
+      patched.push_str(&text[start + needle.len()..]);
+      return Ok((patched, false));
+    }
+    n if n > 1 => {
+      return Err(format!(
+        "Search text matches {n} places; include more surrounding lines to make it unique"
+      ))
+    }
+    _ => {}
+  }
+
+  // 2. Line-based match ignoring indentation and runs of whitespace.
+  let lines: Vec<&str> = text.split_inclusive('\n').collect();
+  let search_lines: Vec<String> = trim_blank_edges(&hunk.search)
+    .lines()
+    .map(normalize_whitespace)
+    .collect();
+  if search_lines.is_empty() {
+    return Err("Search text not found".to_string());
+  }
+
+  let normalized: Vec<String> = lines.iter().map(|l| normalize_whitespace(l)).collect();
+  let matches: Vec<usize> = (0..lines.len().saturating_sub(search_lines.len() - 1))
+    .filter(|&start| normalized[start..start + search_lines.len()] == search_lines[..])
+    .collect();
+
+  match matches.len() {
+    0 => Err("Search text not found in the file".to_string()),
+    1 => {
+      let start = matches[0];
+      let end = start + search_lines.len();
+      let mut patched = String::with_capacity(text.len());
+      patched.extend(lines[..start].iter().copied());
+      let replacement = trim_blank_edges(&hunk.replace);
+      if !replacement.is_empty() {
+        patched.push_str(&ensure_trailing_newline(replacement));
+      }
+      patched.extend(lines[end..].iter().copied());
+      Ok((patched, true))
+    }
+    n => Err(format!(
+      "Search text matches {n} places; include more surrounding lines to make it unique"
+    )),
+  }
+}
+
+/// Byte offsets where `needle` occurs starting at the beginning of a line.
+fn match_indices_at_line_starts(text: &str, needle: &str) -> Vec<usize> {
+  text
+    .match_indices(needle)
+    .map(|(idx, _)| idx)
+    .filter(|&idx| idx == 0 || text.as_bytes()[idx - 1] == b'\n')
+    .collect()
+}
+
+fn normalize_whitespace(line: &str) -> String {
+  line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn trim_blank_edges(text: &str) -> &str {
+  let start = text
+    .char_indices()
+    .find(|(_, c)| !c.is_whitespace())
+    .map(|(idx, _)| text[..idx].rfind('\n').map(|n| n + 1).unwrap_or(0))
+    .unwrap_or(text.len());
+  let trimmed_end = text.trim_end();
+  if start >= trimmed_end.len() {
+    return "";
+  }
+  &text[start..trimmed_end.len()]
+}
+
+fn ensure_trailing_newline(text: &str) -> String {
+  if text.is_empty() || text.ends_with('\n') {
+    text.to_string()
+  } else {
+    format!("{text}\n")
+  }
+}
+
+fn join_lines(lines: &[&str]) -> String {
+  if lines.is_empty() {
+    String::new()
+  } else {
+    format!("{}\n", lines.join("\n"))
+  }
+}
@@ -0,0 +1,113 @@
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::util::error::{Error, Result};
+
+/// Directories inside a project that file commands and AI edits may read but
+/// never write.
+const PROTECTED_DIRS: [&str; 2] = [".git", ".codexlotus"];
+
+/// What the caller is going to do with a resolved path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+  Read,
+  Write,
+}
+
+/// Resolve `path` to a canonical location inside the project.
+///
+/// `path` may be relative to the project root or absolute. Symlinks are
+/// resolved before checking containment, so a link pointing outside the
+/// project is rejected like a `..` path. Paths that do not exist yet are
+/// resolved through their nearest existing ancestor. Writes into `.git` and
+/// `.codexlotus` are refused.
+pub fn resolve_project_path(project_root: &str, path: &str, access: Access) -> Result<PathBuf> {
+  let root = fs::canonicalize(project_root)
+    .map_err(|err| rejected(format!("Project folder '{project_root}' is not accessible: {err}")))?;
+  if !root.is_dir() {
+    return Err(rejected(format!("Project root '{project_root}' is not a directory")));
+  }
+
+  let candidate = Path::new(path);
+  if candidate
+    .components()
+    .any(|c| matches!(c, Component::ParentDir))
+  {
+    return Err(rejected(format!("Path '{path}' must not contain '..'")));
+  }
+  let joined = if candidate.is_absolute() {
+    candidate.to_path_buf()
+  } else {
+    if let Some(problem) = validate_relative_path(path) {
+      return Err(rejected(problem));
+    }
+    root.join(candidate)
+  };
+
+  let resolved = canonicalize_lenient(&joined)
+    .map_err(|err| rejected(format!("Could not resolve '{path}': {err}")))?;
+  let Ok(relative) = resolved.strip_prefix(&root) else {
+    return Err(rejected(format!("Path '{path}' is outside the project")));
+  };
+
+  if access == Access::Write {
+    let first = relative.components().next().map(|c| c.as_os_str().to_string_lossy().to_lowercase());
+    if let Some(dir) = first.filter(|dir| PROTECTED_DIRS.contains(&dir.as_str())) {
+      return Err(rejected(format!("Writing into '{dir}' is not allowed")));
+    }
+  }
+
+  Ok(resolved)
+}
+
+/// Path of an already resolved location relative to the project root, with
+/// `/` separators. Used as a stable key for per-file data.
+pub fn project_relative_path(project_root: &str, resolved: &Path) -> Result<String> {
+  let root = fs::canonicalize(project_root)
+    .map_err(|err| rejected(format!("Project folder '{project_root}' is not accessible: {err}")))?;
+  let relative = resolved
+    .strip_prefix(&root)
+    .map_err(|_| rejected(format!("{:?} is outside the project", resolved)))?;
+  Ok(relative.to_string_lossy().replace('\\', "/"))
+}
+
+/// Reject paths that are absolute or climb out of the project.
+pub fn validate_relative_path(path: &str) -> Option<String> {
+  let candidate = Path::new(path);
+  if candidate.is_absolute() || path.starts_with('/') || path.starts_with('\\') {
+    return Some(format!("Path '{path}' must be relative to the project root"));
+  }
+  if candidate
+    .components()
+    .any(|c| matches!(c, Component::ParentDir | Component::Prefix(_)))
+  {
+    return Some(format!("Path '{path}' points outside the project"));
+  }
+  None
+}
+
+/// Like `fs::canonicalize`, but allows the trailing components not to exist
+/// yet. An entry that exists only as a dangling symlink is not treated as
+/// missing, so it cannot be used to write through to another location.
+fn canonicalize_lenient(path: &Path) -> std::io::Result<PathBuf> {
+  let mut existing = path;
+  let mut missing: Vec<OsString> = Vec::new();
+  while fs::symlink_metadata(existing).is_err() {
+    let (Some(name), Some(parent)) = (existing.file_name(), existing.parent()) else {
+      break;
+    };
+    missing.push(name.to_os_string());
+    existing = parent;
+  }
+
+  let mut resolved = fs::canonicalize(existing)?;
+  for name in missing.iter().rev() {
+    resolved.push(name);
+  }
+  Ok(resolved)
+}
+
+fn rejected(message: String) -> Error {
+  Error::Anyhow(anyhow::Error::msg(message))
+}
@@ -0,0 +1,194 @@
+use std::fs;
+use std::path::Path;
+
+use crate::db::embeddings::EmbeddingDb;
+use crate::util::error::{Error, Result};
+
+use super::links::{link_text, markdown_stem, markdown_target_path, parse_links, project_files, LinkKind, LinkResolver};
+use super::paths::{project_relative_path, resolve_project_path, Access};
+use super::transactions::{EditRequest, EditTransaction, FileMove, TransactionStatus};
+
+/// A rename of a file or folder together with the link updates it needs.
+#[derive(Debug, Clone)]
+pub struct RenamePlan {
+  pub file_move: FileMove,
+  /// Files whose links were rewritten, at their paths after the move.
+  pub edits: Vec<EditRequest>,
+  /// Number of links rewritten across all files.
+  pub updated_links: usize,
+}
+
+impl RenamePlan {
+  /// Undo history label, e.g. `Rename rules/combat.md to rules/fighting.md`.
+  pub fn label(&self) -> String {
+    let FileMove { from, to } = &self.file_move;
+    let verb = if parent(from) == parent(to) { "Rename" } else { "Move" };
+    format!("{verb} {from} to {to}")
+  }
+}
+
+/// Work out how moving `from` (a file or folder) to `to` changes every link
+/// that points into it, and every relative link inside it. Wiki links keep
+/// their style: `[[Combat]]` stays a bare name unless the new name is
+/// ambiguous, `[[rules/Combat]]` stays a path. Heading anchors and labels are
+/// left as written.
+pub fn plan_rename(project_root: &str, from: &str, to: &str) -> Result<RenamePlan> {
+  let source = resolve_project_path(project_root, from, Access::Write)?;
+  let target = resolve_project_path(project_root, to, Access::Write)?;
+  let from = project_relative_path(project_root, &source)?;
+  let to = project_relative_path(project_root, &target)?;
+
+  if from.is_empty() || to.is_empty() {
+    return Err(Error::Anyhow(anyhow::Error::msg("The project folder itself cannot be moved")));
+  }
+  if !source.exists() {
+    return Err(Error::Anyhow(anyhow::anyhow!("'{from}' does not exist")));
+  }
+  if target.exists() {
+    return Err(Error::Anyhow(anyhow::anyhow!("'{to}' already exists")));
+  }
+  if to.starts_with(&format!("{from}/")) {
+    return Err(Error::Anyhow(anyhow::anyhow!("Cannot move '{from}' into itself")));
+  }
+
+  let file_move = FileMove { from, to };
+  let files = project_files(project_root)?;
+  let before = LinkResolver::new(files.clone());
+  let after = LinkResolver::new(
+    files
+      .iter()
+      .map(|f| moved_path(&file_move, f).unwrap_or_else(|| f.clone()))
+      .collect(),
+  );
+
+  let mut edits = Vec::new();
+  let mut updated_links = 0;
+  for path in files.iter().filter(|f| markdown_stem(f).is_some()) {
+    let contents = match fs::read_to_string(Path::new(project_root).join(path)) {
+      Ok(contents) => contents,
+      Err(err) => {
+        eprintln!("[Rename] Skipping {path}: {err}");
+        continue;
+      }
+    };
+    let new_path = moved_path(&file_move, path).unwrap_or_else(|| path.clone());
+    let rewrites = rewrite_links(&file_move, path, &new_path, &contents, &before, &after);
+    if rewrites.is_empty() {
+      continue;
+    }
+    updated_links += rewrites.len();
+    edits.push(EditRequest {
+      path: new_path,
+      contents: Some(apply_rewrites(&contents, &rewrites)),
+    });
+  }
+
+  Ok(RenamePlan {
+    file_move,
+    edits,
+    updated_links,
+  })
+}
+
+/// Keep the embedding index in step with a transaction that moved files, in
+/// the direction it was last applied or undone. Rewritten link text is
+/// stored as-is; the embeddings are refreshed on the next full index.
+pub fn update_embedding_index(project_root: &str, tx: &EditTransaction) {
+  if tx.moves.is_empty() {
+    return;
+  }
+  let result = EmbeddingDb::open_for_project(project_root).and_then(|mut db| {
+    if tx.status == TransactionStatus::Applied {
+      move_index_paths(&mut db, project_root, tx)?;
+    }
+    for change in &tx.changes {
+      let contents = match tx.status {
+        TransactionStatus::Applied => &change.after,
+        TransactionStatus::Undone => &change.before,
+      };
+      if let Some(contents) = contents {
+        db.update_file_content(project_root, &change.path, contents)?;
+      }
+    }
+    if tx.status == TransactionStatus::Undone {
+      move_index_paths(&mut db, project_root, tx)?;
+    }
+    Ok(())
+  });
+  if let Err(err) = result {
+    eprintln!("[Rename] Failed to update the embedding index: {err}");
+  }
+}
+
+fn move_index_paths(db: &mut EmbeddingDb, project_root: &str, tx: &EditTransaction) -> Result<()> {
+  match tx.status {
+    TransactionStatus::Applied => {
+      for file_move in &tx.moves {
+        db.rename_path(project_root, &file_move.from, &file_move.to)?;
+      }
+    }
+    TransactionStatus::Undone => {
+      for file_move in tx.moves.iter().rev() {
+        db.rename_path(project_root, &file_move.to, &file_move.from)?;
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Where `path` ends up after the move, or `None` if it is not moved.
+fn moved_path(file_move: &FileMove, path: &str) -> Option<String> {
+  if path == file_move.from {
+    return Some(file_move.to.clone());
+  }
+  path
+    .strip_prefix(&file_move.from)
+    .filter(|rest| rest.starts_with('/'))
+    .map(|rest| format!("{}{rest}", file_move.to))
+}
+
+/// Byte ranges of link targets in a document and their new text.
+fn rewrite_links(
+  file_move: &FileMove,
+  old_path: &str,
+  new_path: &str,
+  contents: &str,
+  before: &LinkResolver,
+  after: &LinkResolver,
+) -> Vec<(usize, usize, String)> {
+  let mut rewrites = Vec::new();
+  for link in parse_links(contents) {
+    let Some(old_target) = before.resolve(old_path, &link) else {
+      continue;
+    };
+    let new_target = moved_path(file_move, old_target).unwrap_or_else(|| old_target.to_string());
+    let unchanged = match link.kind {
+      LinkKind::Wiki => new_target == old_target,
+      LinkKind::Markdown => markdown_target_path(new_path, &link.target).as_deref() == Some(new_target.as_str()),
+    };
+    if unchanged {
+      continue;
+    }
+    let replacement = link_text(&link, new_path, &new_target, after);
+    if replacement != link.target {
+      rewrites.push((link.start, link.end, replacement));
+    }
+  }
+  rewrites
+}
+
+fn apply_rewrites(contents: &str, rewrites: &[(usize, usize, String)]) -> String {
+  let mut output = String::with_capacity(contents.len());
+  let mut cursor = 0;
+  for (start, end, replacement) in rewrites {
+    output.push_str(&contents[cursor..*start]);
+    output.push_str(replacement);
+    cursor = *end;
+  }
+  output.push_str(&contents[cursor..]);
+  output
+}
+
+fn parent(path: &str) -> &str {
+  path.rsplit_once('/').map(|(parent, _)| parent).unwrap_or("")
+}
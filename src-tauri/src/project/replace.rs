@@ -0,0 +1,242 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::util::error::{Error, Result};
+
+use super::paths::{project_relative_path, resolve_project_path, Access};
+use super::search::{build_pattern, for_each_text_file, SearchOptions};
+use super::transactions::EditRequest;
+use super::versions::{ensure_version, version_token};
+
+/// The preview stops adding files once it lists this many changes.
+const MAX_PREVIEW_CHANGES: usize = 5000;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReplaceOptions {
+  #[serde(flatten)]
+  pub search: SearchOptions,
+  /// Replacement text. In regex mode `$1`, `${name}` and `$$` refer to
+  /// capture groups and a literal `$`; otherwise it is used as-is.
+  pub replacement: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplacementChange {
+  /// Position among the file's matches; used to select changes to apply.
+  pub index: usize,
+  /// 1-based line number.
+  pub line: usize,
+  /// 1-based column, counted in characters.
+  pub column: usize,
+  pub matched: String,
+  pub replacement: String,
+  pub line_text: String,
+  /// `line_text` with only this change applied.
+  pub new_line_text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReplacePreview {
+  pub path: String,
+  /// Version the preview was computed from; applying fails if the file has
+  /// changed since.
+  pub version: String,
+  pub changes: Vec<ReplacementChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplacePreview {
+  pub files: Vec<FileReplacePreview>,
+  pub total_changes: usize,
+  /// True when files were left out to keep the preview small.
+  pub truncated: bool,
+}
+
+/// Changes to apply in one file: the indices of `ReplacementChange`s from a
+/// preview computed at `version`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReplaceSelection {
+  pub path: String,
+  pub version: String,
+  pub indices: Vec<usize>,
+}
+
+/// Dry run of a project-wide replace. Files are searched like
+/// `search::search_project`, line by line.
+pub fn preview_replace(project_root: &str, options: &ReplaceOptions) -> Result<ReplacePreview> {
+  let root = Path::new(project_root);
+  if !root.is_dir() {
+    return Err(Error::Anyhow(anyhow::anyhow!(
+      "Project root is not a directory: {project_root}"
+    )));
+  }
+  let pattern = pattern_for(options)?;
+
+  let preview = Mutex::new(ReplacePreview {
+    files: Vec::new(),
+    total_changes: 0,
+    truncated: false,
+  });
+
+  for_each_text_file(root, |relative, path, contents| {
+    let occurrences = find_occurrences(contents, &pattern, options);
+    if occurrences.is_empty() {
+      return true;
+    }
+    let version = match version_token(path, contents.as_bytes()) {
+      Ok(version) => version,
+      Err(err) => {
+        eprintln!("[Replace] Skipping {relative}: {err}");
+        return true;
+      }
+    };
+
+    let mut preview = preview.lock().expect("replace preview lock poisoned");
+    if preview.total_changes >= MAX_PREVIEW_CHANGES {
+      preview.truncated = true;
+      return false;
+    }
+    preview.total_changes += occurrences.len();
+    preview.files.push(FileReplacePreview {
+      path: relative.to_string(),
+      version,
+      changes: describe_changes(contents, &occurrences),
+    });
+    true
+  });
+
+  let mut preview = preview.into_inner().expect("replace preview lock poisoned");
+  preview.files.sort_by(|a, b| a.path.cmp(&b.path));
+  Ok(preview)
+}
+
+/// New contents for each selected file. Every file must still be at the
+/// version its preview was computed from, so the selected indices refer to
+/// the same matches the user reviewed.
+pub fn replacement_edits(
+  project_root: &str,
+  options: &ReplaceOptions,
+  selections: &[ReplaceSelection],
+) -> Result<Vec<EditRequest>> {
+  let pattern = pattern_for(options)?;
+  let mut edits = Vec::new();
+
+  for selection in selections.iter().filter(|s| !s.indices.is_empty()) {
+    let resolved = resolve_project_path(project_root, &selection.path, Access::Write)?;
+    ensure_version(&resolved, &selection.path, &selection.version)?;
+    let contents = fs::read_to_string(&resolved)?;
+
+    let occurrences = find_occurrences(&contents, &pattern, options);
+    let selected: HashSet<usize> = selection.indices.iter().copied().collect();
+    if let Some(bad) = selected.iter().find(|&&idx| idx >= occurrences.len()) {
+      return Err(Error::Anyhow(anyhow::anyhow!(
+        "'{}' has no change {bad}; preview the replacement again",
+        selection.path
+      )));
+    }
+
+    edits.push(EditRequest {
+      path: project_relative_path(project_root, &resolved)?,
+      contents: Some(apply_occurrences(&contents, &occurrences, &selected)),
+    });
+  }
+
+  if edits.is_empty() {
+    return Err(Error::Anyhow(anyhow::Error::msg("No changes were selected")));
+  }
+  Ok(edits)
+}
+
+/// One match and what replaces it. `start`/`end` are byte offsets within the
+/// line, which excludes its line break.
+struct Occurrence {
+  line: usize,
+  start: usize,
+  end: usize,
+  replacement: String,
+}
+
+fn pattern_for(options: &ReplaceOptions) -> Result<Regex> {
+  if options.search.query.is_empty() {
+    return Err(Error::Anyhow(anyhow::Error::msg("The search text is empty")));
+  }
+  build_pattern(&options.search)
+}
+
+fn find_occurrences(contents: &str, pattern: &Regex, options: &ReplaceOptions) -> Vec<Occurrence> {
+  let mut occurrences = Vec::new();
+  for (line_idx, line) in contents.split_inclusive('\n').enumerate() {
+    let body = strip_line_break(line);
+    for caps in pattern.captures_iter(body) {
+      let found = caps.get(0).expect("group 0 is always present");
+      if found.start() == found.end() {
+        continue;
+      }
+      let mut replacement = String::new();
+      if options.search.regex {
+        caps.expand(&options.replacement, &mut replacement);
+      } else {
+        replacement.push_str(&options.replacement);
+      }
+      occurrences.push(Occurrence {
+        line: line_idx,
+        start: found.start(),
+        end: found.end(),
+        replacement,
+      });
+    }
+  }
+  occurrences
+}
+
+fn describe_changes(contents: &str, occurrences: &[Occurrence]) -> Vec<ReplacementChange> {
+  let lines: Vec<&str> = contents.split_inclusive('\n').map(strip_line_break).collect();
+  occurrences
+    .iter()
+    .enumerate()
+    .map(|(index, occurrence)| {
+      let line = lines[occurrence.line];
+      ReplacementChange {
+        index,
+        line: occurrence.line + 1,
+        column: line[..occurrence.start].chars().count() + 1,
+        matched: line[occurrence.start..occurrence.end].to_string(),
+        replacement: occurrence.replacement.clone(),
+        line_text: line.to_string(),
+        new_line_text: format!(
+          "{}{}{}",
+          &line[..occurrence.start],
+          occurrence.replacement,
+          &line[occurrence.end..]
+        ),
+      }
+    })
+    .collect()
+}
+
+fn apply_occurrences(contents: &str, occurrences: &[Occurrence], selected: &HashSet<usize>) -> String {
+  let mut output = String::with_capacity(contents.len());
+  let mut pending = occurrences.iter().enumerate().peekable();
+  for (line_idx, line) in contents.split_inclusive('\n').enumerate() {
+    let mut cursor = 0;
+    while let Some((index, occurrence)) = pending.next_if(|(_, o)| o.line == line_idx) {
+      if selected.contains(&index) {
+        output.push_str(&line[cursor..occurrence.start]);
+        output.push_str(&occurrence.replacement);
+        cursor = occurrence.end;
+      }
+    }
+    output.push_str(&line[cursor..]);
+  }
+  output
+}
+
+fn strip_line_break(line: &str) -> &str {
+  let line = line.strip_suffix('\n').unwrap_or(line);
+  line.strip_suffix('\r').unwrap_or(line)
+}
@@ -0,0 +1,234 @@
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use ignore::{WalkBuilder, WalkState};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::util::error::{Error, Result};
+
+const DEFAULT_MAX_RESULTS: usize = 1000;
+const DEFAULT_CONTEXT_LINES: usize = 2;
+const MAX_CONTEXT_LINES: usize = 10;
+/// Larger files are skipped; they are almost never hand-written text.
+const MAX_FILE_BYTES: u64 = 8 * 1024 * 1024;
+/// A NUL byte within this many leading bytes marks a file as binary.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchOptions {
+  pub query: String,
+  /// Treat `query` as a regular expression instead of literal text.
+  #[serde(default)]
+  pub regex: bool,
+  #[serde(default)]
+  pub case_sensitive: bool,
+  #[serde(default)]
+  pub whole_word: bool,
+  /// Lines of context before and after each match.
+  #[serde(default)]
+  pub context_lines: Option<usize>,
+  #[serde(default)]
+  pub max_results: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchMatch {
+  /// Relative to the project root, with `/` separators.
+  pub path: String,
+  /// 1-based line number.
+  pub line: usize,
+  /// 1-based column of the first matched character, counted in characters.
+  pub column: usize,
+  /// Length of the match in characters.
+  pub length: usize,
+  pub line_text: String,
+  pub context_before: Vec<String>,
+  pub context_after: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResults {
+  pub matches: Vec<SearchMatch>,
+  pub files_searched: usize,
+  /// True when the search stopped at `max_results`.
+  pub truncated: bool,
+}
+
+/// Search every text file in the project, in parallel. `.gitignore`,
+/// `.ignore` and hidden files (including `.git` and `.codexlotus`) are
+/// skipped. Matches are sorted by path and position.
+pub fn search_project(project_root: &str, options: &SearchOptions) -> Result<SearchResults> {
+  let root = Path::new(project_root);
+  if !root.is_dir() {
+    return Err(Error::Anyhow(anyhow::anyhow!(
+      "Project root is not a directory: {project_root}"
+    )));
+  }
+  if options.query.is_empty() {
+    return Ok(SearchResults {
+      matches: Vec::new(),
+      files_searched: 0,
+      truncated: false,
+    });
+  }
+
+  let pattern = build_pattern(options)?;
+  let max_results = options.max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+  let context = options
+    .context_lines
+    .unwrap_or(DEFAULT_CONTEXT_LINES)
+    .min(MAX_CONTEXT_LINES);
+
+  let matches = Mutex::new(Vec::new());
+  let found = AtomicUsize::new(0);
+  let truncated = AtomicBool::new(false);
+
+  let files_searched = for_each_text_file(root, |relative, _path, contents| {
+    let file_matches = search_text(relative, contents, &pattern, context);
+    if file_matches.is_empty() {
+      return true;
+    }
+
+    // Reserve room for this file's matches; the last file may only
+    // contribute part of them.
+    let before = found.fetch_add(file_matches.len(), Ordering::Relaxed);
+    if before >= max_results {
+      truncated.store(true, Ordering::Relaxed);
+      return false;
+    }
+    let room = max_results - before;
+    let mut shared = matches.lock().expect("search results lock poisoned");
+    if file_matches.len() > room {
+      truncated.store(true, Ordering::Relaxed);
+      shared.extend(file_matches.into_iter().take(room));
+      return false;
+    }
+    shared.extend(file_matches);
+    true
+  });
+
+  let mut matches = matches.into_inner().expect("search results lock poisoned");
+  matches.sort_by(|a, b| {
+    (a.path.as_str(), a.line, a.column).cmp(&(b.path.as_str(), b.line, b.column))
+  });
+  Ok(SearchResults {
+    matches,
+    files_searched,
+    truncated: truncated.into_inner(),
+  })
+}
+
+/// Call `visit` with the relative path, full path and contents of every
+/// searchable text file, from several threads at once. Returning `false`
+/// stops the walk. Returns the number of files visited.
+pub(crate) fn for_each_text_file<F>(root: &Path, visit: F) -> usize
+where
+  F: Fn(&str, &Path, &str) -> bool + Sync,
+{
+  let visited = AtomicUsize::new(0);
+  let stopped = AtomicBool::new(false);
+
+  WalkBuilder::new(root)
+    .require_git(false)
+    .build_parallel()
+    .run(|| {
+      Box::new(|entry| {
+        if stopped.load(Ordering::Relaxed) {
+          return WalkState::Quit;
+        }
+        let entry = match entry {
+          Ok(entry) => entry,
+          Err(err) => {
+            eprintln!("[Search] Skipping entry: {err}");
+            return WalkState::Continue;
+          }
+        };
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+          return WalkState::Continue;
+        }
+        let Some(contents) = read_text(entry.path()) else {
+          return WalkState::Continue;
+        };
+        visited.fetch_add(1, Ordering::Relaxed);
+
+        let relative = entry
+          .path()
+          .strip_prefix(root)
+          .unwrap_or(entry.path())
+          .to_string_lossy()
+          .replace('\\', "/");
+        if visit(&relative, entry.path(), &contents) {
+          WalkState::Continue
+        } else {
+          stopped.store(true, Ordering::Relaxed);
+          WalkState::Quit
+        }
+      })
+    });
+
+  visited.into_inner()
+}
+
+pub(crate) fn build_pattern(options: &SearchOptions) -> Result<Regex> {
+  let mut pattern = if options.regex {
+    options.query.clone()
+  } else {
+    regex::escape(&options.query)
+  };
+  if options.whole_word {
+    // Half boundaries only require no word character outside the match, so
+    // queries that start or end with punctuation (`#undead`, `(see`) still
+    // match as whole words.
+    pattern = format!(r"\b{{start-half}}(?:{pattern})\b{{end-half}}");
+  }
+  RegexBuilder::new(&pattern)
+    .case_insensitive(!options.case_sensitive)
+    .build()
+    .map_err(|err| Error::Anyhow(anyhow::anyhow!("Invalid search pattern: {err}")))
+}
+
+/// File contents as text, or `None` for binary, oversized or non-UTF-8 files.
+fn read_text(path: &Path) -> Option<String> {
+  let size = fs::metadata(path).ok()?.len();
+  if size > MAX_FILE_BYTES {
+    return None;
+  }
+  let bytes = fs::read(path).ok()?;
+  if bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0) {
+    return None;
+  }
+  String::from_utf8(bytes).ok()
+}
+
+/// Every match in `contents`, one entry per occurrence. Matches spanning
+/// several lines are not found, since each line is searched on its own.
+fn search_text(path: &str, contents: &str, pattern: &Regex, context: usize) -> Vec<SearchMatch> {
+  let lines: Vec<&str> = contents.lines().collect();
+  let mut matches = Vec::new();
+  for (idx, line) in lines.iter().enumerate() {
+    for found in pattern.find_iter(line) {
+      if found.start() == found.end() {
+        continue;
+      }
+      matches.push(SearchMatch {
+        path: path.to_string(),
+        line: idx + 1,
+        column: line[..found.start()].chars().count() + 1,
+        length: found.as_str().chars().count(),
+        line_text: line.to_string(),
+        context_before: lines[idx.saturating_sub(context)..idx]
+          .iter()
+          .map(|l| l.to_string())
+          .collect(),
+        context_after: lines[idx + 1..(idx + 1 + context).min(lines.len())]
+          .iter()
+          .map(|l| l.to_string())
+          .collect(),
+      });
+    }
+  }
+  matches
+}
@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::db::embeddings::{cosine_similarity, EmbeddingDb};
+use crate::util::error::Result;
+
+use super::links::{markdown_stem, project_files};
+use super::markdown::{document_tags, slugify};
+
+/// Suggestions less confident than this are left out.
+const MIN_CONFIDENCE: f32 = 0.5;
+const MAX_SUGGESTIONS: usize = 5;
+/// Tagged files listed with a suggestion as the reason for it.
+const MAX_EXAMPLES: usize = 3;
+
+/// A tag a file is about without carrying it or naming it.
+#[derive(Debug, Clone, Serialize)]
+pub struct SemanticTag {
+  /// With `#`.
+  pub tag: String,
+  /// From 0 to 1. Reaches 1 when the file is as close to the tag as the
+  /// files carrying it are on average.
+  pub confidence: f32,
+  /// Cosine similarity between the tag and the file's closest chunk.
+  pub similarity: f32,
+  /// Files carrying the tag that are most like this one.
+  pub examples: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileSemanticTags {
+  pub path: String,
+  /// Tags the file already carries, with `#`.
+  pub current_tags: Vec<String>,
+  /// Most confident first.
+  pub suggestions: Vec<SemanticTag>,
+}
+
+struct Document {
+  path: String,
+  tags: Vec<String>,
+  chunks: Vec<Vec<f32>>,
+  /// Normalized mean of the chunks.
+  vector: Vec<f32>,
+}
+
+/// A tag as a point in embedding space: the mean of the files carrying it
+/// and of its description, the note named after it (`undead.md` for
+/// `#undead`).
+struct TagProfile {
+  tag: String,
+  /// Indices into the documents.
+  members: Vec<usize>,
+  centroid: Vec<f32>,
+  /// Mean similarity of the members to the centroid.
+  cohesion: f32,
+}
+
+/// Tags suggested for every indexed markdown file, or only for `only`, by
+/// comparing chunk embeddings already in the project index with each tag's
+/// files. Nothing is embedded, so files indexed before their last edit are
+/// judged on their indexed text. Files without suggestions are not listed.
+pub fn suggest_tags(project_root: &str, only: Option<&str>) -> Result<Vec<FileSemanticTags>> {
+  let documents = load_documents(project_root)?;
+  let profiles = tag_profiles(&documents);
+
+  let mut results = Vec::new();
+  for (idx, document) in documents.iter().enumerate() {
+    if only.is_some_and(|only| only != document.path) {
+      continue;
+    }
+    let suggestions = suggestions_for(idx, &documents, &profiles);
+    if !suggestions.is_empty() {
+      results.push(FileSemanticTags {
+        path: document.path.clone(),
+        current_tags: document.tags.iter().map(|tag| format!("#{tag}")).collect(),
+        suggestions,
+      });
+    }
+  }
+  Ok(results)
+}
+
+fn suggestions_for(idx: usize, documents: &[Document], profiles: &[TagProfile]) -> Vec<SemanticTag> {
+  let document = &documents[idx];
+  let description = markdown_stem(document.path.rsplit('/').next().unwrap_or(&document.path)).map(slugify);
+  let candidates: Vec<(&TagProfile, f32)> = profiles
+    .iter()
+    .filter(|profile| !document.tags.contains(&profile.tag))
+    .filter(|profile| description.as_deref() != Some(profile.tag.as_str()))
+    .map(|profile| {
+      let similarity = document
+        .chunks
+        .iter()
+        .map(|chunk| cosine_similarity(chunk, &profile.centroid))
+        .fold(f32::MIN, f32::max);
+      (profile, similarity)
+    })
+    .collect();
+  if candidates.is_empty() {
+    return Vec::new();
+  }
+  // Embeddings of unrelated texts are still somewhat similar, so scores are
+  // measured from the file's average similarity to all tags.
+  let baseline = candidates.iter().map(|(_, s)| s).sum::<f32>() / candidates.len() as f32;
+
+  let mut suggestions: Vec<SemanticTag> = candidates
+    .into_iter()
+    .filter(|(_, similarity)| *similarity > baseline)
+    // A tag whose own files are no closer to it than the baseline has
+    // nothing to measure against, however close the file is.
+    .filter(|(profile, _)| profile.cohesion > baseline)
+    .map(|(profile, similarity)| {
+      let confidence = ((similarity - baseline) / (profile.cohesion - baseline)).min(1.0);
+      let mut examples: Vec<(f32, &str)> = profile
+        .members
+        .iter()
+        .map(|m| (cosine_similarity(&document.vector, &documents[*m].vector), documents[*m].path.as_str()))
+        .collect();
+      examples.sort_by(|a, b| b.0.total_cmp(&a.0));
+      SemanticTag {
+        tag: format!("#{}", profile.tag),
+        confidence,
+        similarity,
+        examples: examples
+          .into_iter()
+          .take(MAX_EXAMPLES)
+          .map(|(_, path)| path.to_string())
+          .collect(),
+      }
+    })
+    .filter(|suggestion| suggestion.confidence >= MIN_CONFIDENCE)
+    .collect();
+  suggestions.sort_by(|a, b| b.confidence.total_cmp(&a.confidence).then(b.similarity.total_cmp(&a.similarity)));
+  suggestions.truncate(MAX_SUGGESTIONS);
+  suggestions
+}
+
+/// Indexed markdown files with their current tags and their embeddings.
+/// Embeddings of another size than most, left over from a different
+/// embedding model, are ignored.
+fn load_documents(project_root: &str) -> Result<Vec<Document>> {
+  let root = Path::new(project_root);
+  let mut embeddings = EmbeddingDb::open_for_project(project_root)?.file_embeddings(project_root)?;
+  let mut sizes: HashMap<usize, usize> = HashMap::new();
+  for chunk in embeddings.values().flatten() {
+    *sizes.entry(chunk.len()).or_default() += 1;
+  }
+  let Some(size) = sizes.into_iter().max_by_key(|(size, count)| (*count, *size)).map(|(size, _)| size) else {
+    return Ok(Vec::new());
+  };
+
+  let mut documents = Vec::new();
+  for path in project_files(project_root)? {
+    if markdown_stem(&path).is_none() {
+      continue;
+    }
+    let Some(chunks) = embeddings.remove(&path) else {
+      continue;
+    };
+    let chunks: Vec<Vec<f32>> = chunks.into_iter().filter(|c| c.len() == size).collect();
+    let Some(vector) = mean(&chunks) else {
+      continue;
+    };
+    let contents = match fs::read_to_string(root.join(&path)) {
+      Ok(contents) => contents,
+      Err(err) => {
+        eprintln!("[SemanticTags] Skipping {path}: {err}");
+        continue;
+      }
+    };
+    documents.push(Document {
+      tags: document_tags(&contents),
+      path,
+      chunks,
+      vector,
+    });
+  }
+  Ok(documents)
+}
+
+fn tag_profiles(documents: &[Document]) -> Vec<TagProfile> {
+  let mut members: HashMap<&str, Vec<usize>> = HashMap::new();
+  for (idx, document) in documents.iter().enumerate() {
+    for tag in &document.tags {
+      members.entry(tag).or_default().push(idx);
+    }
+  }
+  for (idx, document) in documents.iter().enumerate() {
+    let Some(stem) = markdown_stem(document.path.rsplit('/').next().unwrap_or(&document.path)) else {
+      continue;
+    };
+    let slug = slugify(stem);
+    if let Some(tagged) = members.get_mut(slug.as_str()) {
+      if !tagged.contains(&idx) {
+        tagged.push(idx);
+      }
+    }
+  }
+
+  let mut profiles: Vec<TagProfile> = members
+    .into_iter()
+    .filter_map(|(tag, members)| {
+      let vectors: Vec<Vec<f32>> = members.iter().map(|m| documents[*m].vector.clone()).collect();
+      let centroid = mean(&vectors)?;
+      let cohesion = vectors.iter().map(|v| cosine_similarity(v, &centroid)).sum::<f32>() / vectors.len() as f32;
+      Some(TagProfile {
+        tag: tag.to_string(),
+        members,
+        centroid,
+        cohesion,
+      })
+    })
+    .collect();
+  profiles.sort_by(|a, b| a.tag.cmp(&b.tag));
+  profiles
+}
+
+/// The normalized mean of normalized vectors, so each counts the same
+/// however long it is.
+fn mean(vectors: &[Vec<f32>]) -> Option<Vec<f32>> {
+  let size = vectors.first()?.len();
+  let mut sum = vec![0.0; size];
+  for vector in vectors {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+      for (total, x) in sum.iter_mut().zip(vector) {
+        *total += x / norm;
+      }
+    }
+  }
+  let norm = sum.iter().map(|x| x * x).sum::<f32>().sqrt();
+  (norm > 0.0).then(|| sum.into_iter().map(|x| x / norm).collect())
+}
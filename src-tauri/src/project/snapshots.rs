@@ -0,0 +1,247 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::db::snapshots::{FileSnapshot, SnapshotDb, SnapshotSource};
+use crate::util::error::{Error, Result};
+use crate::util::time::now_secs;
+
+use super::paths::{project_relative_path, resolve_project_path, Access};
+use super::transactions::{EditTransaction, TransactionStatus};
+use super::versions::content_hash;
+
+const OBJECTS_DIR: &str = ".codexlotus/snapshots";
+
+/// Snapshots kept per file regardless of age.
+const MIN_SNAPSHOTS_PER_FILE: usize = 10;
+/// Snapshots kept per file at most.
+const MAX_SNAPSHOTS_PER_FILE: usize = 100;
+/// Snapshots older than this are dropped once a file has more than
+/// `MIN_SNAPSHOTS_PER_FILE`.
+const MAX_SNAPSHOT_AGE_SECS: i64 = 90 * 24 * 60 * 60;
+
+/// Local version history of project files.
+///
+/// Each snapshot is a row in the project database pointing at a gzip-compressed
+/// object named by the SHA-256 of the contents, so identical versions (of the
+/// same file or different files) are stored once. Recording the same contents
+/// twice in a row for a file is a no-op.
+pub struct SnapshotStore {
+  db: SnapshotDb,
+  objects: PathBuf,
+}
+
+impl SnapshotStore {
+  pub fn open(project_root: &str) -> Result<Self> {
+    let db = SnapshotDb::open_for_project(project_root)?;
+    let objects = Path::new(project_root).join(OBJECTS_DIR);
+    fs::create_dir_all(&objects)?;
+    Ok(Self { db, objects })
+  }
+
+  /// Record `contents` as the newest version of `path`. Returns `None` when it
+  /// matches the newest snapshot already stored.
+  pub fn record(
+    &mut self,
+    path: &str,
+    contents: &str,
+    source: SnapshotSource,
+    label: Option<&str>,
+  ) -> Result<Option<FileSnapshot>> {
+    let hash = content_hash(contents.as_bytes());
+    if let Some(latest) = self.db.latest_for_path(path)? {
+      if latest.content_hash == hash {
+        return Ok(None);
+      }
+    }
+
+    self.write_object(&hash, contents)?;
+    let snapshot = self
+      .db
+      .insert(path, &hash, contents.len() as u64, source, label)?;
+    self.prune(path)?;
+    Ok(Some(snapshot))
+  }
+
+  /// Record a change the app made to `path`. `before` is stored first if the
+  /// history does not already end with it, so the version being replaced can
+  /// always be restored even if it was never saved through the app. A deleted
+  /// file (`after: None`) keeps its history as it was.
+  pub fn record_change(
+    &mut self,
+    path: &str,
+    before: Option<&str>,
+    after: Option<&str>,
+    source: SnapshotSource,
+    label: Option<&str>,
+  ) -> Result<()> {
+    if let Some(before) = before {
+      self.record(path, before, SnapshotSource::External, None)?;
+    }
+    if let Some(after) = after {
+      self.record(path, after, source, label)?;
+    }
+    Ok(())
+  }
+
+  /// Snapshots of `path`, newest first.
+  pub fn list(&self, path: &str) -> Result<Vec<FileSnapshot>> {
+    self.db.list_for_path(path)
+  }
+
+  /// Look up a snapshot of `path`. Ids of other files are rejected so a stale
+  /// id from the UI cannot restore the wrong file.
+  pub fn get(&self, path: &str, id: i64) -> Result<FileSnapshot> {
+    let snapshot = self.db.get(id)?;
+    if snapshot.path != path {
+      return Err(Error::Anyhow(anyhow::anyhow!(
+        "Snapshot {id} belongs to '{}', not '{path}'",
+        snapshot.path
+      )));
+    }
+    Ok(snapshot)
+  }
+
+  pub fn contents(&self, snapshot: &FileSnapshot) -> Result<String> {
+    let file = fs::File::open(self.object_path(&snapshot.content_hash)).map_err(|err| {
+      Error::Anyhow(anyhow::anyhow!(
+        "Contents of snapshot {} are missing: {err}",
+        snapshot.id
+      ))
+    })?;
+    let mut contents = String::with_capacity(snapshot.size as usize);
+    GzDecoder::new(file).read_to_string(&mut contents)?;
+    Ok(contents)
+  }
+
+  /// Drop snapshots past the retention limits, then any objects no snapshot
+  /// refers to anymore.
+  fn prune(&mut self, path: &str) -> Result<()> {
+    let cutoff = now_secs() as i64 - MAX_SNAPSHOT_AGE_SECS;
+    let expired: Vec<FileSnapshot> = self
+      .db
+      .list_for_path(path)?
+      .into_iter()
+      .enumerate()
+      .filter(|(idx, snapshot)| {
+        *idx >= MIN_SNAPSHOTS_PER_FILE
+          && (*idx >= MAX_SNAPSHOTS_PER_FILE || snapshot.created_at < cutoff)
+      })
+      .map(|(_, snapshot)| snapshot)
+      .collect();
+    if expired.is_empty() {
+      return Ok(());
+    }
+
+    let ids: Vec<i64> = expired.iter().map(|s| s.id).collect();
+    self.db.delete(&ids)?;
+
+    let mut hashes: Vec<&str> = expired.iter().map(|s| s.content_hash.as_str()).collect();
+    hashes.sort_unstable();
+    hashes.dedup();
+    for hash in hashes {
+      if self.db.hash_in_use(hash)? {
+        continue;
+      }
+      if let Err(err) = fs::remove_file(self.object_path(hash)) {
+        eprintln!("[Snapshots] Failed to remove object {hash}: {err}");
+      }
+    }
+    Ok(())
+  }
+
+  fn write_object(&self, hash: &str, contents: &str) -> Result<()> {
+    let path = self.object_path(hash);
+    if path.exists() {
+      return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("gz.tmp");
+    let mut encoder = GzEncoder::new(fs::File::create(&tmp)?, Compression::default());
+    encoder.write_all(contents.as_bytes())?;
+    encoder.finish()?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+  }
+
+  fn object_path(&self, hash: &str) -> PathBuf {
+    let prefix = hash.get(..2).unwrap_or(hash);
+    self.objects.join(prefix).join(format!("{hash}.gz"))
+  }
+}
+
+/// Snapshot a change to a resolved project file. History is a safety net, so
+/// failures are logged rather than failing the write that triggered them.
+pub fn snapshot_change(
+  project_root: &str,
+  resolved: &Path,
+  before: Option<&str>,
+  after: Option<&str>,
+  source: SnapshotSource,
+  label: Option<&str>,
+) {
+  let result = project_relative_path(project_root, resolved).and_then(|path| {
+    SnapshotStore::open(project_root)?.record_change(&path, before, after, source, label)
+  });
+  if let Err(err) = result {
+    eprintln!("[Snapshots] Failed to snapshot {:?}: {err}", resolved);
+  }
+}
+
+/// Record the files a transaction touched in their version history, in the
+/// state the transaction just left them in. Renamed files take their history
+/// with them.
+pub fn snapshot_transaction(project_root: &str, tx: &EditTransaction, source: SnapshotSource) {
+  if tx.status == TransactionStatus::Applied {
+    move_histories(project_root, tx);
+  }
+  for change in &tx.changes {
+    let (before, after) = match tx.status {
+      TransactionStatus::Applied => (&change.before, &change.after),
+      TransactionStatus::Undone => (&change.after, &change.before),
+    };
+    match resolve_project_path(project_root, &change.path, Access::Read) {
+      Ok(resolved) => snapshot_change(
+        project_root,
+        &resolved,
+        before.as_deref(),
+        after.as_deref(),
+        source,
+        Some(&tx.label),
+      ),
+      Err(err) => eprintln!("[Snapshots] Failed to snapshot {}: {err}", change.path),
+    }
+  }
+  if tx.status == TransactionStatus::Undone {
+    move_histories(project_root, tx);
+  }
+}
+
+/// Follow the moves of `tx` in the direction it was last applied or undone.
+fn move_histories(project_root: &str, tx: &EditTransaction) {
+  if tx.moves.is_empty() {
+    return;
+  }
+  let db = match SnapshotDb::open_for_project(project_root) {
+    Ok(db) => db,
+    Err(err) => {
+      eprintln!("[Snapshots] Failed to move file histories: {err}");
+      return;
+    }
+  };
+  let mut moves: Vec<(&str, &str)> = tx.moves.iter().map(|m| (m.from.as_str(), m.to.as_str())).collect();
+  if tx.status == TransactionStatus::Undone {
+    moves = moves.into_iter().rev().map(|(from, to)| (to, from)).collect();
+  }
+  for (from, to) in moves {
+    if let Err(err) = db.rename_path(from, to) {
+      eprintln!("[Snapshots] Failed to move the history of {from} to {to}: {err}");
+    }
+  }
+}
@@ -0,0 +1,511 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::util::error::{Error, Result};
+use crate::util::time::now_secs;
+
+use super::paths::{resolve_project_path, validate_relative_path, Access};
+
+const HISTORY_DIR: &str = ".codexlotus/history";
+
+/// Oldest applied transactions beyond this count are dropped from the journal.
+const MAX_JOURNAL_ENTRIES: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStatus {
+  Applied,
+  Undone,
+}
+
+/// A file edit to include in a transaction. `contents: None` deletes the file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EditRequest {
+  pub path: String,
+  pub contents: Option<String>,
+}
+
+/// One file's state before and after a transaction. `None` means the file
+/// does not exist in that state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChange {
+  pub path: String,
+  pub before: Option<String>,
+  pub after: Option<String>,
+}
+
+/// A file or folder renamed by a transaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileMove {
+  pub from: String,
+  pub to: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditTransaction {
+  pub id: u64,
+  pub label: String,
+  pub created_at: u64,
+  pub status: TransactionStatus,
+  /// Applied before `changes`, whose paths refer to the moved locations.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub moves: Vec<FileMove>,
+  pub changes: Vec<FileChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionSummary {
+  pub id: u64,
+  pub label: String,
+  pub created_at: u64,
+  pub status: TransactionStatus,
+  pub paths: Vec<String>,
+}
+
+impl From<&EditTransaction> for TransactionSummary {
+  fn from(tx: &EditTransaction) -> Self {
+    Self {
+      id: tx.id,
+      label: tx.label.clone(),
+      created_at: tx.created_at,
+      status: tx.status,
+      paths: tx
+        .moves
+        .iter()
+        .flat_map(|m| [m.from.clone(), m.to.clone()])
+        .chain(tx.changes.iter().map(|c| c.path.clone()))
+        .collect(),
+    }
+  }
+}
+
+/// Multi-file edits applied all-or-nothing, with an undo/redo journal stored
+/// as one JSON file per transaction under `.codexlotus/history`.
+pub struct EditJournal {
+  project_root: String,
+  dir: PathBuf,
+}
+
+impl EditJournal {
+  pub fn open(project_root: &str) -> Result<Self> {
+    let root = PathBuf::from(project_root);
+    if !root.is_dir() {
+      return Err(Error::Anyhow(anyhow::anyhow!(
+        "Project root is not a directory: {project_root}"
+      )));
+    }
+    let dir = root.join(HISTORY_DIR);
+    fs::create_dir_all(&dir)?;
+    Ok(Self {
+      project_root: project_root.to_string(),
+      dir,
+    })
+  }
+
+  /// Apply every edit or none of them, then record the transaction. Applying
+  /// a new transaction discards anything that could have been redone.
+  pub fn apply(&self, label: &str, edits: &[EditRequest]) -> Result<EditTransaction> {
+    self.apply_with_moves(label, &[], edits)
+  }
+
+  /// Like `apply`, but first renames files or folders. The edits refer to
+  /// paths after the moves; undoing reverts the edits, then moves everything
+  /// back.
+  pub fn apply_with_moves(
+    &self,
+    label: &str,
+    moves: &[FileMove],
+    edits: &[EditRequest],
+  ) -> Result<EditTransaction> {
+    for path in moves.iter().flat_map(|m| [&m.from, &m.to]).chain(edits.iter().map(|e| &e.path)) {
+      if let Some(problem) = validate_relative_path(path) {
+        return Err(Error::Anyhow(anyhow::Error::msg(problem)));
+      }
+    }
+    self.ensure_moves(moves, label, Direction::Backward)?;
+    self.move_all(moves, Direction::Forward)?;
+
+    let result = self.collect_changes(edits).and_then(|changes| {
+      if changes.is_empty() && moves.is_empty() {
+        return Err(Error::Anyhow(anyhow::Error::msg("The edits do not change any files")));
+      }
+      self.record(label, moves, changes)
+    });
+    if result.is_err() {
+      self.unmove(moves, Direction::Forward);
+    }
+    result
+  }
+
+  fn collect_changes(&self, edits: &[EditRequest]) -> Result<Vec<FileChange>> {
+    let mut changes: Vec<FileChange> = Vec::new();
+    for edit in edits {
+      let target = self.resolve(&edit.path)?;
+      if changes.iter().any(|c| c.path == edit.path) {
+        return Err(Error::Anyhow(anyhow::anyhow!(
+          "'{}' appears more than once in the transaction",
+          edit.path
+        )));
+      }
+      let before = read_optional(&target)?;
+      if before != edit.contents {
+        changes.push(FileChange {
+          path: edit.path.clone(),
+          before,
+          after: edit.contents.clone(),
+        });
+      }
+    }
+    Ok(changes)
+  }
+
+  /// Write `changes` and add the transaction to the journal.
+  fn record(&self, label: &str, moves: &[FileMove], changes: Vec<FileChange>) -> Result<EditTransaction> {
+    let mut history = self.load_all()?;
+    let tx = EditTransaction {
+      id: history.iter().map(|t| t.id).max().unwrap_or(0) + 1,
+      label: label.to_string(),
+      created_at: now_secs(),
+      status: TransactionStatus::Applied,
+      moves: moves.to_vec(),
+      changes,
+    };
+
+    self.write_states(&tx.changes, Direction::Forward)?;
+    if let Err(err) = self.save(&tx) {
+      self.restore(&tx.changes, Direction::Backward);
+      return Err(err);
+    }
+
+    let (applied, undone): (Vec<_>, Vec<_>) = history
+      .drain(..)
+      .partition(|t| t.status == TransactionStatus::Applied);
+    for stale in &undone {
+      self.remove(stale.id);
+    }
+    let excess = (applied.len() + 1).saturating_sub(MAX_JOURNAL_ENTRIES);
+    for old in &applied[..excess] {
+      self.remove(old.id);
+    }
+
+    Ok(tx)
+  }
+
+  /// Revert the most recent applied transaction. Returns `None` when there is
+  /// nothing to undo.
+  pub fn undo(&self) -> Result<Option<EditTransaction>> {
+    let history = self.load_all()?;
+    let Some(mut tx) = history
+      .into_iter()
+      .rev()
+      .find(|t| t.status == TransactionStatus::Applied)
+    else {
+      return Ok(None);
+    };
+
+    self.ensure_moves(&tx.moves, &tx.label, Direction::Forward)?;
+    self.ensure_unchanged(&tx, Direction::Forward)?;
+    self.write_states(&tx.changes, Direction::Backward)?;
+    if let Err(err) = self.move_all(&tx.moves, Direction::Backward) {
+      self.restore(&tx.changes, Direction::Forward);
+      return Err(err);
+    }
+    tx.status = TransactionStatus::Undone;
+    if let Err(err) = self.save(&tx) {
+      self.unmove(&tx.moves, Direction::Backward);
+      self.restore(&tx.changes, Direction::Forward);
+      return Err(err);
+    }
+    Ok(Some(tx))
+  }
+
+  /// Re-apply the most recently undone transaction. Returns `None` when there
+  /// is nothing to redo.
+  pub fn redo(&self) -> Result<Option<EditTransaction>> {
+    let history = self.load_all()?;
+    let Some(mut tx) = history
+      .into_iter()
+      .find(|t| t.status == TransactionStatus::Undone)
+    else {
+      return Ok(None);
+    };
+
+    self.ensure_moves(&tx.moves, &tx.label, Direction::Backward)?;
+    self.move_all(&tx.moves, Direction::Forward)?;
+    let written = self
+      .ensure_unchanged(&tx, Direction::Backward)
+      .and_then(|_| self.write_states(&tx.changes, Direction::Forward));
+    if let Err(err) = written {
+      self.unmove(&tx.moves, Direction::Forward);
+      return Err(err);
+    }
+    tx.status = TransactionStatus::Applied;
+    if let Err(err) = self.save(&tx) {
+      self.restore(&tx.changes, Direction::Backward);
+      self.unmove(&tx.moves, Direction::Forward);
+      return Err(err);
+    }
+    Ok(Some(tx))
+  }
+
+  /// Journal entries, newest first.
+  pub fn list(&self) -> Result<Vec<TransactionSummary>> {
+    let mut history = self.load_all()?;
+    history.reverse();
+    Ok(history.iter().map(TransactionSummary::from).collect())
+  }
+
+  /// Refuse to undo/redo over a file that was edited since the transaction,
+  /// which would silently throw that work away.
+  fn ensure_unchanged(&self, tx: &EditTransaction, current: Direction) -> Result<()> {
+    for change in &tx.changes {
+      let expected = current.target(change);
+      let actual = read_optional(&self.resolve(&change.path)?)?;
+      if actual.as_ref() != expected {
+        return Err(Error::Anyhow(anyhow::anyhow!(
+          "'{}' has changed since \"{}\"; resolve it before undoing or redoing",
+          change.path,
+          tx.label
+        )));
+      }
+    }
+    Ok(())
+  }
+
+  /// Check that the files are still in their `current` state and every move
+  /// away from it can be made: the source exists and nothing is in the way
+  /// at the destination.
+  fn ensure_moves(&self, moves: &[FileMove], label: &str, current: Direction) -> Result<()> {
+    for file_move in moves {
+      let (from, to) = current.reverse().endpoints(file_move);
+      if !self.resolve(from)?.exists() {
+        return Err(Error::Anyhow(anyhow::anyhow!(
+          "'{from}' no longer exists; cannot complete \"{label}\""
+        )));
+      }
+      if self.resolve(to)?.exists() {
+        return Err(Error::Anyhow(anyhow::anyhow!(
+          "'{to}' already exists; cannot complete \"{label}\""
+        )));
+      }
+    }
+    Ok(())
+  }
+
+  /// Rename every moved path towards `direction`, in order going forward and
+  /// in reverse going back. On failure the renames already made are undone.
+  fn move_all(&self, moves: &[FileMove], direction: Direction) -> Result<()> {
+    let ordered: Vec<&FileMove> = match direction {
+      Direction::Forward => moves.iter().collect(),
+      Direction::Backward => moves.iter().rev().collect(),
+    };
+    for (idx, file_move) in ordered.iter().enumerate() {
+      let (from, to) = direction.endpoints(file_move);
+      let result = self.resolve(from).and_then(|source| {
+        let target = self.resolve(to)?;
+        if let Some(parent) = target.parent() {
+          fs::create_dir_all(parent)?;
+        }
+        fs::rename(source, target).map_err(Error::from)
+      });
+      if let Err(err) = result {
+        for done in ordered[..idx].iter().rev() {
+          self.rename_back(done, direction);
+        }
+        return Err(err);
+      }
+    }
+    Ok(())
+  }
+
+  /// Best-effort rollback of `move_all(moves, direction)`.
+  fn unmove(&self, moves: &[FileMove], direction: Direction) {
+    let ordered: Vec<&FileMove> = match direction {
+      Direction::Forward => moves.iter().rev().collect(),
+      Direction::Backward => moves.iter().collect(),
+    };
+    for file_move in ordered {
+      self.rename_back(file_move, direction);
+    }
+  }
+
+  fn rename_back(&self, file_move: &FileMove, direction: Direction) {
+    let (from, to) = direction.reverse().endpoints(file_move);
+    let result = self
+      .resolve(from)
+      .and_then(|source| Ok(fs::rename(source, self.resolve(to)?)?));
+    if let Err(err) = result {
+      eprintln!("[EditJournal] Failed to move {from} back to {to}: {err}");
+    }
+  }
+
+  /// Move every file to its `direction` state. New contents are staged in
+  /// temp files next to their targets and renamed into place; if any rename
+  /// fails, files already replaced are put back.
+  fn write_states(&self, changes: &[FileChange], direction: Direction) -> Result<()> {
+    let targets = changes
+      .iter()
+      .map(|change| self.resolve(&change.path))
+      .collect::<Result<Vec<_>>>()?;
+
+    let mut staged: Vec<Option<PathBuf>> = Vec::with_capacity(changes.len());
+    for (change, target) in changes.iter().zip(&targets) {
+      match direction.target(change) {
+        Some(contents) => match stage(target, contents) {
+          Ok(tmp) => staged.push(Some(tmp)),
+          Err(err) => {
+            remove_staged(&staged);
+            return Err(err);
+          }
+        },
+        None => staged.push(None),
+      }
+    }
+
+    for (idx, target) in targets.iter().enumerate() {
+      let result = match &staged[idx] {
+        Some(tmp) => fs::rename(tmp, target),
+        None if target.exists() => fs::remove_file(target),
+        None => Ok(()),
+      };
+      if let Err(err) = result {
+        remove_staged(&staged[idx..]);
+        self.restore(&changes[..idx], direction.reverse());
+        return Err(Error::Io(err));
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Best-effort rollback; failures are logged since the original error is
+  /// what gets reported.
+  fn restore(&self, changes: &[FileChange], direction: Direction) {
+    for change in changes {
+      let target = match self.resolve(&change.path) {
+        Ok(target) => target,
+        Err(err) => {
+          eprintln!("[EditJournal] Failed to roll back {}: {err}", change.path);
+          continue;
+        }
+      };
+      let result = match direction.target(change) {
+        Some(contents) => fs::write(&target, contents),
+        None if target.exists() => fs::remove_file(&target),
+        None => Ok(()),
+      };
+      if let Err(err) = result {
+        eprintln!("[EditJournal] Failed to roll back {}: {err}", change.path);
+      }
+    }
+  }
+
+  /// Every project file the journal touches goes through the sandbox, including
+  /// paths read back from journal entries on disk.
+  fn resolve(&self, path: &str) -> Result<PathBuf> {
+    resolve_project_path(&self.project_root, path, Access::Write)
+  }
+
+  fn load_all(&self) -> Result<Vec<EditTransaction>> {
+    let mut history = Vec::new();
+    for entry in fs::read_dir(&self.dir)? {
+      let path = entry?.path();
+      if path.extension().and_then(|e| e.to_str()) != Some("json") {
+        continue;
+      }
+      match fs::read_to_string(&path)
+        .map_err(Error::from)
+        .and_then(|json| serde_json::from_str::<EditTransaction>(&json).map_err(Error::from))
+      {
+        Ok(tx) => history.push(tx),
+        Err(err) => eprintln!("[EditJournal] Skipping unreadable journal entry {:?}: {err}", path),
+      }
+    }
+    history.sort_by_key(|t| t.id);
+    Ok(history)
+  }
+
+  fn save(&self, tx: &EditTransaction) -> Result<()> {
+    let path = self.entry_path(tx.id);
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec_pretty(tx)?)?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+  }
+
+  fn remove(&self, id: u64) {
+    if let Err(err) = fs::remove_file(self.entry_path(id)) {
+      eprintln!("[EditJournal] Failed to remove journal entry {id}: {err}");
+    }
+  }
+
+  fn entry_path(&self, id: u64) -> PathBuf {
+    self.dir.join(format!("{id:010}.json"))
+  }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+  /// Towards each change's `after` state.
+  Forward,
+  /// Towards each change's `before` state.
+  Backward,
+}
+
+impl Direction {
+  fn target(self, change: &FileChange) -> Option<&String> {
+    match self {
+      Direction::Forward => change.after.as_ref(),
+      Direction::Backward => change.before.as_ref(),
+    }
+  }
+
+  /// Source and destination of a move made towards this direction.
+  fn endpoints(self, file_move: &FileMove) -> (&str, &str) {
+    match self {
+      Direction::Forward => (&file_move.from, &file_move.to),
+      Direction::Backward => (&file_move.to, &file_move.from),
+    }
+  }
+
+  fn reverse(self) -> Self {
+    match self {
+      Direction::Forward => Direction::Backward,
+      Direction::Backward => Direction::Forward,
+    }
+  }
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>> {
+  if !path.exists() {
+    return Ok(None);
+  }
+  if !path.is_file() {
+    return Err(Error::Anyhow(anyhow::anyhow!("{:?} is not a file", path)));
+  }
+  Ok(Some(fs::read_to_string(path)?))
+}
+
+/// Write `contents` to a temp file next to `target`, so the final rename
+/// stays on one filesystem.
+fn stage(target: &Path, contents: &str) -> Result<PathBuf> {
+  let parent = target
+    .parent()
+    .ok_or_else(|| Error::Anyhow(anyhow::anyhow!("{:?} has no parent directory", target)))?;
+  fs::create_dir_all(parent)?;
+  let file_name = target
+    .file_name()
+    .map(|n| n.to_string_lossy().to_string())
+    .unwrap_or_default();
+  let tmp = parent.join(format!(".{file_name}.{}.codexlotus-tmp", std::process::id()));
+  fs::write(&tmp, contents)?;
+  Ok(tmp)
+}
+
+fn remove_staged(staged: &[Option<PathBuf>]) {
+  for tmp in staged.iter().flatten() {
+    let _ = fs::remove_file(tmp);
+  }
+}
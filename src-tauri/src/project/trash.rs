@@ -0,0 +1,212 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::util::error::{Error, Result};
+use crate::util::time::now_secs;
+
+use super::paths::{project_relative_path, resolve_project_path, Access};
+
+const TRASH_DIR: &str = ".codexlotus/trash";
+
+/// A deleted file or folder waiting in the trash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+  pub id: u64,
+  /// Where it was, relative to the project root.
+  pub original_path: String,
+  pub is_dir: bool,
+  /// Files it contains; 1 for a file.
+  pub file_count: usize,
+  /// Total size in bytes.
+  pub size: u64,
+  pub deleted_at: u64,
+}
+
+/// Project-local trash. Each deleted item is moved, unchanged, into
+/// `.codexlotus/trash/<id>/` and described by `<id>.json` next to it.
+pub struct ProjectTrash {
+  project_root: String,
+  dir: PathBuf,
+}
+
+impl ProjectTrash {
+  pub fn open(project_root: &str) -> Result<Self> {
+    let root = PathBuf::from(project_root);
+    if !root.is_dir() {
+      return Err(Error::Anyhow(anyhow::anyhow!(
+        "Project root is not a directory: {project_root}"
+      )));
+    }
+    let dir = root.join(TRASH_DIR);
+    fs::create_dir_all(&dir)?;
+    Ok(Self {
+      project_root: project_root.to_string(),
+      dir,
+    })
+  }
+
+  /// Move a file or folder into the trash.
+  pub fn delete(&self, path: &str) -> Result<TrashEntry> {
+    let source = resolve_project_path(&self.project_root, path, Access::Write)?;
+    let original_path = project_relative_path(&self.project_root, &source)?;
+    if original_path.is_empty() {
+      return Err(Error::Anyhow(anyhow::Error::msg("The project folder itself cannot be deleted")));
+    }
+    if !source.exists() {
+      return Err(Error::Anyhow(anyhow::anyhow!("'{original_path}' does not exist")));
+    }
+
+    let (file_count, size) = measure(&source);
+    let entry = TrashEntry {
+      id: self.next_id()?,
+      original_path,
+      is_dir: source.is_dir(),
+      file_count,
+      size,
+      deleted_at: now_secs(),
+    };
+
+    let holder = self.holder_path(entry.id);
+    fs::create_dir_all(&holder)?;
+    let stored = holder.join(file_name(&entry.original_path));
+    if let Err(err) = fs::rename(&source, &stored) {
+      let _ = fs::remove_dir(&holder);
+      return Err(Error::Io(err));
+    }
+    if let Err(err) = self.save(&entry) {
+      if let Err(rollback) = fs::rename(&stored, &source) {
+        eprintln!("[Trash] Failed to put back {}: {rollback}", entry.original_path);
+      }
+      let _ = fs::remove_dir(&holder);
+      return Err(err);
+    }
+    Ok(entry)
+  }
+
+  /// Trash contents, most recently deleted first.
+  pub fn list(&self) -> Result<Vec<TrashEntry>> {
+    let mut entries = Vec::new();
+    for item in fs::read_dir(&self.dir)? {
+      let path = item?.path();
+      if path.extension().and_then(|e| e.to_str()) != Some("json") {
+        continue;
+      }
+      match fs::read_to_string(&path)
+        .map_err(Error::from)
+        .and_then(|json| serde_json::from_str::<TrashEntry>(&json).map_err(Error::from))
+      {
+        Ok(entry) => entries.push(entry),
+        Err(err) => eprintln!("[Trash] Skipping unreadable trash entry {:?}: {err}", path),
+      }
+    }
+    entries.sort_by_key(|e| std::cmp::Reverse(e.id));
+    Ok(entries)
+  }
+
+  /// Put an item back where it was, or at `destination`. Fails rather than
+  /// overwrite anything already at that path. Returns the restored path.
+  pub fn restore(&self, id: u64, destination: Option<&str>) -> Result<String> {
+    let entry = self.get(id)?;
+    let target_path = destination.unwrap_or(&entry.original_path);
+    let target = resolve_project_path(&self.project_root, target_path, Access::Write)?;
+    let restored = project_relative_path(&self.project_root, &target)?;
+    if target.exists() {
+      return Err(Error::Anyhow(anyhow::anyhow!(
+        "'{restored}' already exists; restore the deleted copy under another name"
+      )));
+    }
+
+    if let Some(parent) = target.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::rename(self.stored_path(&entry), &target)?;
+    self.forget(id);
+    Ok(restored)
+  }
+
+  /// Permanently delete the given items. Returns how many were removed.
+  pub fn purge(&self, ids: &[u64]) -> Result<usize> {
+    let mut purged = 0;
+    for id in ids {
+      let entry = self.get(*id)?;
+      let holder = self.holder_path(entry.id);
+      if holder.exists() {
+        fs::remove_dir_all(&holder)?;
+      }
+      self.forget(entry.id);
+      purged += 1;
+    }
+    Ok(purged)
+  }
+
+  /// Permanently delete everything in the trash.
+  pub fn empty(&self) -> Result<usize> {
+    let ids: Vec<u64> = self.list()?.iter().map(|e| e.id).collect();
+    self.purge(&ids)
+  }
+
+  fn get(&self, id: u64) -> Result<TrashEntry> {
+    let path = self.entry_path(id);
+    if !path.exists() {
+      return Err(Error::Anyhow(anyhow::anyhow!("Trash entry {id} not found")));
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+  }
+
+  fn next_id(&self) -> Result<u64> {
+    let mut max = 0;
+    for item in fs::read_dir(&self.dir)? {
+      let name = item?.file_name().to_string_lossy().to_string();
+      if let Ok(id) = name.trim_end_matches(".json").parse::<u64>() {
+        max = max.max(id);
+      }
+    }
+    Ok(max + 1)
+  }
+
+  fn save(&self, entry: &TrashEntry) -> Result<()> {
+    let path = self.entry_path(entry.id);
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec_pretty(entry)?)?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+  }
+
+  /// Drop the metadata and the (now empty) holder folder of an entry.
+  fn forget(&self, id: u64) {
+    if let Err(err) = fs::remove_file(self.entry_path(id)) {
+      eprintln!("[Trash] Failed to remove trash entry {id}: {err}");
+    }
+    let _ = fs::remove_dir(self.holder_path(id));
+  }
+
+  fn stored_path(&self, entry: &TrashEntry) -> PathBuf {
+    self.holder_path(entry.id).join(file_name(&entry.original_path))
+  }
+
+  fn holder_path(&self, id: u64) -> PathBuf {
+    self.dir.join(format!("{id:010}"))
+  }
+
+  fn entry_path(&self, id: u64) -> PathBuf {
+    self.dir.join(format!("{id:010}.json"))
+  }
+}
+
+/// Number of files under `path` and their total size.
+fn measure(path: &Path) -> (usize, u64) {
+  WalkDir::new(path)
+    .into_iter()
+    .filter_map(|e| e.ok())
+    .filter(|e| e.file_type().is_file())
+    .fold((0, 0), |(count, size), e| {
+      (count + 1, size + e.metadata().map(|m| m.len()).unwrap_or(0))
+    })
+}
+
+fn file_name(path: &str) -> &str {
+  path.rsplit('/').next().unwrap_or(path)
+}
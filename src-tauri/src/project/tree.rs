@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use serde::Serialize;
+
+use crate::db::embeddings::EmbeddingDb;
+use crate::util::error::{Error, Result};
+
+use super::links::markdown_stem;
+use super::markdown::{parse_headings, split_frontmatter};
+
+/// Whether a markdown file's current contents are in the embedding index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexStatus {
+  Indexed,
+  /// Indexed, but the file has changed since.
+  Stale,
+  NotIndexed,
+}
+
+/// What the tree knows about a markdown file's contents.
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentInfo {
+  pub word_count: usize,
+  /// The frontmatter `title`, or else the first heading.
+  pub title: Option<String>,
+  /// Frontmatter tags.
+  pub tags: Vec<String>,
+  pub index_status: IndexStatus,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TreeNode {
+  pub name: String,
+  /// Relative to the project root, with `/` separators. Empty for the root.
+  pub path: String,
+  pub is_dir: bool,
+  /// File size, or the total size of the files below a folder, in bytes.
+  pub size: u64,
+  /// Seconds since the Unix epoch. For a folder, the newest file below it.
+  pub modified: Option<u64>,
+  /// Markdown files only.
+  pub document: Option<DocumentInfo>,
+  /// Folders first, then files, each sorted by name.
+  pub children: Vec<TreeNode>,
+}
+
+/// The project as a nested tree. Hidden files and folders (including `.git`
+/// and `.codexlotus`) are left out, and with `markdown_only` so are all
+/// files except markdown documents.
+pub fn project_tree(project_root: &str, markdown_only: bool) -> Result<TreeNode> {
+  let root = fs::canonicalize(project_root)?;
+  if !root.is_dir() {
+    return Err(Error::Anyhow(anyhow::anyhow!(
+      "Project root is not a directory: {project_root}"
+    )));
+  }
+  let indexed = EmbeddingDb::open_for_project(project_root)
+    .and_then(|db| db.indexed_contents(project_root))
+    .unwrap_or_else(|err| {
+      eprintln!("[ProjectTree] Embedding index unavailable: {err}");
+      HashMap::new()
+    });
+
+  let mut tree = build_dir(&root, "", markdown_only, &indexed);
+  tree.name = root
+    .file_name()
+    .map(|n| n.to_string_lossy().to_string())
+    .unwrap_or_default();
+  Ok(tree)
+}
+
+fn build_dir(dir: &Path, relative: &str, markdown_only: bool, indexed: &HashMap<String, String>) -> TreeNode {
+  let mut children = Vec::new();
+  match fs::read_dir(dir) {
+    Ok(entries) => {
+      for entry in entries.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+          continue;
+        }
+        let path = if relative.is_empty() {
+          name.clone()
+        } else {
+          format!("{relative}/{name}")
+        };
+        let Ok(file_type) = entry.file_type() else {
+          continue;
+        };
+        if file_type.is_dir() {
+          children.push(build_dir(&entry.path(), &path, markdown_only, indexed));
+        } else if file_type.is_file() {
+          let markdown = markdown_stem(&name).is_some();
+          if markdown_only && !markdown {
+            continue;
+          }
+          children.push(build_file(&entry.path(), name, path, markdown, indexed));
+        }
+      }
+    }
+    Err(err) => eprintln!("[ProjectTree] Skipping {:?}: {err}", dir),
+  }
+
+  children.sort_by(|a, b| {
+    b.is_dir
+      .cmp(&a.is_dir)
+      .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+  });
+  TreeNode {
+    name: dir
+      .file_name()
+      .map(|n| n.to_string_lossy().to_string())
+      .unwrap_or_default(),
+    path: relative.to_string(),
+    is_dir: true,
+    size: children.iter().map(|c| c.size).sum(),
+    modified: children.iter().filter_map(|c| c.modified).max(),
+    document: None,
+    children,
+  }
+}
+
+fn build_file(
+  path: &Path,
+  name: String,
+  relative: String,
+  markdown: bool,
+  indexed: &HashMap<String, String>,
+) -> TreeNode {
+  let metadata = fs::metadata(path).ok();
+  let document = if markdown {
+    match fs::read_to_string(path) {
+      Ok(contents) => Some(describe(&contents, indexed.get(&relative))),
+      Err(err) => {
+        eprintln!("[ProjectTree] Could not read {relative}: {err}");
+        None
+      }
+    }
+  } else {
+    None
+  };
+
+  TreeNode {
+    name,
+    path: relative,
+    is_dir: false,
+    size: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+    modified: metadata
+      .and_then(|m| m.modified().ok())
+      .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+      .map(|d| d.as_secs()),
+    document,
+    children: Vec::new(),
+  }
+}
+
+fn describe(contents: &str, indexed: Option<&String>) -> DocumentInfo {
+  let (frontmatter, body) = split_frontmatter(contents);
+  let frontmatter = frontmatter.unwrap_or_default();
+  let title = frontmatter
+    .title
+    .or_else(|| parse_headings(body).into_iter().next().map(|h| h.text));
+
+  DocumentInfo {
+    word_count: body
+      .split_whitespace()
+      .filter(|word| word.chars().any(char::is_alphanumeric))
+      .count(),
+    title,
+    tags: frontmatter.tags,
+    index_status: match indexed {
+      Some(stored) if stored == contents => IndexStatus::Indexed,
+      Some(_) => IndexStatus::Stale,
+      None => IndexStatus::NotIndexed,
+    },
+  }
+}
@@ -0,0 +1,60 @@
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use sha2::{Digest, Sha256};
+
+use crate::util::error::{Error, FileConflict, Result};
+
+/// Version token for a file's contents: `<mtime in ms>-<sha256>`.
+///
+/// The modification time makes tokens from different saves easy to tell
+/// apart; only the hash decides whether the contents actually changed, so a
+/// `touch` or a checkout that rewrites identical bytes is not a conflict.
+pub fn version_token(path: &Path, contents: &[u8]) -> Result<String> {
+  let modified = fs::metadata(path)?
+    .modified()
+    .ok()
+    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+    .map(|d| d.as_millis())
+    .unwrap_or(0);
+  Ok(format!("{modified}-{}", content_hash(contents)))
+}
+
+/// Version of the file currently on disk, or `None` if it does not exist.
+pub fn current_version(path: &Path) -> Result<Option<String>> {
+  if !path.exists() {
+    return Ok(None);
+  }
+  let contents = fs::read(path)?;
+  Ok(Some(version_token(path, &contents)?))
+}
+
+/// Fail with `Error::Conflict` unless the file on disk still has the contents
+/// `expected` was issued for.
+pub fn ensure_version(path: &Path, display_path: &str, expected: &str) -> Result<()> {
+  let actual = current_version(path)?;
+  let unchanged = actual
+    .as_deref()
+    .map(|actual| hash_part(actual) == hash_part(expected))
+    .unwrap_or(false);
+  if unchanged {
+    return Ok(());
+  }
+  Err(Error::Conflict(FileConflict {
+    path: display_path.to_string(),
+    expected_version: expected.to_string(),
+    actual_version: actual,
+  }))
+}
+
+pub fn content_hash(contents: &[u8]) -> String {
+  Sha256::digest(contents)
+    .iter()
+    .map(|b| format!("{b:02x}"))
+    .collect()
+}
+
+fn hash_part(token: &str) -> &str {
+  token.split_once('-').map(|(_, hash)| hash).unwrap_or(token)
+}
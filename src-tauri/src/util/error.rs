@@ -12,15 +12,48 @@ pub enum Error {
   Sqlite(#[from] rusqlite::Error),
   #[error(transparent)]
   SerdeJson(#[from] serde_json::Error),
+  #[error("Git: {}", .0.message())]
+  Git(#[from] git2::Error),
+  #[error("'{}' was changed on disk since it was opened", .0.path)]
+  Conflict(FileConflict),
 }
 
-// Allow this error to be returned from Tauri commands
+/// A write was refused because the file no longer matches the version the
+/// caller read.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileConflict {
+  pub path: String,
+  pub expected_version: String,
+  /// `None` when the file has been deleted.
+  pub actual_version: Option<String>,
+}
+
+// Allow this error to be returned from Tauri commands. Conflicts are sent as
+// an object so the frontend can offer to reload or overwrite; everything else
+// is a plain message.
 impl Serialize for Error {
   fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
   where
     S: serde::ser::Serializer,
   {
-    serializer.serialize_str(self.to_string().as_ref())
+    match self {
+      Error::Conflict(conflict) => {
+        #[derive(Serialize)]
+        struct ConflictPayload<'a> {
+          kind: &'static str,
+          message: String,
+          #[serde(flatten)]
+          conflict: &'a FileConflict,
+        }
+        ConflictPayload {
+          kind: "conflict",
+          message: self.to_string(),
+          conflict,
+        }
+        .serialize(serializer)
+      }
+      _ => serializer.serialize_str(self.to_string().as_ref()),
+    }
   }
 }
 
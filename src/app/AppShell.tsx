@@ -21,6 +21,9 @@ import { PlaytestSimulatorTab } from "./features/playtest/PlaytestSimulatorTab";
 import { useAutoIndex } from "./hooks/useAutoIndex";
 import { ExportDialog } from "./features/export/ExportDialog";
 import { HelpTab } from "./features/help/HelpTab";
+import { ProjectSearchPanel } from "./features/search/ProjectSearchPanel";
+import { TrashPanel } from "./features/trash/TrashPanel";
+import { LinkCheckerPanel } from "./features/links/LinkCheckerPanel";
 
 function getComponentForTab(tab: WorkspaceTab): React.ComponentType | null {
   switch (tab.type) {
@@ -30,6 +33,9 @@ function getComponentForTab(tab: WorkspaceTab): React.ComponentType | null {
       return SettingsTab;
     case "tool":
       if (tab.payload?.toolId === "help") return HelpTab;
+      if (tab.payload?.toolId === "project-search") return ProjectSearchPanel;
+      if (tab.payload?.toolId === "trash") return TrashPanel;
+      if (tab.payload?.toolId === "link-checker") return LinkCheckerPanel;
       if (tab.payload?.toolId === "dice-calculator") return DiceProbabilityTab;
       if (tab.payload?.toolId === "stat-block-designer") return StatBlockDesignerTab;
       if (tab.payload?.toolId === "lore-map") return LoreMapTab;
@@ -11,7 +11,38 @@ import {
   activeFileTabAtom,
   activeFilePathAtom,
 } from "../../state/atoms/projectAtoms";
-import { listMarkdownFiles } from "../../../lib/api/files";
+import { workspaceAtoms } from "../../state/atoms/workspaceAtoms";
+import { getProjectTree, IndexStatus, movePath, TreeNode } from "../../../lib/api/files";
+import { deletePath } from "../../../lib/api/trash";
+
+const INDEX_STATUS_COLORS: Record<IndexStatus, string> = {
+  indexed: vars.color.state.success,
+  stale: vars.color.state.warning,
+  not_indexed: vars.color.text.muted,
+};
+
+const INDEX_STATUS_LABELS: Record<IndexStatus, string> = {
+  indexed: "Indexed for AI search",
+  stale: "Changed since it was indexed",
+  not_indexed: "Not indexed yet",
+};
+
+function flattenFiles(node: TreeNode): TreeNode[] {
+  return node.is_dir ? node.children.flatMap(flattenFiles) : [node];
+}
+
+// Tooltip for a file row
+function describeFile(node: TreeNode): string {
+  const lines = [node.path];
+  if (node.document) {
+    if (node.document.title) lines.push(node.document.title);
+    lines.push(`${node.document.word_count} words · ${INDEX_STATUS_LABELS[node.document.index_status]}`);
+    if (node.document.tags.length > 0) lines.push(node.document.tags.map(tag => `#${tag}`).join(" "));
+  }
+  if (node.modified) lines.push(`Modified ${new Date(node.modified * 1000).toLocaleString()}`);
+  lines.push("Double-click to rename or move");
+  return lines.join("\n");
+}
 
 export const Sidebar: React.FC = () => {
   const [projectRoot, setProjectFiles] = useAtom(projectRootAtom); // Corrected to match hook return
@@ -19,7 +50,14 @@ export const Sidebar: React.FC = () => {
   const contextFiles = useAtomValue(resolvedContextFilesAtom);
   const setActiveFileTab = useSetAtom(activeFileTabAtom);
   const activePath = useAtomValue(activeFilePathAtom);
+  const renameFileTabs = useSetAtom(workspaceAtoms.renameFileTabsAtom);
+  const closeFileTabs = useSetAtom(workspaceAtoms.closeFileTabsAtom);
+  const [hoveredPath, setHoveredPath] = useState<string | null>(null);
   const [collapsedFolders, setCollapsedFolders] = useState<Set<string>>(new Set());
+  // Double-clicking a file or folder edits its path; links to it are rewritten on save
+  const [renaming, setRenaming] = useState<string | null>(null);
+  const [renameValue, setRenameValue] = useState("");
+  const [actionStatus, setActionStatus] = useState<{ error: boolean; message: string } | null>(null);
 
   const toggleFolder = (path: string) => {
     setCollapsedFolders(prev => {
@@ -33,122 +71,200 @@ export const Sidebar: React.FC = () => {
     });
   };
 
-  // We still need useQuery for fetching, but actions are gone
-  const { isLoading } = useQuery({
+  // The tree also feeds the flat file list other features read from projectFilesAtom
+  const { data: tree, isLoading, refetch } = useQuery({
     queryKey: ["project-files", projectRoot],
     enabled: !!projectRoot,
     queryFn: async () => {
-      if (!projectRoot) return [];
-      const files = await listMarkdownFiles(projectRoot);
-      setFilesList(files);
-      return files;
+      if (!projectRoot) return null;
+      const root = await getProjectTree(projectRoot, true);
+      setFilesList(flattenFiles(root).map(node => ({ path: node.path })));
+      return root;
     },
   });
 
+  const startRename = (path: string) => {
+    setRenaming(path);
+    setRenameValue(path);
+    setActionStatus(null);
+  };
+
+  const commitRename = async () => {
+    const from = renaming;
+    const to = renameValue.trim();
+    setRenaming(null);
+    if (!projectRoot || !from || !to || to === from) return;
+    try {
+      const result = await movePath(projectRoot, from, to);
+      renameFileTabs(result.from, result.to);
+      setActionStatus({
+        error: false,
+        message: `Moved to ${result.to}; updated ${result.updated_links} links. Undo in the assistant panel.`
+      });
+      refetch();
+    } catch (err) {
+      console.error("Rename failed", err);
+      setActionStatus({ error: true, message: String(err) });
+    }
+  };
+
+  const handleDelete = async (path: string) => {
+    if (!projectRoot) return;
+    try {
+      await deletePath(projectRoot, path);
+      closeFileTabs(path);
+      setActionStatus({ error: false, message: `Moved ${path} to the trash (Tools > Trash to restore).` });
+      refetch();
+    } catch (err) {
+      console.error("Delete failed", err);
+      setActionStatus({ error: true, message: String(err) });
+    }
+  };
+
+  // Shown on the hovered row only
+  const renderDeleteButton = (path: string) => hoveredPath === path && (
+    <button
+      type="button"
+      title="Move to trash"
+      onClick={(e) => { e.stopPropagation(); handleDelete(path); }}
+      style={{
+        position: "absolute",
+        right: 2,
+        top: 2,
+        background: "none",
+        border: "none",
+        cursor: "pointer",
+        fontSize: 11,
+        padding: "0 4px"
+      }}
+    >
+      🗑
+    </button>
+  );
+
+  const renderRenameInput = (depth: number) => (
+    <input
+      autoFocus
+      value={renameValue}
+      onChange={(e) => setRenameValue(e.target.value)}
+      onKeyDown={(e) => {
+        if (e.key === "Enter") commitRename();
+        if (e.key === "Escape") setRenaming(null);
+      }}
+      onBlur={() => setRenaming(null)}
+      style={{
+        marginLeft: depth * 12,
+        width: `calc(100% - ${depth * 12}px)`,
+        fontSize: 12,
+        padding: "2px 6px",
+        boxSizing: "border-box"
+      }}
+    />
+  );
+
   const renderFileList = () => {
     if (!projectRoot) return <div>No project selected.</div>;
-    if (isLoading && filesList.length === 0) return <div>Loading files…</div>;
-    if (filesList.length === 0) return <div>No markdown files found.</div>;
-
-    // Build tree
-    const tree: Record<string, any> = {};
-    filesList.forEach(file => {
-        const parts = file.path.split(/[/\\]/);
-        let current = tree;
-        parts.forEach((part, index) => {
-            if (!current[part]) {
-                current[part] = index === parts.length - 1 
-                    ? { __file: true, path: file.path, name: part }
-                    : { __folder: true, name: part, children: {} };
-            }
-            if (index < parts.length - 1) {
-                current = current[part].children;
-            }
-        });
-    });
+    if (isLoading && !tree) return <div>Loading files…</div>;
+    if (!tree || tree.children.length === 0) return <div>No markdown files found.</div>;
 
-    const renderTree = (nodes: Record<string, any>, depth: number = 0, parentPath: string = "") => {
-        // Sort: folders first, then files, alphabetically
-        const entries = Object.values(nodes).sort((a, b) => {
-            if (a.__folder && b.__file) return -1;
-            if (a.__file && b.__folder) return 1;
-            return a.name.localeCompare(b.name);
-        });
-
-        return (
-            <ul style={{ listStyle: "none", paddingLeft: 0, margin: 0 }}>
-                {entries.map((node: any) => {
-                    if (node.__file) {
-                        const isActive = activePath === node.path;
-                        return (
-                            <li key={node.path} style={{ paddingLeft: depth * 12 }}>
-                                <button
-                                    type="button"
-                                    style={{
-                                        background: isActive ? vars.color.background.panelRaised : "none",
-                                        border: "none",
-                                        padding: "2px 6px",
-                                        width: "100%",
-                                        textAlign: "left",
-                                        color: isActive ? vars.color.text.primary : "inherit",
-                                        fontSize: 12,
-                                        cursor: "pointer",
-                                        borderRadius: 4,
-                                        fontWeight: isActive ? 600 : 400,
-                                        whiteSpace: "nowrap",
-                                        overflow: "hidden",
-                                        textOverflow: "ellipsis"
-                                    }}
-                                    onClick={() => setActiveFileTab(node.path)}
-                                    title={node.name}
-                                >
-                                    <span style={{ marginRight: 6 }}>📄</span>
-                                    {node.name}
-                                </button>
-                            </li>
-                        );
-                    } else if (node.__folder) {
-                        const folderPath = parentPath ? `${parentPath}/${node.name}` : node.name;
-                        const isCollapsed = collapsedFolders.has(folderPath);
-                        
-                        return (
-                            <li key={folderPath}>
-                                <div 
-                                    onClick={() => toggleFolder(folderPath)}
-                                    style={{ 
-                                        paddingLeft: depth * 12, 
-                                        paddingTop: 4, 
-                                        paddingBottom: 4,
-                                        fontSize: 12, 
-                                        fontWeight: 600, 
-                                        color: vars.color.text.secondary,
-                                        display: "flex",
-                                        alignItems: "center",
-                                        cursor: "pointer",
-                                        userSelect: "none"
-                                    }}
-                                >
-                                    <span style={{ 
-                                        marginRight: 6, 
-                                        transform: isCollapsed ? "rotate(-90deg)" : "rotate(0deg)",
-                                        transition: "transform 0.2s",
-                                        display: "inline-block",
-                                        fontSize: 10
-                                    }}>▼</span>
-                                    <span style={{ marginRight: 6 }}>{isCollapsed ? "📁" : "📂"}</span>
-                                    {node.name}
-                                </div>
-                                {!isCollapsed && renderTree(node.children, depth + 1, folderPath)}
-                            </li>
-                        );
+    const renderTree = (nodes: TreeNode[], depth: number = 0) => (
+        <ul style={{ listStyle: "none", paddingLeft: 0, margin: 0 }}>
+            {nodes.map((node) => {
+                if (!node.is_dir) {
+                    const isActive = activePath === node.path;
+                    if (renaming === node.path) {
+                        return <li key={node.path}>{renderRenameInput(depth)}</li>;
                     }
-                    return null;
-                })}
-            </ul>
-        );
-    };
+                    return (
+                        <li
+                            key={node.path}
+                            style={{ paddingLeft: depth * 12, position: "relative" }}
+                            onMouseEnter={() => setHoveredPath(node.path)}
+                            onMouseLeave={() => setHoveredPath(null)}
+                        >
+                            <button
+                                type="button"
+                                style={{
+                                    background: isActive ? vars.color.background.panelRaised : "none",
+                                    border: "none",
+                                    padding: "2px 6px",
+                                    width: "100%",
+                                    textAlign: "left",
+                                    color: isActive ? vars.color.text.primary : "inherit",
+                                    fontSize: 12,
+                                    cursor: "pointer",
+                                    borderRadius: 4,
+                                    fontWeight: isActive ? 600 : 400,
+                                    whiteSpace: "nowrap",
+                                    overflow: "hidden",
+                                    textOverflow: "ellipsis"
+                                }}
+                                onClick={() => setActiveFileTab(node.path)}
+                                onDoubleClick={() => startRename(node.path)}
+                                title={describeFile(node)}
+                            >
+                                {node.document && (
+                                    <span style={{ marginRight: 4, color: INDEX_STATUS_COLORS[node.document.index_status] }}>●</span>
+                                )}
+                                <span style={{ marginRight: 6 }}>📄</span>
+                                {node.name}
+                                {node.document && (
+                                    <span style={{ marginLeft: 6, color: vars.color.text.muted, fontWeight: 400 }}>
+                                        {node.document.word_count} w
+                                    </span>
+                                )}
+                            </button>
+                            {renderDeleteButton(node.path)}
+                        </li>
+                    );
+                }
+
+                const isCollapsed = collapsedFolders.has(node.path);
+                if (renaming === node.path) {
+                    return <li key={node.path}>{renderRenameInput(depth)}</li>;
+                }
+
+                return (
+                    <li key={node.path}>
+                        <div
+                            onClick={() => toggleFolder(node.path)}
+                            onMouseEnter={() => setHoveredPath(node.path)}
+                            onMouseLeave={() => setHoveredPath(null)}
+                            onDoubleClick={() => startRename(node.path)}
+                            title={`${node.path} (double-click to rename or move)`}
+                            style={{
+                                paddingLeft: depth * 12,
+                                paddingTop: 4,
+                                paddingBottom: 4,
+                                fontSize: 12,
+                                fontWeight: 600,
+                                color: vars.color.text.secondary,
+                                display: "flex",
+                                position: "relative",
+                                alignItems: "center",
+                                cursor: "pointer",
+                                userSelect: "none"
+                            }}
+                        >
+                            <span style={{
+                                marginRight: 6,
+                                transform: isCollapsed ? "rotate(-90deg)" : "rotate(0deg)",
+                                transition: "transform 0.2s",
+                                display: "inline-block",
+                                fontSize: 10
+                            }}>▼</span>
+                            <span style={{ marginRight: 6 }}>{isCollapsed ? "📁" : "📂"}</span>
+                            {node.name}
+                            {renderDeleteButton(node.path)}
+                        </div>
+                        {!isCollapsed && renderTree(node.children, depth + 1)}
+                    </li>
+                );
+            })}
+        </ul>
+    );
 
-    return renderTree(tree);
+    return renderTree(tree.children);
   };
 
   return (
@@ -166,6 +282,11 @@ export const Sidebar: React.FC = () => {
         <div>
           <strong>Files</strong>
           <div style={{ marginTop: 4, maxHeight: 260, overflowY: "auto" }}>{renderFileList()}</div>
+          {actionStatus && (
+            <div style={{ marginTop: 4, color: actionStatus.error ? vars.color.state.danger : vars.color.text.muted }}>
+              {actionStatus.message}
+            </div>
+          )}
         </div>
         <div>
           <strong>AI Context</strong>
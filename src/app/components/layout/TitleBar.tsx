@@ -103,6 +103,16 @@ export const TitleBar: React.FC = () => {
             <div className={menuItem} onClick={() => toggleMenu("tools")}>Tools</div>
             {activeMenu === "tools" && (
                 <div className={menuDropdown}>
+                    <div className={menuDropdownItem} onClick={() => { closeMenu(); openTool("project-search", "Search"); }}>
+                        Search in Project
+                    </div>
+                    <div className={menuDropdownItem} onClick={() => { closeMenu(); openTool("link-checker", "Check Links"); }}>
+                        Check Links
+                    </div>
+                    <div className={menuDropdownItem} onClick={() => { closeMenu(); openTool("trash", "Trash"); }}>
+                        Trash
+                    </div>
+                    <div style={{ height: 1, backgroundColor: "rgba(255,255,255,0.1)", margin: "4px 0" }} />
                     <div className={menuDropdownItem} onClick={() => { closeMenu(); openTool("dice-calculator", "Dice Calculator"); }}>
                         Dice Probability Calculator
                     </div>
@@ -45,6 +45,25 @@ const suggestedTagStyle = {
     cursor: "pointer"
 };
 
+// "line 4:5 Wizard's Guild" for each place a tag's name was found, or for
+// tags suggested by meaning, the tagged files this one resembles.
+function suggestionSummary(s: TagSuggestion, tag: string): string {
+    const semantic = s.semantic.find(t => t.tag === tag);
+    if (semantic) {
+        return `${Math.round(semantic.confidence * 100)}% confident, similar to:\n${semantic.examples.join("\n")}`;
+    }
+    return s.mentions
+        .filter(m => m.target.tag === tag)
+        .map(m => `line ${m.line}:${m.column} ${m.text}`)
+        .join("\n");
+}
+
+const semanticTagStyle = {
+    ...suggestedTagStyle,
+    backgroundColor: "rgba(0, 150, 255, 0.1)",
+    border: "1px dashed rgba(0, 150, 255, 0.4)"
+};
+
 export const AutoTaggerTab: React.FC = () => {
     const projectRoot = useAtomValue(projectRootAtom);
     const [suggestions, setSuggestions] = useState<TagSuggestion[]>([]);
@@ -54,7 +73,7 @@ export const AutoTaggerTab: React.FC = () => {
     const runScan = async () => {
         if (!projectRoot) return;
         setLoading(true);
-        setStatus("Scanning files for keywords...");
+        setStatus("Scanning files for mentions...");
         try {
             const results = await scanForTags(projectRoot);
             setSuggestions(results);
@@ -68,8 +87,9 @@ export const AutoTaggerTab: React.FC = () => {
     };
 
     const handleApply = async (file: string, tags: string[]) => {
+        if (!projectRoot) return;
         try {
-            await applyTags(file, tags);
+            await applyTags(projectRoot, file, tags);
             // Remove from list
             setSuggestions(prev => prev.filter(s => s.file !== file));
         } catch (e) {
@@ -79,13 +99,14 @@ export const AutoTaggerTab: React.FC = () => {
     };
 
     const handleApplyAll = async () => {
+        if (!projectRoot) return;
         if (!confirm(`Apply all tags to ${suggestions.length} files?`)) return;
         
         setLoading(true);
         setStatus("Applying tags...");
         
         for (const s of suggestions) {
-            await applyTags(s.file, s.suggestedTags);
+            await applyTags(projectRoot, s.file, s.suggestedTags);
         }
         
         setSuggestions([]);
@@ -99,7 +120,7 @@ export const AutoTaggerTab: React.FC = () => {
                 <div>
                     <h2 style={{ margin: 0 }}>Auto-Tagger</h2>
                     <p style={{ margin: "5px 0 0 0", opacity: 0.7, fontSize: "14px" }}>
-                        Scans your files and suggests tags based on file names found in content.
+                        Suggests tags for the files, aliases and codex entities your files mention, and for topics they resemble.
                     </p>
                 </div>
                 <div style={{ display: "flex", gap: "10px" }}>
@@ -146,9 +167,14 @@ export const AutoTaggerTab: React.FC = () => {
 
                         <div>
                             <span style={{ fontSize: "12px", opacity: 0.6, display: "block", marginBottom: "4px" }}>Suggested:</span>
-                            {s.suggestedTags.map(t => (
-                                <span key={t} style={suggestedTagStyle} title="Click to toggle (not impl)">{t}</span>
-                            ))}
+                            {s.suggestedTags.map(t => {
+                                const semantic = s.semantic.find(x => x.tag === t);
+                                return (
+                                    <span key={t} style={semantic ? semanticTagStyle : suggestedTagStyle} title={suggestionSummary(s, t)}>
+                                        {t}{semantic && ` ${Math.round(semantic.confidence * 100)}%`}
+                                    </span>
+                                );
+                            })}
                         </div>
                     </div>
                 ))}
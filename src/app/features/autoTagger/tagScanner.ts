@@ -1,86 +1,62 @@
-import { listMarkdownFiles, readFile, writeFile, FileEntry } from "../../../lib/api/files";
+import { readFile, writeFile } from "../../../lib/api/files";
+import { detectMentions, suggestSemanticTags, Mention, SemanticTag } from "../../../lib/api/tags";
 
 export interface TagSuggestion {
   file: string; // relative path
   currentTags: string[];
   suggestedTags: string[];
+  // Where each suggested tag's name, title or alias was found in the file
+  mentions: Mention[];
+  // Tags suggested by meaning rather than by name, with confidence
+  semantic: SemanticTag[];
 }
 
-// Helper to get base filename without extension
-function getBaseName(path: string): string {
-    const parts = path.replace(/\\/g, "/").split("/");
-    return parts[parts.length - 1].replace(/\.md$/i, "");
-}
-
-// Helper to extract existing tags from content
-function extractTags(content: string): string[] {
-    const tags = new Set<string>();
-    const tagRegex = /(?<=^|\s)(#[a-zA-Z][\w-]*)/g;
-    let match;
-    while ((match = tagRegex.exec(content)) !== null) {
-        tags.add(match[1].toLowerCase());
-    }
-    return Array.from(tags);
-}
-
+// Mentions are matched in the backend on word boundaries, including
+// aliases, codex entity names, plurals and possessives, outside code
+// blocks and existing links. Semantic suggestions come from the embedding
+// index and are skipped if the project has not been indexed.
 export async function scanForTags(projectRoot: string): Promise<TagSuggestion[]> {
-    const files = await listMarkdownFiles(projectRoot);
-    
-    // 1. Build a dictionary of "Keywords" based on file names
-    // e.g. "Wizard.md" -> Keyword "wizard"
-    // We filter out common words or very short ones to avoid noise
-    const keywordMap = new Map<string, string>(); // keyword (lower) -> Tag Name (#Wizard)
-    
-    files.forEach(f => {
-        const name = getBaseName(f.path);
-        if (name.length > 3) { // Skip short names like "Map", "NPC" might be too generic?
-            keywordMap.set(name.toLowerCase(), `#${name.toLowerCase()}`);
-        }
-    });
-
-    // 2. Scan each file
-    const results: TagSuggestion[] = [];
-
-    // Parallel read
-    const fileContents = await Promise.all(
-        files.map(async (f) => ({
-            path: f.path,
-            content: await readFile(f.path).catch(() => "")
-        }))
-    );
-
-    fileContents.forEach(({ path, content }) => {
-        const currentTags = extractTags(content);
-        const suggestedTags = new Set<string>();
-        const lowerContent = content.toLowerCase();
+    const [mentioned, semantic] = await Promise.all([
+        detectMentions(projectRoot),
+        suggestSemanticTags(projectRoot).catch((e) => {
+            console.warn("Semantic tag suggestions unavailable", e);
+            return [];
+        }),
+    ]);
 
-        // Check for keywords in content
-        keywordMap.forEach((tag, keyword) => {
-            // Simple inclusion check (could be improved with regex for word boundaries)
-            // We check if the keyword appears, but the tag is NOT already there
-            if (lowerContent.includes(keyword) && !currentTags.includes(tag)) {
-                // Don't suggest self-tagging (e.g. Wizard.md containing "wizard")
-                const selfName = getBaseName(path).toLowerCase();
-                if (keyword !== selfName) {
-                    suggestedTags.add(tag);
-                }
-            }
-        });
+    const byFile = new Map<string, TagSuggestion>();
+    mentioned
+        .filter(r => r.suggested_tags.length > 0)
+        .forEach(r => byFile.set(r.path, {
+            file: r.path,
+            currentTags: r.current_tags,
+            suggestedTags: r.suggested_tags,
+            mentions: r.mentions.filter(m => r.suggested_tags.includes(m.target.tag)),
+            semantic: [],
+        }));
 
-        if (suggestedTags.size > 0) {
-            results.push({
-                file: path,
-                currentTags: currentTags,
-                suggestedTags: Array.from(suggestedTags)
+    semantic.forEach(r => {
+        const entry = byFile.get(r.path) ?? {
+            file: r.path,
+            currentTags: r.current_tags,
+            suggestedTags: [],
+            mentions: [],
+            semantic: [],
+        };
+        r.suggestions
+            .filter(s => !entry.suggestedTags.includes(s.tag))
+            .forEach(s => {
+                entry.suggestedTags.push(s.tag);
+                entry.semantic.push(s);
             });
-        }
+        if (entry.suggestedTags.length > 0) byFile.set(r.path, entry);
     });
 
-    return results;
+    return Array.from(byFile.values()).sort((a, b) => a.file.localeCompare(b.file));
 }
 
-export async function applyTags(file: string, tagsToAdd: string[]) {
-    let content = await readFile(file);
+export async function applyTags(projectRoot: string, file: string, tagsToAdd: string[]) {
+    let content = await readFile(projectRoot, file);
     
     // Append to end of file with a newline
     // Check if file ends with newline
@@ -90,6 +66,6 @@ export async function applyTags(file: string, tagsToAdd: string[]) {
     content += "\n";
     content += tagsToAdd.join(" ");
     
-    await writeFile(file, content);
+    await writeFile(projectRoot, file, content);
 }
 
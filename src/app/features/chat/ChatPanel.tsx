@@ -5,7 +5,9 @@ import { projectRootAtom } from "../../state/atoms/projectAtoms";
 import { vars } from "../../theme/tokens.css";
 import { MarkdownPreview } from "../../components/markdown/MarkdownPreview";
 import { DiffViewer } from "../diff/DiffViewer";
-import { readFile, writeFile } from "../../../lib/api/files";
+import { MergeConflicts } from "../diff/MergeConflicts";
+import { mergeFileEdit, MergeConflict, MergeResolution } from "../../../lib/api/files";
+import { applyEditTransaction, undoEditTransaction, redoEditTransaction } from "../../../lib/api/history";
 import { useAutoIndex } from "../../hooks/useAutoIndex";
 
 // Types for file edit proposals
@@ -13,6 +15,9 @@ interface FileEditProposal {
   path: string;
   newContent: string;
   originalContent?: string;
+  errors?: string[];
+  // Set when the file changed on disk since the AI read it and both changed the same lines
+  conflicts?: MergeConflict[];
   status: "pending" | "accepted" | "rejected";
 }
 
@@ -55,9 +60,10 @@ interface AIStatus {
   startTime?: number;
 }
 
-// Parse AI response for ALL file edit blocks (supports multiple)
+// Split an AI response into text and file edit/patch segments for display.
+// The edits themselves are parsed and validated by the backend (ChatResponse.edits).
 function parseFileEditBlocksMultiple(content: string): ParsedMessageWithMultipleEdits {
-  const regex = /<file_edit\s+path="([^"]+)">\s*([\s\S]*?)\s*<\/file_edit>/g;
+  const regex = /<(file_edit|file_patch)\s+path="([^"]+)">\s*([\s\S]*?)\s*<\/\1>/g;
   const fileEdits: FileEditBlock[] = [];
   const segments: ParsedMessageWithMultipleEdits["segments"] = [];
   
@@ -66,7 +72,7 @@ function parseFileEditBlocksMultiple(content: string): ParsedMessageWithMultiple
   let editIndex = 0;
   
   while ((match = regex.exec(content)) !== null) {
-    const [fullMatch, path, editContent] = match;
+    const [fullMatch, , path, editContent] = match;
     const startIndex = match.index;
     const endIndex = startIndex + fullMatch.length;
     
@@ -102,6 +108,13 @@ function parseFileEditBlocksMultiple(content: string): ParsedMessageWithMultiple
   return { segments, fileEdits };
 }
 
+// Keys of a message's edits that are still pending and passed backend validation.
+function pendingValidEditKeys(fileEdits: Map<string, FileEditProposal>, messageId: string): string[] {
+  return Array.from(fileEdits.entries())
+    .filter(([key, edit]) => key.startsWith(`${messageId}-`) && edit.status === "pending" && !edit.errors?.length && !edit.conflicts)
+    .map(([key]) => key);
+}
+
 // Generate a unique ID
 function generateId(): string {
   return `${Date.now()}-${Math.random().toString(36).substr(2, 9)}`;
@@ -476,7 +489,8 @@ const FileEditCard: React.FC<{
   edit: FileEditProposal;
   onAccept: () => void;
   onReject: () => void;
-}> = ({ edit, onAccept, onReject }) => {
+  onResolveConflicts: (resolutions: MergeResolution[]) => void;
+}> = ({ edit, onAccept, onReject, onResolveConflicts }) => {
   return (
     <div style={{
       margin: "12px 0",
@@ -521,27 +535,43 @@ const FileEditCard: React.FC<{
             >
               Discard
             </button>
-            <button
-              onClick={onAccept}
-              style={{
-                padding: "4px 12px",
-                borderRadius: 4,
-                border: "none",
-                background: "#fff",
-                color: vars.color.state.info,
-                cursor: "pointer",
-                fontWeight: 600,
-                fontSize: 12
-              }}
-            >
-              Accept
-            </button>
+            {!edit.errors?.length && !edit.conflicts && (
+              <button
+                onClick={onAccept}
+                style={{
+                  padding: "4px 12px",
+                  borderRadius: 4,
+                  border: "none",
+                  background: "#fff",
+                  color: vars.color.state.info,
+                  cursor: "pointer",
+                  fontWeight: 600,
+                  fontSize: 12
+                }}
+              >
+                Accept
+              </button>
+            )}
           </div>
         )}
       </div>
+
+      {/* Validation errors reported by the backend */}
+      {edit.status === "pending" && !!edit.errors?.length && (
+        <div style={{ padding: "8px 12px", fontSize: 12, color: vars.color.state.danger }}>
+          {edit.errors.map((error, i) => <div key={i}>⚠ {error}</div>)}
+        </div>
+      )}
       
+      {/* Conflicts with changes made on disk since the AI read the file */}
+      {edit.status === "pending" && edit.conflicts && (
+        <div style={{ maxHeight: 400, overflow: "auto" }}>
+          <MergeConflicts conflicts={edit.conflicts} onResolve={onResolveConflicts} onCancel={onReject} />
+        </div>
+      )}
+
       {/* Diff Content */}
-      {edit.status === "pending" && (
+      {edit.status === "pending" && !edit.conflicts && (
         <div style={{ height: 300, overflow: "auto" }}>
           <DiffViewer
             original={edit.originalContent || ""}
@@ -559,9 +589,11 @@ const ChatMessageBubble: React.FC<{
   fileEdits: Map<string, FileEditProposal>;
   onAcceptEdit: (editKey: string) => void;
   onRejectEdit: (editKey: string) => void;
+  onAcceptAllEdits: (messageId: string) => void;
+  onResolveConflicts: (editKey: string, resolutions: MergeResolution[]) => void;
   onRetry?: () => void;
   onDismissError?: () => void;
-}> = ({ message, fileEdits, onAcceptEdit, onRejectEdit, onRetry, onDismissError }) => {
+}> = ({ message, fileEdits, onAcceptEdit, onRejectEdit, onAcceptAllEdits, onResolveConflicts, onRetry, onDismissError }) => {
   const [copied, setCopied] = useState(false);
   
   const handleCopy = async () => {
@@ -577,6 +609,7 @@ const ChatMessageBubble: React.FC<{
   
   // Parse message for multiple file edits
   const parsed = message.role === "assistant" ? parseFileEditBlocksMultiple(message.content) : null;
+  const acceptableEdits = pendingValidEditKeys(fileEdits, message.id).length;
   
   return (
     <div style={{
@@ -661,12 +694,30 @@ const ChatMessageBubble: React.FC<{
                     edit={fileEdit}
                     onAccept={() => onAcceptEdit(editKey)}
                     onReject={() => onRejectEdit(editKey)}
+                    onResolveConflicts={(resolutions) => onResolveConflicts(editKey, resolutions)}
                   />
                 );
               }
               return null;
             }
-          })
+          }).concat(acceptableEdits > 1 ? [
+            <button
+              key="accept-all"
+              onClick={() => onAcceptAllEdits(message.id)}
+              style={{
+                padding: "4px 12px",
+                borderRadius: 4,
+                border: "none",
+                background: vars.color.state.info,
+                color: "#fff",
+                cursor: "pointer",
+                fontWeight: 600,
+                fontSize: 12
+              }}
+            >
+              Accept all {acceptableEdits} edits
+            </button>
+          ] : [])
         ) : (
           <div style={{
             whiteSpace: "pre-wrap",
@@ -966,13 +1017,6 @@ export const ChatPanel: React.FC = () => {
     });
   }, [messages, currentSessionId]);
 
-  function joinPath(root: string, relative: string): string {
-    if (root.endsWith("/") || root.endsWith("\\")) {
-      return root + relative;
-    }
-    return `${root}/${relative}`;
-  }
-
   const handleStop = useCallback(() => {
     if (abortControllerRef.current) {
       abortControllerRef.current.abort();
@@ -1036,35 +1080,21 @@ export const ChatPanel: React.FC = () => {
       
       setMessages(prev => [...prev, assistantMsg]);
       
-      // Check if response contains file edit proposals (supports multiple)
-      const parsed = parseFileEditBlocksMultiple(response.content);
-      if (parsed.fileEdits.length > 0 && projectRoot) {
-        setAiStatus({ stage: "editing", message: `Loading ${parsed.fileEdits.length} file(s) for comparison`, startTime: Date.now() });
-        
-        // Load all files for diff comparison
-        for (let i = 0; i < parsed.fileEdits.length; i++) {
-          const fileEdit = parsed.fileEdits[i];
-          const editKey = `${assistantMsg.id}-${i}`;
-          const fullPath = joinPath(projectRoot, fileEdit.path);
-          
-            try {
-                const originalContent = await readFile(fullPath);
-            setFileEdits(prev => new Map(prev).set(editKey, {
-              path: fileEdit.path,
-              newContent: fileEdit.content,
-                    originalContent,
-                    status: "pending"
-                }));
-            } catch {
-            // File might not exist yet
-            setFileEdits(prev => new Map(prev).set(editKey, {
-              path: fileEdit.path,
-              newContent: fileEdit.content,
-                    originalContent: "",
-                    status: "pending"
-                }));
-            }
-        }
+      // File edit proposals are parsed and diffed by the backend (supports multiple)
+      if (response.edits.length > 0 && projectRoot) {
+        setFileEdits(prev => {
+          const next = new Map(prev);
+          response.edits.forEach((edit, i) => {
+            next.set(`${assistantMsg.id}-${i}`, {
+              path: edit.path,
+              newContent: edit.new_contents,
+              originalContent: edit.original_contents ?? "",
+              errors: edit.errors,
+              status: "pending"
+            });
+          });
+          return next;
+        });
       }
       
       setAiStatus({ stage: "done", message: "" });
@@ -1105,22 +1135,83 @@ export const ChatPanel: React.FC = () => {
     setAiStatus({ stage: "idle", message: "" });
   }, []);
 
-  async function handleAcceptEdit(editKey: string) {
-    const edit = fileEdits.get(editKey);
-    if (!edit || !projectRoot) return;
+  async function acceptEdits(editKeys: string[], resolutions: Record<string, MergeResolution[]> = {}) {
+    const edits = editKeys
+      .map(key => [key, fileEdits.get(key)] as const)
+      .filter((entry): entry is readonly [string, FileEditProposal] => !!entry[1]);
+    if (edits.length === 0 || !projectRoot) return;
     
     try {
-      const fullPath = joinPath(projectRoot, edit.path);
-      await writeFile(fullPath, edit.newContent);
+      // Files may have changed since the AI read them; merge instead of overwriting
+      const merges = await Promise.all(edits.map(([key, edit]) => mergeFileEdit({
+        project_root: projectRoot,
+        path: edit.path,
+        base: edit.originalContent ?? "",
+        proposed: edit.newContent,
+        resolutions: resolutions[key]
+      })));
+      if (merges.some(merge => merge.conflicts.length > 0)) {
+        setFileEdits(prev => {
+          const newMap = new Map(prev);
+          edits.forEach(([key, edit], i) => {
+            if (merges[i].conflicts.length > 0) {
+              newMap.set(key, { ...edit, conflicts: merges[i].conflicts });
+            }
+          });
+          return newMap;
+        });
+        return;
+      }
+
+      // All edits are written atomically and can be undone as one step
+      await applyEditTransaction({
+        project_root: projectRoot,
+        label: `AI edit: ${edits.map(([, edit]) => edit.path).join(", ")}`,
+        edits: edits.map(([, edit], i) => ({ path: edit.path, contents: merges[i].merged }))
+      });
       setFileEdits(prev => {
         const newMap = new Map(prev);
-        newMap.set(editKey, { ...edit, status: "accepted" });
+        edits.forEach(([key, edit]) => newMap.set(key, { ...edit, conflicts: undefined, status: "accepted" }));
         return newMap;
       });
       // Trigger re-indexing with status display
       triggerIndex(true, true);
     } catch (err) {
       console.error("Failed to save file:", err);
+      setFileEdits(prev => {
+        const newMap = new Map(prev);
+        edits.forEach(([key, edit]) => newMap.set(key, { ...edit, errors: [String(err)] }));
+        return newMap;
+      });
+    }
+  }
+
+  function handleAcceptEdit(editKey: string) {
+    return acceptEdits([editKey]);
+  }
+
+  function handleResolveConflicts(editKey: string, resolutions: MergeResolution[]) {
+    return acceptEdits([editKey], { [editKey]: resolutions });
+  }
+
+  function handleAcceptAllEdits(messageId: string) {
+    return acceptEdits(pendingValidEditKeys(fileEdits, messageId));
+  }
+
+  async function handleUndoRedo(action: "undo" | "redo") {
+    if (!projectRoot) return;
+    try {
+      const tx = action === "undo"
+        ? await undoEditTransaction(projectRoot)
+        : await redoEditTransaction(projectRoot);
+      if (tx) triggerIndex(true, true);
+    } catch (err) {
+      setMessages(prev => [...prev, {
+        id: generateId(),
+        role: "system",
+        content: `Could not ${action} the last edit: ${String(err)}`,
+        timestamp: Date.now()
+      }]);
     }
   }
 
@@ -1242,6 +1333,24 @@ export const ChatPanel: React.FC = () => {
             {/* Context usage wheel */}
             <ContextWheel usedTokens={contextTokens} maxTokens={MAX_CONTEXT_TOKENS} />
             
+            {projectRoot && (["undo", "redo"] as const).map(action => (
+              <button
+                key={action}
+                onClick={() => handleUndoRedo(action)}
+                title={action === "undo" ? "Undo the last applied edit" : "Redo the last undone edit"}
+                style={{
+                    background: "none",
+                    border: `1px solid ${vars.color.border.subtle}`,
+                    color: vars.color.text.secondary,
+                    borderRadius: 4,
+                    padding: "2px 8px",
+                    fontSize: 10,
+                    cursor: "pointer"
+                }}
+              >
+                {action === "undo" ? "↶ Undo" : "↷ Redo"}
+              </button>
+            ))}
             {messages.length > 0 && (
               <button
                 onClick={handleNewChat}
@@ -1324,6 +1433,8 @@ export const ChatPanel: React.FC = () => {
             fileEdits={fileEdits}
             onAcceptEdit={handleAcceptEdit}
             onRejectEdit={handleRejectEdit}
+            onAcceptAllEdits={handleAcceptAllEdits}
+            onResolveConflicts={handleResolveConflicts}
             onRetry={msg.error ? handleRetry : undefined}
             onDismissError={msg.error ? () => handleDismissError(msg.id) : undefined}
           />
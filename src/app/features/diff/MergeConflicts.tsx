@@ -0,0 +1,87 @@
+import React, { useState } from "react";
+import type { MergeConflict, MergeResolution } from "../../../lib/api/files";
+import { vars } from "../../theme/tokens.css";
+
+export interface MergeConflictsProps {
+  conflicts: MergeConflict[];
+  onResolve: (resolutions: MergeResolution[]) => void;
+  onCancel: () => void;
+}
+
+const CHOICES: Array<{ value: MergeResolution; label: string }> = [
+  { value: "current", label: "Keep mine" },
+  { value: "proposed", label: "Use AI" },
+  { value: "both", label: "Keep both" },
+];
+
+// Lists the regions changed both by the user and by the AI since the AI read
+// the file, and lets the user pick a side for each one.
+export const MergeConflicts: React.FC<MergeConflictsProps> = ({ conflicts, onResolve, onCancel }) => {
+  const [choices, setChoices] = useState<Array<MergeResolution | null>>(() => conflicts.map(() => null));
+  const allChosen = choices.every(choice => choice !== null);
+
+  const textBlock = (title: string, text: string) => (
+    <div style={{ flex: 1, minWidth: 0 }}>
+      <div style={{ fontSize: 11, color: vars.color.text.muted, marginBottom: 4 }}>{title}</div>
+      <pre style={{
+        margin: 0,
+        padding: 8,
+        fontSize: 12,
+        whiteSpace: "pre-wrap",
+        backgroundColor: vars.color.background.panelRaised,
+        border: `1px solid ${vars.color.border.subtle}`,
+        borderRadius: 4
+      }}>
+        {text || "(removed)"}
+      </pre>
+    </div>
+  );
+
+  return (
+    <div style={{ height: "100%", overflow: "auto", padding: 12, display: "flex", flexDirection: "column", gap: 12 }}>
+      <div style={{ fontSize: 13, color: vars.color.text.primary }}>
+        The file changed while the AI was working. Choose which version to keep for each conflicting section.
+      </div>
+      {conflicts.map((conflict, i) => (
+        <div key={conflict.index} style={{ border: `1px solid ${vars.color.border.subtle}`, borderRadius: 6, padding: 8 }}>
+          <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center", marginBottom: 8 }}>
+            <strong style={{ fontSize: 12 }}>Conflict {i + 1} (line {conflict.start_line})</strong>
+            <div style={{ display: "flex", gap: 4 }}>
+              {CHOICES.map(choice => (
+                <button
+                  key={choice.value}
+                  onClick={() => setChoices(prev => prev.map((c, j) => (j === i ? choice.value : c)))}
+                  style={{
+                    padding: "2px 8px",
+                    fontSize: 11,
+                    borderRadius: 4,
+                    cursor: "pointer",
+                    border: `1px solid ${vars.color.border.subtle}`,
+                    background: choices[i] === choice.value ? vars.color.state.info : "transparent",
+                    color: choices[i] === choice.value ? "#fff" : vars.color.text.secondary
+                  }}
+                >
+                  {choice.label}
+                </button>
+              ))}
+            </div>
+          </div>
+          <div style={{ display: "flex", gap: 8 }}>
+            {textBlock("Your version", conflict.current)}
+            {textBlock("AI version", conflict.proposed)}
+          </div>
+        </div>
+      ))}
+      <div style={{ display: "flex", gap: 8, justifyContent: "flex-end" }}>
+        <button onClick={onCancel} style={{ padding: "4px 12px", cursor: "pointer" }}>Cancel</button>
+        <button
+          onClick={() => onResolve(choices.map(choice => choice ?? "current"))}
+          disabled={!allChosen}
+          style={{ padding: "4px 12px", fontWeight: "bold", cursor: allChosen ? "pointer" : "not-allowed" }}
+        >
+          Apply Resolutions
+        </button>
+      </div>
+    </div>
+  );
+};
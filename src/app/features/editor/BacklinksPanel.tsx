@@ -0,0 +1,111 @@
+import React, { useEffect, useState } from "react";
+import { useSetAtom } from "jotai";
+import { workspaceAtoms } from "../../state/atoms/workspaceAtoms";
+import { getBacklinks, getOutgoingLinks, IndexedLink } from "../../../lib/api/links";
+import { vars } from "../../theme/tokens.css";
+
+export interface BacklinksPanelProps {
+  projectRoot: string;
+  path: string;
+  onClose: () => void;
+}
+
+function writtenTarget(link: IndexedLink): string {
+  const target = link.anchor ? `${link.target}#${link.anchor}` : link.target;
+  return target || "(this file)";
+}
+
+const LinkRow: React.FC<{ link: IndexedLink; label: string; onOpen: () => void }> = ({ link, label, onOpen }) => (
+  <div
+    onClick={onOpen}
+    title={link.context}
+    style={{
+      padding: "6px 12px",
+      fontSize: 12,
+      cursor: "pointer",
+      borderBottom: `1px solid ${vars.color.border.subtle}`
+    }}
+  >
+    <div style={{ color: link.resolved === null ? vars.color.state.danger : vars.color.accent.primary }}>
+      {label} <span style={{ color: vars.color.text.muted }}>· line {link.line}</span>
+    </div>
+    <div style={{ color: vars.color.text.secondary, whiteSpace: "nowrap", overflow: "hidden", textOverflow: "ellipsis" }}>
+      {link.context}
+    </div>
+  </div>
+);
+
+// Files linking to the open file and the links it contains, both read from
+// the link index.
+export const BacklinksPanel: React.FC<BacklinksPanelProps> = ({ projectRoot, path, onClose }) => {
+  const openFile = useSetAtom(workspaceAtoms.openFileTabAtom);
+  const [backlinks, setBacklinks] = useState<IndexedLink[]>([]);
+  const [outgoing, setOutgoing] = useState<IndexedLink[]>([]);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    let cancelled = false;
+    setError(null);
+    Promise.all([getBacklinks(projectRoot, path), getOutgoingLinks(projectRoot, path)])
+      .then(([incoming, links]) => {
+        if (cancelled) return;
+        // Links to a heading in the same file are not backlinks
+        setBacklinks(incoming.filter((link) => link.source !== path));
+        setOutgoing(links);
+      })
+      .catch((err) => {
+        console.error("Failed to load links", err);
+        if (!cancelled) setError("Failed to load links.");
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [projectRoot, path]);
+
+  const sectionTitle = { padding: "8px 12px", fontSize: 12, fontWeight: 600, color: vars.color.text.primary };
+
+  return (
+    <div style={{ height: "100%", overflowY: "auto", backgroundColor: vars.color.background.panel }}>
+      <div style={{
+        padding: 8,
+        fontSize: 12,
+        display: "flex",
+        justifyContent: "space-between",
+        alignItems: "center",
+        borderBottom: `1px solid ${vars.color.border.subtle}`
+      }}>
+        <span style={{ color: vars.color.text.secondary }}>Links for {path}</span>
+        <button onClick={onClose} style={{ padding: "4px 12px", cursor: "pointer" }}>Close</button>
+      </div>
+      {error && <div style={{ padding: 12, fontSize: 12, color: vars.color.state.danger }}>{error}</div>}
+
+      <div style={sectionTitle}>Linked from ({backlinks.length})</div>
+      {backlinks.length === 0 && (
+        <div style={{ padding: "0 12px 8px", fontSize: 12, color: vars.color.text.muted }}>No other file links here.</div>
+      )}
+      {backlinks.map((link) => (
+        <LinkRow
+          key={`${link.source}:${link.line}:${link.column}`}
+          link={link}
+          label={link.source}
+          onOpen={() => openFile(link.source)}
+        />
+      ))}
+
+      <div style={sectionTitle}>Links to ({outgoing.length})</div>
+      {outgoing.length === 0 && (
+        <div style={{ padding: "0 12px 8px", fontSize: 12, color: vars.color.text.muted }}>This file has no links.</div>
+      )}
+      {outgoing.map((link) => (
+        <LinkRow
+          key={`${link.line}:${link.column}`}
+          link={link}
+          label={link.resolved === null ? `${writtenTarget(link)} (missing)` : writtenTarget(link)}
+          onOpen={() => {
+            if (link.resolved) openFile(link.resolved);
+          }}
+        />
+      ))}
+    </div>
+  );
+};
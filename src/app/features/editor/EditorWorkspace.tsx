@@ -2,15 +2,18 @@ import React, { useEffect, useState } from "react";
 import { useAtomValue, useSetAtom } from "jotai";
 import { MarkdownEditor } from "./MarkdownEditor";
 import { DiffViewer } from "../diff/DiffViewer";
+import { MergeConflicts } from "../diff/MergeConflicts";
 import { MarkdownPreview } from "../../components/markdown/MarkdownPreview";
 import { activeFilePathAtom, projectRootAtom } from "../../state/atoms/projectAtoms";
 import { exportContentAtom } from "../../state/atoms/exportAtoms";
-import { readFile, writeFile } from "../../../lib/api/files";
+import { readFileVersioned, writeFile, isFileConflict, mergeFileEdit, MergeConflict, MergeResolution } from "../../../lib/api/files";
 import { useFileEdit } from "../../../lib/api/ai";
 import { useAutoIndex } from "../../hooks/useAutoIndex";
 import { vars } from "../../theme/tokens.css";
 import { LayoutToolbar } from "./LayoutToolbar";
 import { StatBlockInserter } from "./StatBlockInserter";
+import { FileHistoryPanel } from "./FileHistoryPanel";
+import { BacklinksPanel } from "./BacklinksPanel";
 
 function joinProjectPath(projectRoot: string, relativePath: string): string {
   if (!projectRoot) return relativePath;
@@ -20,6 +23,21 @@ function joinProjectPath(projectRoot: string, relativePath: string): string {
   return `${projectRoot}/${relativePath}`;
 }
 
+// Heading texts for the AI edit section picker (fenced code blocks skipped).
+function listHeadings(markdown: string): string[] {
+  const headings: string[] = [];
+  let inFence = false;
+  for (const line of markdown.split("\n")) {
+    if (/^\s{0,3}(```|~~~)/.test(line)) {
+      inFence = !inFence;
+      continue;
+    }
+    const match = !inFence && line.match(/^\s{0,3}#{1,6}\s+(.+?)\s*#*\s*$/);
+    if (match) headings.push(match[1]);
+  }
+  return headings;
+}
+
 export const EditorWorkspace: React.FC = () => {
   const projectRoot = useAtomValue(projectRootAtom);
   const activePath = useAtomValue(activeFilePathAtom);
@@ -27,12 +45,21 @@ export const EditorWorkspace: React.FC = () => {
   const [isLoading, setIsLoading] = useState(false);
   const [isSaving, setIsSaving] = useState(false);
   const [error, setError] = useState<string | null>(null);
+  // Version of the file as last read or saved, used to detect changes by other programs
+  const [fileVersion, setFileVersion] = useState<string | null>(null);
+  const [saveConflict, setSaveConflict] = useState(false);
+  const [showHistory, setShowHistory] = useState(false);
+  const [showLinks, setShowLinks] = useState(false);
   
   // AI Edit State
   const [isAiEditMode, setIsAiEditMode] = useState(false);
   const [aiInstruction, setAiInstruction] = useState("");
+  // Heading of the section to edit; empty edits the whole file
+  const [aiSection, setAiSection] = useState("");
   const [diffOriginal, setDiffOriginal] = useState<string | null>(null);
   const [diffModified, setDiffModified] = useState<string | null>(null);
+  // Conflicts between the AI edit and typing done while it was generated
+  const [mergeConflicts, setMergeConflicts] = useState<MergeConflict[] | null>(null);
 
   // Preview State
   const [isPreviewMode, setIsPreviewMode] = useState(false);
@@ -46,6 +73,11 @@ export const EditorWorkspace: React.FC = () => {
   }, [value, setExportContent]);
 
   const { mutateAsync: requestEdit, isPending: isAiThinking } = useFileEdit();
+
+  // Section headings belong to the file they were picked from
+  useEffect(() => {
+    setAiSection("");
+  }, [activePath]);
   const { triggerIndex } = useAutoIndex();
 
   useEffect(() => {
@@ -54,6 +86,7 @@ export const EditorWorkspace: React.FC = () => {
       setError(null);
       setIsAiEditMode(false);
       setDiffModified(null);
+      setMergeConflicts(null);
       setIsPreviewMode(false);
       return;
     }
@@ -63,11 +96,15 @@ export const EditorWorkspace: React.FC = () => {
 
     setIsLoading(true);
     setError(null);
+    setSaveConflict(false);
+    setShowHistory(false);
+    setShowLinks(false);
 
-    readFile(fullPath)
-      .then((contents) => {
+    readFileVersioned(projectRoot, fullPath)
+      .then((file) => {
         if (!cancelled) {
-          setValue(contents);
+          setValue(file.contents);
+          setFileVersion(file.version);
         }
       })
       .catch((err) => {
@@ -87,24 +124,46 @@ export const EditorWorkspace: React.FC = () => {
     };
   }, [projectRoot, activePath]);
 
-  const handleSave = async () => {
+  const saveFile = async (overwrite: boolean) => {
     if (!projectRoot || !activePath) return;
     
     const fullPath = joinProjectPath(projectRoot, activePath);
     setIsSaving(true);
     setError(null);
     try {
-      await writeFile(fullPath, value);
+      const expectedVersion = overwrite ? undefined : fileVersion ?? undefined;
+      setFileVersion(await writeFile(projectRoot, fullPath, value, expectedVersion));
+      setSaveConflict(false);
       // Trigger re-indexing (debounced) after save
       triggerIndex();
     } catch (err) {
-      console.error("Failed to save file", err);
-      setError("Failed to save file.");
+      if (isFileConflict(err)) {
+        setSaveConflict(true);
+      } else {
+        console.error("Failed to save file", err);
+        setError("Failed to save file.");
+      }
     } finally {
       setIsSaving(false);
     }
   };
 
+  const handleSave = () => saveFile(false);
+
+  // Discard local edits and load the version that was changed on disk
+  const handleReloadFromDisk = async () => {
+    if (!projectRoot || !activePath) return;
+    try {
+      const file = await readFileVersioned(projectRoot, joinProjectPath(projectRoot, activePath));
+      setValue(file.contents);
+      setFileVersion(file.version);
+      setSaveConflict(false);
+    } catch (err) {
+      console.error("Failed to reload file", err);
+      setError("Failed to reload file.");
+    }
+  };
+
   const handleAiEditSubmit = async () => {
     if (!activePath || !aiInstruction.trim()) return;
     
@@ -112,26 +171,58 @@ export const EditorWorkspace: React.FC = () => {
         const response = await requestEdit({
             path: activePath,
             contents: value,
-            instruction: aiInstruction
+            instruction: aiInstruction,
+            mode: "patch",
+            section: aiSection || undefined
         });
-        setDiffOriginal(value);
+        if (response.error) {
+            setError("AI Edit failed: " + response.error);
+            return;
+        }
+        setDiffOriginal(response.base_contents);
         setDiffModified(response.updated_contents);
     } catch (e) {
         setError("AI Edit failed: " + String(e));
     }
   };
 
-  const handleAcceptDiff = () => {
-      if (diffModified) {
-          setValue(diffModified);
-          setDiffModified(null);
-          setDiffOriginal(null);
-          setIsAiEditMode(false);
-          setAiInstruction("");
+  const finishAiEdit = () => {
+      setDiffModified(null);
+      setDiffOriginal(null);
+      setMergeConflicts(null);
+      setIsAiEditMode(false);
+      setAiInstruction("");
+      setAiSection("");
+  };
+
+  // The editor may have changed since the AI read it, so the AI's result is
+  // merged with the current buffer instead of replacing it.
+  const mergeAiEdit = async (resolutions?: MergeResolution[]) => {
+      if (diffModified === null || diffOriginal === null || !projectRoot || !activePath) return;
+      try {
+          const result = await mergeFileEdit({
+              project_root: projectRoot,
+              path: activePath,
+              base: diffOriginal,
+              proposed: diffModified,
+              current: value,
+              resolutions
+          });
+          if (result.conflicts.length > 0) {
+              setMergeConflicts(result.conflicts);
+              return;
+          }
+          setValue(result.merged);
+          finishAiEdit();
+      } catch (e) {
+          setError("Failed to merge AI edit: " + String(e));
       }
   };
 
+  const handleAcceptDiff = () => mergeAiEdit();
+
   const handleRejectDiff = () => {
+      setMergeConflicts(null);
       setDiffModified(null);
       setDiffOriginal(null);
   };
@@ -211,6 +302,38 @@ export const EditorWorkspace: React.FC = () => {
             ✨ AI Edit
           </button>
 
+          <button 
+            type="button" 
+            onClick={() => { setShowHistory(!showHistory); setShowLinks(false); }}
+            disabled={!activePath || !projectRoot || diffModified !== null}
+            style={{
+                background: "none",
+                border: `1px solid ${vars.color.border.subtle}`,
+                color: showHistory ? vars.color.accent.primary : vars.color.text.secondary,
+                borderRadius: 4,
+                padding: "4px 8px",
+                cursor: "pointer"
+            }}
+          >
+            🕘 History
+          </button>
+
+          <button 
+            type="button" 
+            onClick={() => { setShowLinks(!showLinks); setShowHistory(false); }}
+            disabled={!activePath || !projectRoot || diffModified !== null}
+            style={{
+                background: "none",
+                border: `1px solid ${vars.color.border.subtle}`,
+                color: showLinks ? vars.color.accent.primary : vars.color.text.secondary,
+                borderRadius: 4,
+                padding: "4px 8px",
+                cursor: "pointer"
+            }}
+          >
+            🔗 Links
+          </button>
+
           <button 
             type="button" 
             onClick={handleSave} 
@@ -240,6 +363,24 @@ export const EditorWorkspace: React.FC = () => {
               display: "flex",
               gap: 8
           }}>
+              <select
+                value={aiSection}
+                onChange={(e) => setAiSection(e.target.value)}
+                title="Limit the edit to one section"
+                style={{
+                    maxWidth: 200,
+                    padding: "8px",
+                    borderRadius: 4,
+                    border: `1px solid ${vars.color.border.subtle}`,
+                    backgroundColor: vars.color.background.base,
+                    color: vars.color.text.primary
+                }}
+              >
+                <option value="">Whole file</option>
+                {listHeadings(value).map((heading, i) => (
+                  <option key={i} value={heading}>{heading}</option>
+                ))}
+              </select>
               <input 
                 autoFocus
                 value={aiInstruction}
@@ -273,6 +414,26 @@ export const EditorWorkspace: React.FC = () => {
           </div>
       )}
 
+      {/* Save conflict: the file changed on disk since it was opened */}
+      {saveConflict && (
+          <div style={{ 
+            padding: 8, 
+            backgroundColor: vars.color.state.danger, 
+            color: "#fff",
+            display: "flex", 
+            justifyContent: "space-between", 
+            alignItems: "center" 
+          }}>
+              <span style={{ marginLeft: 8, fontWeight: 600 }}>
+                This file was changed outside CodexLotus since you opened it.
+              </span>
+              <div style={{ display: "flex", gap: 8 }}>
+                  <button onClick={handleReloadFromDisk} style={{ padding: "4px 12px", cursor: "pointer" }}>Reload (discard my changes)</button>
+                  <button onClick={() => saveFile(true)} style={{ padding: "4px 12px", fontWeight: "bold", cursor: "pointer" }}>Overwrite</button>
+              </div>
+          </div>
+      )}
+
       {/* Diff Action Bar */}
       {diffModified && (
           <div style={{ 
@@ -293,7 +454,7 @@ export const EditorWorkspace: React.FC = () => {
 
       {/* Editor / Diff / Preview View */}
       <div style={{ flex: 1, overflow: "hidden", display: "flex", flexDirection: "column" }}>
-        {!isPreviewMode && !diffModified && (
+        {!isPreviewMode && !diffModified && !showHistory && !showLinks && (
              <LayoutToolbar 
                 onInsert={handleInsertText} 
                 onInsertStatBlock={() => setShowStatBlockInserter(true)}
@@ -301,7 +462,29 @@ export const EditorWorkspace: React.FC = () => {
         )}
         
         <div style={{ flex: 1, overflow: "hidden" }}>
-             {diffModified && diffOriginal ? (
+             {showHistory && projectRoot && activePath ? (
+                <FileHistoryPanel
+                   projectRoot={projectRoot}
+                   path={activePath}
+                   onRestored={() => {
+                     setShowHistory(false);
+                     handleReloadFromDisk();
+                   }}
+                   onClose={() => setShowHistory(false)}
+                />
+             ) : showLinks && projectRoot && activePath ? (
+                <BacklinksPanel
+                   projectRoot={projectRoot}
+                   path={activePath}
+                   onClose={() => setShowLinks(false)}
+                />
+             ) : diffModified && mergeConflicts ? (
+                <MergeConflicts
+                   conflicts={mergeConflicts}
+                   onResolve={(resolutions) => mergeAiEdit(resolutions)}
+                   onCancel={() => setMergeConflicts(null)}
+                />
+             ) : diffModified && diffOriginal ? (
                 <DiffViewer original={diffOriginal} modified={diffModified} />
              ) : isPreviewMode ? (
                 <MarkdownPreview content={value} />
@@ -0,0 +1,167 @@
+import React, { useEffect, useState } from "react";
+import { DiffViewer } from "../diff/DiffViewer";
+import {
+  diffFileVersions,
+  FileSnapshot,
+  FileVersionDiff,
+  listFileVersions,
+  restoreFileVersion,
+  SnapshotSource,
+} from "../../../lib/api/history";
+import { vars } from "../../theme/tokens.css";
+
+export interface FileHistoryPanelProps {
+  projectRoot: string;
+  path: string;
+  // Called after a version was written back to disk
+  onRestored: () => void;
+  onClose: () => void;
+}
+
+const SOURCE_LABELS: Record<SnapshotSource, string> = {
+  external: "Changed outside the app",
+  save: "Saved",
+  ai_edit: "AI edit",
+  undo: "Undo",
+  redo: "Redo",
+  restore: "Restored",
+  replace: "Find and replace",
+  rename: "Links updated after a rename",
+};
+
+function formatTime(seconds: number): string {
+  return new Date(seconds * 1000).toLocaleString();
+}
+
+// Lists saved versions of a file and shows each one as a diff against the
+// file on disk, with a button to restore it.
+export const FileHistoryPanel: React.FC<FileHistoryPanelProps> = ({ projectRoot, path, onRestored, onClose }) => {
+  const [versions, setVersions] = useState<FileSnapshot[]>([]);
+  const [selectedId, setSelectedId] = useState<number | null>(null);
+  const [diff, setDiff] = useState<FileVersionDiff | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [isRestoring, setIsRestoring] = useState(false);
+
+  useEffect(() => {
+    let cancelled = false;
+    listFileVersions(projectRoot, path)
+      .then((list) => {
+        if (cancelled) return;
+        setVersions(list);
+        setSelectedId(list.length > 0 ? list[0].id : null);
+      })
+      .catch((err) => {
+        console.error("Failed to load file history", err);
+        if (!cancelled) setError("Failed to load file history.");
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [projectRoot, path]);
+
+  useEffect(() => {
+    if (selectedId === null) {
+      setDiff(null);
+      return;
+    }
+    let cancelled = false;
+    diffFileVersions(projectRoot, path, selectedId)
+      .then((result) => {
+        if (!cancelled) setDiff(result);
+      })
+      .catch((err) => {
+        console.error("Failed to diff file version", err);
+        if (!cancelled) setError("Failed to load this version.");
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [projectRoot, path, selectedId]);
+
+  const handleRestore = async () => {
+    if (selectedId === null) return;
+    setIsRestoring(true);
+    setError(null);
+    try {
+      await restoreFileVersion(projectRoot, path, selectedId);
+      onRestored();
+    } catch (err) {
+      console.error("Failed to restore file version", err);
+      setError(String(err));
+    } finally {
+      setIsRestoring(false);
+    }
+  };
+
+  const unchanged = diff !== null && diff.insertions === 0 && diff.deletions === 0;
+
+  return (
+    <div style={{ height: "100%", display: "flex" }}>
+      <div style={{
+        width: 240,
+        overflowY: "auto",
+        borderRight: `1px solid ${vars.color.border.subtle}`,
+        backgroundColor: vars.color.background.panel
+      }}>
+        {versions.length === 0 && (
+          <div style={{ padding: 12, fontSize: 12, color: vars.color.text.muted }}>
+            No saved versions yet. A version is recorded every time the file is saved or edited by the AI.
+          </div>
+        )}
+        {versions.map((version) => (
+          <div
+            key={version.id}
+            onClick={() => setSelectedId(version.id)}
+            style={{
+              padding: "8px 12px",
+              fontSize: 12,
+              cursor: "pointer",
+              borderBottom: `1px solid ${vars.color.border.subtle}`,
+              backgroundColor: version.id === selectedId ? vars.color.background.panelRaised : "transparent"
+            }}
+          >
+            <div style={{ color: vars.color.text.primary }}>{formatTime(version.created_at)}</div>
+            <div style={{ color: vars.color.text.muted }}>
+              {SOURCE_LABELS[version.source]}
+              {version.label ? ` · ${version.label}` : ""}
+            </div>
+          </div>
+        ))}
+      </div>
+
+      <div style={{ flex: 1, display: "flex", flexDirection: "column", minWidth: 0 }}>
+        <div style={{
+          padding: 8,
+          fontSize: 12,
+          display: "flex",
+          justifyContent: "space-between",
+          alignItems: "center",
+          borderBottom: `1px solid ${vars.color.border.subtle}`
+        }}>
+          <span style={{ color: error ? vars.color.state.danger : vars.color.text.secondary }}>
+            {error
+              ? error
+              : unchanged
+                ? "Same as the current file"
+                : diff
+                  ? `Selected version (left) against the current file (right): +${diff.insertions} −${diff.deletions} lines`
+                  : ""}
+          </span>
+          <div style={{ display: "flex", gap: 8 }}>
+            <button onClick={onClose} style={{ padding: "4px 12px", cursor: "pointer" }}>Close</button>
+            <button
+              onClick={handleRestore}
+              disabled={selectedId === null || unchanged || isRestoring}
+              style={{ padding: "4px 12px", fontWeight: "bold", cursor: "pointer" }}
+            >
+              {isRestoring ? "Restoring..." : "Restore this version"}
+            </button>
+          </div>
+        </div>
+        <div style={{ flex: 1, overflow: "hidden" }}>
+          {diff && <DiffViewer original={diff.original} modified={diff.modified} />}
+        </div>
+      </div>
+    </div>
+  );
+};
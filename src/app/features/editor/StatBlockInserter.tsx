@@ -22,7 +22,7 @@ export const StatBlockInserter: React.FC<StatBlockInserterProps> = ({ onInsert,
     const loadFiles = async () => {
         try {
             const statBlocksDir = await join(projectRoot, "StatBlocks");
-            const list = await listFilesInDir(statBlocksDir);
+            const list = await listFilesInDir(projectRoot, statBlocksDir);
             setFiles(list.filter(f => f.endsWith(".md")));
         } catch (e) {
             console.warn("No StatBlocks folder found or empty.");
@@ -39,7 +39,7 @@ export const StatBlockInserter: React.FC<StatBlockInserterProps> = ({ onInsert,
       const loadPreview = async () => {
           try {
               const path = await join(projectRoot, "StatBlocks", selectedFile);
-              const content = await readFile(path);
+              const content = await readFile(projectRoot, path);
               setPreview(content);
           } catch (e) {
               console.error(e);
@@ -84,6 +84,11 @@ export const ExportDialog: React.FC = () => {
                 setIsExporting(false);
                 return;
             }
+            if (!projectRoot) {
+                alert("Open a project to export its files.");
+                setIsExporting(false);
+                return;
+            }
             
             setStatusMessage(`Preparing to load ${selectedFiles.length} files...`);
             
@@ -94,7 +99,7 @@ export const ExportDialog: React.FC = () => {
             
             const contents = await Promise.all(selectedFiles.map(async (path) => {
                 const fullPath = projectRoot ? `${projectRoot}/${path}` : path;
-                const fileContent = await readFile(`${projectRoot}/${path}`);
+                const fileContent = await readFile(projectRoot, path);
                 
                 loadedCount++;
                 const percentage = Math.round((loadedCount / total) * 80); // 0-80% for loading
@@ -0,0 +1,105 @@
+import React, { useState } from "react";
+import { useAtomValue, useSetAtom } from "jotai";
+import { projectRootAtom } from "../../state/atoms/projectAtoms";
+import { workspaceAtoms } from "../../state/atoms/workspaceAtoms";
+import { BrokenLink, checkLinks, LinkReport } from "../../../lib/api/links";
+import { vars } from "../../theme/tokens.css";
+
+// Group broken links by file, keeping the backend's path order.
+function groupByFile(broken: BrokenLink[]): Array<[string, BrokenLink[]]> {
+  const groups = new Map<string, BrokenLink[]>();
+  for (const link of broken) {
+    const list = groups.get(link.path) ?? [];
+    list.push(link);
+    groups.set(link.path, list);
+  }
+  return Array.from(groups.entries());
+}
+
+function describe(link: BrokenLink): string {
+  const written = link.kind === "wiki" ? `[[${link.link}]]` : `(${link.link})`;
+  return link.problem === "missing_heading" ? `${written}: no such heading` : `${written}: no such file`;
+}
+
+// Reports wiki links, markdown links, images and heading anchors that point
+// nowhere, with likely fixes.
+export const LinkCheckerPanel: React.FC = () => {
+  const projectRoot = useAtomValue(projectRootAtom);
+  const openFile = useSetAtom(workspaceAtoms.openFileTabAtom);
+  const [report, setReport] = useState<LinkReport | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [isChecking, setIsChecking] = useState(false);
+
+  const runCheck = async () => {
+    if (!projectRoot) return;
+    setIsChecking(true);
+    setError(null);
+    try {
+      setReport(await checkLinks(projectRoot));
+    } catch (err) {
+      console.error("Link check failed", err);
+      setError(String(err));
+    } finally {
+      setIsChecking(false);
+    }
+  };
+
+  return (
+    <div style={{ padding: 16, height: "100%", overflowY: "auto", color: vars.color.text.primary }}>
+      <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center" }}>
+        <h2 style={{ marginTop: 0 }}>Check Links</h2>
+        <button onClick={runCheck} disabled={!projectRoot || isChecking} style={{ padding: "4px 12px", cursor: "pointer" }}>
+          {isChecking ? "Checking..." : report ? "Check again" : "Check links"}
+        </button>
+      </div>
+
+      {!projectRoot && <div>Open a project to check its links.</div>}
+      {error && <div style={{ color: vars.color.state.danger, marginBottom: 12, whiteSpace: "pre-wrap" }}>{error}</div>}
+
+      {report && (
+        <div
+          style={{
+            fontSize: 12,
+            marginBottom: 12,
+            color: report.broken.length === 0 ? vars.color.state.success : vars.color.text.muted
+          }}
+        >
+          {report.broken.length === 0 ? "No broken links" : `${report.broken.length} broken links`} in{" "}
+          {report.links_checked} links across {report.files_checked} files
+        </div>
+      )}
+
+      {report && groupByFile(report.broken).map(([path, links]) => (
+        <div key={path} style={{ marginBottom: 16 }}>
+          <div
+            onClick={() => openFile(path)}
+            style={{ fontWeight: 600, cursor: "pointer", color: vars.color.accent.primary, marginBottom: 4 }}
+          >
+            {path} <span style={{ color: vars.color.text.muted, fontWeight: 400 }}>({links.length})</span>
+          </div>
+          {links.map((link, i) => (
+            <div
+              key={i}
+              onClick={() => openFile(path)}
+              style={{ display: "flex", gap: 8, padding: "2px 8px", fontSize: 12, cursor: "pointer" }}
+            >
+              <span style={{ color: vars.color.text.muted, minWidth: 56, textAlign: "right", fontFamily: "monospace" }}>
+                {link.line}:{link.column}
+              </span>
+              <div style={{ minWidth: 0 }}>
+                <div style={{ fontFamily: "monospace", color: vars.color.state.danger, wordBreak: "break-all" }}>
+                  {describe(link)}
+                </div>
+                {link.suggestions.length > 0 && (
+                  <div style={{ color: vars.color.text.muted }}>
+                    Did you mean {link.suggestions.map((s) => <code key={s} style={{ marginRight: 6 }}>{s}</code>)}
+                  </div>
+                )}
+              </div>
+            </div>
+          ))}
+        </div>
+      ))}
+    </div>
+  );
+};
@@ -0,0 +1,77 @@
+import React, { useState } from "react";
+import { save } from "@tauri-apps/api/dialog";
+import { writeTextFile } from "@tauri-apps/api/fs";
+import { EdgeKind, exportGraph, GraphFormat } from "../../../lib/api/graph";
+
+const FORMATS: Array<{ format: GraphFormat; label: string; extension: string }> = [
+  { format: "graphml", label: "GraphML (yEd, Gephi)", extension: "graphml" },
+  { format: "dot", label: "Graphviz DOT", extension: "dot" },
+  { format: "canvas", label: "Obsidian Canvas", extension: "canvas" }
+];
+
+const EDGE_KINDS: Array<{ kind: EdgeKind; label: string }> = [
+  { kind: "link", label: "Links" },
+  { kind: "tag", label: "Tags" },
+  { kind: "co_occurrence", label: "Tags used together" },
+  { kind: "defines", label: "Entities" }
+];
+
+function splitList(value: string): string[] {
+  return value.split(",").map((item) => item.trim()).filter(Boolean);
+}
+
+// Saves the lore map graph for layout in external tools, optionally limited
+// to some folders, tags and kinds of connection.
+export const GraphExportForm: React.FC<{ projectRoot: string }> = ({ projectRoot }) => {
+  const [format, setFormat] = useState<GraphFormat>("graphml");
+  const [folders, setFolders] = useState("");
+  const [tags, setTags] = useState("");
+  const [edgeKinds, setEdgeKinds] = useState<EdgeKind[]>(EDGE_KINDS.map((e) => e.kind));
+  const [status, setStatus] = useState<string | null>(null);
+
+  const toggleKind = (kind: EdgeKind) => {
+    setEdgeKinds(edgeKinds.includes(kind) ? edgeKinds.filter((k) => k !== kind) : [...edgeKinds, kind]);
+  };
+
+  const handleExport = async () => {
+    setStatus(null);
+    const { extension } = FORMATS.find((f) => f.format === format)!;
+    try {
+      const filePath = await save({ filters: [{ name: format, extensions: [extension] }] });
+      if (!filePath) return;
+      const contents = await exportGraph(projectRoot, format, {
+        folders: splitList(folders),
+        tags: splitList(tags),
+        edge_kinds: edgeKinds
+      });
+      await writeTextFile(filePath, contents);
+      setStatus(`Saved ${filePath}`);
+    } catch (err) {
+      console.error("Graph export failed", err);
+      setStatus(String(err));
+    }
+  };
+
+  const inputStyle = { width: "100%", boxSizing: "border-box" as const, marginBottom: 4, fontSize: "11px" };
+
+  return (
+    <div style={{ marginTop: 8, fontSize: "11px" }}>
+      <select value={format} onChange={(e) => setFormat(e.target.value as GraphFormat)} style={inputStyle}>
+        {FORMATS.map((f) => (
+          <option key={f.format} value={f.format}>{f.label}</option>
+        ))}
+      </select>
+      <input placeholder="Folders, e.g. lore, npcs" value={folders} onChange={(e) => setFolders(e.target.value)} style={inputStyle} />
+      <input placeholder="Tags, e.g. undead, faction" value={tags} onChange={(e) => setTags(e.target.value)} style={inputStyle} />
+      <div style={{ display: "flex", flexWrap: "wrap", gap: "2px 8px", marginBottom: 4 }}>
+        {EDGE_KINDS.map(({ kind, label }) => (
+          <label key={kind}>
+            <input type="checkbox" checked={edgeKinds.includes(kind)} onChange={() => toggleKind(kind)} /> {label}
+          </label>
+        ))}
+      </div>
+      <button onClick={handleExport} disabled={edgeKinds.length === 0} style={{ cursor: "pointer" }}>Export…</button>
+      {status && <div style={{ marginTop: 4, opacity: 0.8, wordBreak: "break-all" }}>{status}</div>}
+    </div>
+  );
+};
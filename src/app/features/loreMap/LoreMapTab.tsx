@@ -2,10 +2,11 @@ import React, { useEffect, useState, useRef, useCallback } from "react";
 import { useAtomValue } from "jotai";
 import ForceGraph2D, { ForceGraphMethods } from "react-force-graph-2d";
 import { projectRootAtom } from "../../state/atoms/projectAtoms";
-import { buildLoreGraph, GraphData } from "./graphBuilder";
+import { getLoreGraph, GraphNode, LoreGraph } from "../../../lib/api/graph";
 import { workspaceAtoms } from "../../state/atoms/workspaceAtoms";
 import { useSetAtom } from "jotai";
 import { vars } from "../../theme/tokens.css";
+import { GraphExportForm } from "./GraphExportForm";
 
 // Need to dynamically style the graph container to fit the tab area
 const containerStyle = {
@@ -32,7 +33,10 @@ export const LoreMapTab: React.FC = () => {
   const projectRoot = useAtomValue(projectRootAtom);
   const openFile = useSetAtom(workspaceAtoms.openFileTabAtom);
   
-  const [graphData, setGraphData] = useState<GraphData>({ nodes: [], links: [] });
+  const [graphData, setGraphData] = useState<LoreGraph>({ nodes: [], links: [] });
+  const [hubs, setHubs] = useState<GraphNode[]>([]);
+  const [orphanCount, setOrphanCount] = useState(0);
+  const [showExport, setShowExport] = useState(false);
   const [loading, setLoading] = useState(true);
   const [dimensions, setDimensions] = useState({ width: 800, height: 600 });
   const containerRef = useRef<HTMLDivElement>(null);
@@ -67,9 +71,12 @@ export const LoreMapTab: React.FC = () => {
         if (!projectRoot) return;
         setLoading(true);
         try {
-            const data = await buildLoreGraph(projectRoot);
+            // One call, so the backend builds the graph only once
+            const { nodes, links, hubs: topHubs, orphans } = await getLoreGraph(projectRoot, 5);
             if (mounted) {
-                setGraphData(data);
+                setGraphData({ nodes, links });
+                setHubs(topHubs);
+                setOrphanCount(orphans.length);
             }
         } catch (err) {
             console.error("Failed to load lore graph:", err);
@@ -83,9 +90,9 @@ export const LoreMapTab: React.FC = () => {
   }, [projectRoot]);
 
   const handleNodeClick = useCallback((node: any) => {
-      // Don't try to open tag nodes
-      if (node.id && !node.id.startsWith("tag:")) {
-          openFile(node.id);
+      // Files and entities open the file they are in; tags have none
+      if (node.path) {
+          openFile(node.path);
       }
   }, [openFile]);
 
@@ -113,6 +120,33 @@ export const LoreMapTab: React.FC = () => {
                 <br/>
                 <span style={{ color: "#888" }}>Tags are shown as hubs.</span>
             </p>
+            {hubs.length > 0 && (
+                <div style={{ marginTop: 8, fontSize: "11px" }}>
+                    <div style={{ opacity: 0.6 }}>Most connected</div>
+                    {hubs.map((hub) => (
+                        <div
+                            key={hub.id}
+                            onClick={() => hub.path && openFile(hub.path)}
+                            style={{ cursor: hub.path ? "pointer" : "default" }}
+                        >
+                            {hub.name} <span style={{ opacity: 0.6 }}>({hub.degree})</span>
+                        </div>
+                    ))}
+                </div>
+            )}
+            {orphanCount > 0 && (
+                <p style={{ margin: "8px 0 0 0", fontSize: "11px", opacity: 0.6 }}>
+                    {orphanCount} files are not connected to anything.
+                </p>
+            )}
+            {projectRoot && (
+                <div style={{ marginTop: 8, fontSize: "11px" }}>
+                    <span onClick={() => setShowExport(!showExport)} style={{ cursor: "pointer", textDecoration: "underline" }}>
+                        {showExport ? "Hide export" : "Export graph…"}
+                    </span>
+                    {showExport && <GraphExportForm projectRoot={projectRoot} />}
+                </div>
+            )}
         </div>
 
         {!loading && (
@@ -124,6 +158,7 @@ export const LoreMapTab: React.FC = () => {
                 nodeLabel="name"
                 nodeColor={(node: any) => {
                     if (node.group === "_tags") return "#ff00ff"; // Bright color for tags
+                    if (node.group === "_entities") return "#00e5ff";
                     
                     // Simple color generation based on group
                     const group = node.group || "root";
@@ -135,8 +170,8 @@ export const LoreMapTab: React.FC = () => {
                     return "#" + "00000".substring(0, 6 - c.length) + c;
                 }}
                 nodeRelSize={6}
-                linkColor={(link: any) => link.type === "tag" ? "rgba(255, 0, 255, 0.1)" : "rgba(255,255,255,0.2)"}
-                linkLineDash={(link: any) => link.type === "tag" ? [5, 5] : null}
+                linkColor={(link: any) => link.type === "link" ? "rgba(255,255,255,0.2)" : "rgba(255, 0, 255, 0.1)"}
+                linkLineDash={(link: any) => link.type === "link" ? null : [5, 5]}
                 onNodeClick={handleNodeClick}
                 cooldownTicks={100}
             />
@@ -154,6 +154,7 @@ export const ProceduralGeneratorTab: React.FC = () => {
       
       // Process each selected item
       for (const item of selectedContextItems) {
+        if (!projectRoot) break;
         try {
             // Check if it's a specific header (path::lineNumber) or whole file (path)
             // Split only on the last instance of :: in case path has :: (rare but possible on some OS?)
@@ -162,7 +163,7 @@ export const ProceduralGeneratorTab: React.FC = () => {
             const path = parts[0];
             const lineStr = parts.length > 1 ? parts[1] : null;
             
-            const content = await readFile(path);
+            const content = await readFile(projectRoot, path);
             const filename = path.split(/[/\\]/).pop();
             
             let finalContent = content;
@@ -222,7 +223,7 @@ export const ProceduralGeneratorTab: React.FC = () => {
     if (!projectRoot || !saveFileName || !generatedContent) return;
     try {
         const fullPath = await join(projectRoot, saveFileName);
-        await writeFile(fullPath, generatedContent);
+        await writeFile(projectRoot, fullPath, generatedContent);
         alert(`Saved to ${saveFileName}`);
     } catch (e) {
         console.error("Save failed:", e);
@@ -80,9 +80,9 @@ export const ContextSelector: React.FC<ContextSelectorProps> = ({ onSelectionCha
       } else {
           newExpanded.add(path);
           // Fetch headers if not present
-          if (!fileHeaders[path]) {
+          if (!fileHeaders[path] && projectRoot) {
               try {
-                  const content = await readFile(path);
+                  const content = await readFile(projectRoot, path);
                   const headers = parseMarkdownHeaders(content);
                   setFileHeaders(prev => ({ ...prev, [path]: headers }));
               } catch (e) {
@@ -1,10 +1,279 @@
-import React from "react";
+import React, { useState } from "react";
+import { useAtomValue, useSetAtom } from "jotai";
+import { projectRootAtom } from "../../state/atoms/projectAtoms";
+import { workspaceAtoms } from "../../state/atoms/workspaceAtoms";
+import {
+  applyReplace,
+  previewReplace,
+  ReplacePreview,
+  searchProject,
+  SearchMatch,
+  SearchResults
+} from "../../../lib/api/search";
+import { vars } from "../../theme/tokens.css";
+
+const toggleStyle = (active: boolean) => ({
+  padding: "4px 8px",
+  fontSize: 12,
+  fontFamily: "monospace",
+  borderRadius: 4,
+  cursor: "pointer",
+  border: `1px solid ${vars.color.border.subtle}`,
+  background: active ? vars.color.accent.primary : "transparent",
+  color: active ? vars.color.text.inverse : vars.color.text.secondary
+});
+
+// Group matches by file, keeping the backend's path order.
+function groupByFile(matches: SearchMatch[]): Array<[string, SearchMatch[]]> {
+  const groups = new Map<string, SearchMatch[]>();
+  for (const match of matches) {
+    const list = groups.get(match.path) ?? [];
+    list.push(match);
+    groups.set(match.path, list);
+  }
+  return Array.from(groups.entries());
+}
+
+const MatchLine: React.FC<{ match: SearchMatch }> = ({ match }) => {
+  const chars = Array.from(match.line_text);
+  const start = match.column - 1;
+  const end = start + match.length;
+  return (
+    <span>
+      {chars.slice(0, start).join("")}
+      <mark style={{ backgroundColor: vars.color.accent.primary, color: vars.color.text.inverse }}>
+        {chars.slice(start, end).join("")}
+      </mark>
+      {chars.slice(end).join("")}
+    </span>
+  );
+};
 
 export const ProjectSearchPanel: React.FC = () => {
+  const projectRoot = useAtomValue(projectRootAtom);
+  const openFile = useSetAtom(workspaceAtoms.openFileTabAtom);
+  const [query, setQuery] = useState("");
+  const [regex, setRegex] = useState(false);
+  const [caseSensitive, setCaseSensitive] = useState(false);
+  const [wholeWord, setWholeWord] = useState(false);
+  const [results, setResults] = useState<SearchResults | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [isSearching, setIsSearching] = useState(false);
+  // Replace mode: preview every change first, then apply the checked ones
+  const [showReplace, setShowReplace] = useState(false);
+  const [replacement, setReplacement] = useState("");
+  const [preview, setPreview] = useState<ReplacePreview | null>(null);
+  const [excluded, setExcluded] = useState<Set<string>>(new Set());
+  const [status, setStatus] = useState<string | null>(null);
+
+  const options = () => ({ query, regex, case_sensitive: caseSensitive, whole_word: wholeWord });
+  const changeKey = (path: string, index: number) => `${path}:${index}`;
+
+  const toggleChange = (key: string) => {
+    setExcluded(prev => {
+      const next = new Set(prev);
+      if (next.has(key)) next.delete(key); else next.add(key);
+      return next;
+    });
+  };
+
+  const runPreview = async () => {
+    if (!projectRoot || !query) return;
+    setIsSearching(true);
+    setError(null);
+    setStatus(null);
+    try {
+      setPreview(await previewReplace(projectRoot, { ...options(), replacement }));
+      setExcluded(new Set());
+    } catch (err) {
+      console.error("Replace preview failed", err);
+      setError(String(err));
+      setPreview(null);
+    } finally {
+      setIsSearching(false);
+    }
+  };
+
+  const runReplace = async () => {
+    if (!projectRoot || !preview) return;
+    const selections = preview.files
+      .map(file => ({
+        path: file.path,
+        version: file.version,
+        indices: file.changes.filter(c => !excluded.has(changeKey(file.path, c.index))).map(c => c.index)
+      }))
+      .filter(selection => selection.indices.length > 0);
+    const count = selections.reduce((sum, selection) => sum + selection.indices.length, 0);
+    setIsSearching(true);
+    setError(null);
+    try {
+      await applyReplace(projectRoot, { ...options(), replacement }, selections);
+      setStatus(`Replaced ${count} occurrences in ${selections.length} files. Use Undo in the assistant panel to revert.`);
+      setPreview(null);
+    } catch (err) {
+      console.error("Replace failed", err);
+      setError(`${String(err)} Preview again to pick up the latest file contents.`);
+    } finally {
+      setIsSearching(false);
+    }
+  };
+
+  const runSearch = async () => {
+    if (!projectRoot || !query) return;
+    setIsSearching(true);
+    setError(null);
+    try {
+      setResults(await searchProject({ project_root: projectRoot, ...options() }));
+    } catch (err) {
+      console.error("Project search failed", err);
+      setError(String(err));
+      setResults(null);
+    } finally {
+      setIsSearching(false);
+    }
+  };
+
   return (
-    <div style={{ padding: 16 }}>
-      <h2>Project Search</h2>
-      <p>Search across all markdown files and AI-indexed content (RAG).</p>
+    <div style={{ padding: 16, height: "100%", overflowY: "auto", color: vars.color.text.primary }}>
+      <h2 style={{ marginTop: 0 }}>Project Search</h2>
+      <div style={{ display: "flex", gap: 8, marginBottom: 12 }}>
+        <input
+          autoFocus
+          value={query}
+          onChange={(e) => setQuery(e.target.value)}
+          onKeyDown={(e) => { if (e.key === "Enter") runSearch(); }}
+          placeholder={projectRoot ? "Search all project files" : "Open a project to search"}
+          disabled={!projectRoot}
+          style={{
+            flex: 1,
+            padding: 8,
+            borderRadius: 4,
+            border: `1px solid ${vars.color.border.subtle}`,
+            backgroundColor: vars.color.background.base,
+            color: vars.color.text.primary
+          }}
+        />
+        <button title="Match case" onClick={() => setCaseSensitive(!caseSensitive)} style={toggleStyle(caseSensitive)}>Aa</button>
+        <button title="Whole word" onClick={() => setWholeWord(!wholeWord)} style={toggleStyle(wholeWord)}>ab</button>
+        <button title="Regular expression" onClick={() => setRegex(!regex)} style={toggleStyle(regex)}>.*</button>
+        <button title="Replace" onClick={() => { setShowReplace(!showReplace); setPreview(null); }} style={toggleStyle(showReplace)}>⇄</button>
+        <button
+          onClick={runSearch}
+          disabled={!projectRoot || !query || isSearching}
+          style={{
+            padding: "8px 16px",
+            backgroundColor: vars.color.accent.primary,
+            color: vars.color.text.inverse,
+            border: "none",
+            borderRadius: 4,
+            cursor: "pointer"
+          }}
+        >
+          {isSearching ? "Searching..." : "Search"}
+        </button>
+      </div>
+
+      {showReplace && (
+        <div style={{ display: "flex", gap: 8, marginBottom: 12 }}>
+          <input
+            value={replacement}
+            onChange={(e) => setReplacement(e.target.value)}
+            onKeyDown={(e) => { if (e.key === "Enter") runPreview(); }}
+            placeholder={regex ? "Replace with ($1 inserts the first group)" : "Replace with"}
+            style={{
+              flex: 1,
+              padding: 8,
+              borderRadius: 4,
+              border: `1px solid ${vars.color.border.subtle}`,
+              backgroundColor: vars.color.background.base,
+              color: vars.color.text.primary
+            }}
+          />
+          <button onClick={runPreview} disabled={!projectRoot || !query || isSearching} style={{ padding: "8px 16px", cursor: "pointer" }}>
+            Preview
+          </button>
+          <button
+            onClick={runReplace}
+            disabled={!preview || preview.total_changes === excluded.size || isSearching}
+            style={{ padding: "8px 16px", fontWeight: "bold", cursor: "pointer" }}
+          >
+            Replace selected
+          </button>
+        </div>
+      )}
+
+      {error && <div style={{ color: vars.color.state.danger, marginBottom: 12 }}>{error}</div>}
+      {status && <div style={{ color: vars.color.state.success, marginBottom: 12 }}>{status}</div>}
+
+      {showReplace && preview && (
+        <div>
+          <div style={{ fontSize: 12, color: vars.color.text.muted, marginBottom: 12 }}>
+            {preview.total_changes - excluded.size} of {preview.total_changes} changes selected in {preview.files.length} files
+            {preview.truncated && " (preview capped; replace these first, then preview again)"}
+          </div>
+          {preview.files.map(file => (
+            <div key={file.path} style={{ marginBottom: 16 }}>
+              <div style={{ fontWeight: 600, marginBottom: 4 }}>{file.path}</div>
+              {file.changes.map(change => {
+                const key = changeKey(file.path, change.index);
+                return (
+                  <label
+                    key={key}
+                    style={{ display: "flex", gap: 8, padding: "2px 8px", fontSize: 12, fontFamily: "monospace", cursor: "pointer" }}
+                  >
+                    <input type="checkbox" checked={!excluded.has(key)} onChange={() => toggleChange(key)} />
+                    <span style={{ color: vars.color.text.muted, minWidth: 40, textAlign: "right" }}>{change.line}</span>
+                    <span style={{ whiteSpace: "pre-wrap" }}>
+                      <span style={{ textDecoration: "line-through", color: vars.color.state.danger }}>{change.line_text}</span>
+                      {"\n"}
+                      <span style={{ color: vars.color.state.success }}>{change.new_line_text}</span>
+                    </span>
+                  </label>
+                );
+              })}
+            </div>
+          ))}
+        </div>
+      )}
+
+      {!showReplace && results && (
+        <div style={{ fontSize: 12, color: vars.color.text.muted, marginBottom: 12 }}>
+          {results.matches.length} matches in {results.files_searched} files searched
+          {results.truncated && " (results capped; refine the search to see more)"}
+        </div>
+      )}
+
+      {!showReplace && results && groupByFile(results.matches).map(([path, matches]) => (
+        <div key={path} style={{ marginBottom: 16 }}>
+          <div
+            onClick={() => openFile(path)}
+            style={{ fontWeight: 600, cursor: "pointer", color: vars.color.accent.primary, marginBottom: 4 }}
+          >
+            {path} <span style={{ color: vars.color.text.muted, fontWeight: 400 }}>({matches.length})</span>
+          </div>
+          {matches.map((match, i) => (
+            <div
+              key={i}
+              onClick={() => openFile(path)}
+              title={[...match.context_before, match.line_text, ...match.context_after].join("\n")}
+              style={{
+                display: "flex",
+                gap: 8,
+                padding: "2px 8px",
+                fontSize: 12,
+                fontFamily: "monospace",
+                cursor: "pointer",
+                whiteSpace: "pre",
+                overflow: "hidden",
+                textOverflow: "ellipsis"
+              }}
+            >
+              <span style={{ color: vars.color.text.muted, minWidth: 40, textAlign: "right" }}>{match.line}</span>
+              <MatchLine match={match} />
+            </div>
+          ))}
+        </div>
+      ))}
     </div>
   );
 };
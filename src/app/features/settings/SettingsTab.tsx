@@ -77,7 +77,8 @@ export const SettingsTab: React.FC = () => {
                 input_font_color: loaded.input_font_color,
                 foreground_panel_color: loaded.foreground_panel_color,
                 statblock_bg_color: loaded.statblock_bg_color || "#fdf1dc",
-                statblock_font_color: loaded.statblock_font_color || "#58180D"
+                statblock_font_color: loaded.statblock_font_color || "#58180D",
+                rerank_enabled: !!loaded.rerank_enabled
             });
         }
     } catch(e) {
@@ -185,6 +186,21 @@ export const SettingsTab: React.FC = () => {
             </select>
           </div>
 
+          {/* Reranking */}
+          <div style={{ marginBottom: 16 }}>
+            <label style={{ display: "flex", alignItems: "center", gap: 8, fontWeight: 500 }}>
+              <input
+                type="checkbox"
+                checked={settings.rerank_enabled}
+                onChange={(e) => setSettings(prev => ({ ...prev, rerank_enabled: e.target.checked }))}
+              />
+              Rerank retrieved context with the chat model
+            </label>
+            <div style={{ marginTop: 4, fontSize: 12, color: vars.color.text.muted }}>
+              Scores the top 30 search results in one extra AI call and keeps the most relevant. Slower, but more precise for rules questions.
+            </div>
+          </div>
+
           <button
             onClick={handleSaveSettings}
             style={{
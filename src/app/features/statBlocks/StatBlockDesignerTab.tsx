@@ -39,14 +39,14 @@ export const StatBlockDesignerTab: React.FC = () => {
         try {
             const templatesDir = await join(projectRoot, ".codex", "templates");
             // Ensure dir exists? Maybe not, just try to list
-            const files = await listFilesInDir(templatesDir);
+            const files = await listFilesInDir(projectRoot, templatesDir);
             
             const loadedTemplates: TableTemplate[] = [];
             
             for (const file of files) {
                 if (file.endsWith(".json")) {
                     try {
-                        const content = await readFile(await join(templatesDir, file));
+                        const content = await readFile(projectRoot, await join(templatesDir, file));
                         const parsed = JSON.parse(content);
                         if (parsed.id && parsed.fields) {
                             loadedTemplates.push(parsed);
@@ -127,7 +127,7 @@ export const StatBlockDesignerTab: React.FC = () => {
 
     try {
       const statBlocksDir = await join(projectRoot, "StatBlocks");
-      await createDirectory(statBlocksDir);
+      await createDirectory(projectRoot, statBlocksDir);
 
       // Try to find a good name field
       const nameVal = formData["name"] || "Untitled";
@@ -135,7 +135,7 @@ export const StatBlockDesignerTab: React.FC = () => {
       const fileName = `${safeName}.md`;
       const filePath = await join(statBlocksDir, fileName);
 
-      await writeFile(filePath, markdown);
+      await writeFile(projectRoot, filePath, markdown);
       alert(`Saved ${nameVal} to StatBlocks folder!`);
     } catch (error) {
       console.error("Failed to save:", error);
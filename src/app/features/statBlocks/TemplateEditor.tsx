@@ -52,10 +52,10 @@ export const TemplateEditor: React.FC<{ onClose: () => void }> = ({ onClose }) =
       };
 
       const templatesDir = await join(projectRoot, ".codex", "templates");
-      await createDirectory(templatesDir);
+      await createDirectory(projectRoot, templatesDir);
       
       const filePath = await join(templatesDir, `${templateId}.json`);
-      await writeFile(filePath, JSON.stringify(template, null, 2));
+      await writeFile(projectRoot, filePath, JSON.stringify(template, null, 2));
       
       alert("Template saved successfully!");
       onClose();
@@ -0,0 +1,120 @@
+import React, { useCallback, useEffect, useState } from "react";
+import { useAtomValue } from "jotai";
+import { useQueryClient } from "@tanstack/react-query";
+import { projectRootAtom } from "../../state/atoms/projectAtoms";
+import { listTrash, purgeTrash, restoreFromTrash, TrashEntry } from "../../../lib/api/trash";
+import { vars } from "../../theme/tokens.css";
+
+function formatSize(bytes: number): string {
+  if (bytes < 1024) return `${bytes} B`;
+  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KB`;
+  return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+}
+
+// Lists deleted files and folders with buttons to restore them or delete
+// them for good.
+export const TrashPanel: React.FC = () => {
+  const projectRoot = useAtomValue(projectRootAtom);
+  const queryClient = useQueryClient();
+  const [entries, setEntries] = useState<TrashEntry[]>([]);
+  const [error, setError] = useState<string | null>(null);
+  const [status, setStatus] = useState<string | null>(null);
+
+  const refresh = useCallback(async () => {
+    if (!projectRoot) return;
+    try {
+      setEntries(await listTrash(projectRoot));
+    } catch (err) {
+      console.error("Failed to list trash", err);
+      setError(String(err));
+    }
+  }, [projectRoot]);
+
+  useEffect(() => {
+    refresh();
+  }, [refresh]);
+
+  const run = async (action: () => Promise<string>) => {
+    setError(null);
+    setStatus(null);
+    try {
+      setStatus(await action());
+      queryClient.invalidateQueries({ queryKey: ["project-files", projectRoot] });
+    } catch (err) {
+      console.error("Trash action failed", err);
+      setError(String(err));
+    }
+    refresh();
+  };
+
+  const handleRestore = (entry: TrashEntry) => run(async () => {
+    try {
+      return `Restored ${await restoreFromTrash(projectRoot!, entry.id)}`;
+    } catch (err) {
+      // The original path is taken; offer another name
+      const destination = window.prompt(`${String(err)}\n\nRestore to:`, `${entry.original_path} (restored)`);
+      if (!destination) throw err;
+      return `Restored ${await restoreFromTrash(projectRoot!, entry.id, destination)}`;
+    }
+  });
+
+  const handlePurge = (entry: TrashEntry) => {
+    if (!window.confirm(`Permanently delete ${entry.original_path}? This cannot be undone.`)) return;
+    run(async () => {
+      await purgeTrash(projectRoot!, [entry.id]);
+      return `Permanently deleted ${entry.original_path}`;
+    });
+  };
+
+  const handleEmpty = () => {
+    if (!window.confirm(`Permanently delete all ${entries.length} items in the trash? This cannot be undone.`)) return;
+    run(async () => `Permanently deleted ${await purgeTrash(projectRoot!)} items`);
+  };
+
+  return (
+    <div style={{ padding: 16, height: "100%", overflowY: "auto", color: vars.color.text.primary }}>
+      <div style={{ display: "flex", justifyContent: "space-between", alignItems: "center" }}>
+        <h2 style={{ marginTop: 0 }}>Trash</h2>
+        <button onClick={handleEmpty} disabled={entries.length === 0} style={{ padding: "4px 12px", cursor: "pointer" }}>
+          Empty trash
+        </button>
+      </div>
+
+      {!projectRoot && <div>Open a project to see its trash.</div>}
+      {error && <div style={{ color: vars.color.state.danger, marginBottom: 12, whiteSpace: "pre-wrap" }}>{error}</div>}
+      {status && <div style={{ color: vars.color.state.success, marginBottom: 12 }}>{status}</div>}
+      {projectRoot && entries.length === 0 && (
+        <div style={{ fontSize: 12, color: vars.color.text.muted }}>The trash is empty.</div>
+      )}
+
+      {entries.map((entry) => (
+        <div
+          key={entry.id}
+          style={{
+            display: "flex",
+            alignItems: "center",
+            gap: 12,
+            padding: "8px 0",
+            borderBottom: `1px solid ${vars.color.border.subtle}`
+          }}
+        >
+          <span>{entry.is_dir ? "📁" : "📄"}</span>
+          <div style={{ flex: 1, minWidth: 0 }}>
+            <div style={{ wordBreak: "break-all" }}>{entry.original_path}</div>
+            <div style={{ fontSize: 12, color: vars.color.text.muted }}>
+              Deleted {new Date(entry.deleted_at * 1000).toLocaleString()} · {formatSize(entry.size)}
+              {entry.is_dir ? ` · ${entry.file_count} files` : ""}
+            </div>
+          </div>
+          <button onClick={() => handleRestore(entry)} style={{ padding: "4px 12px", cursor: "pointer" }}>Restore</button>
+          <button
+            onClick={() => handlePurge(entry)}
+            style={{ padding: "4px 12px", cursor: "pointer", color: vars.color.state.danger }}
+          >
+            Delete forever
+          </button>
+        </div>
+      ))}
+    </div>
+  );
+};
@@ -1,9 +1,9 @@
 import { useAtom } from "jotai";
 import { useQueryClient } from "@tanstack/react-query";
 import { open } from "@tauri-apps/api/dialog";
-import { join, basename } from "@tauri-apps/api/path";
+import { join } from "@tauri-apps/api/path";
 import { projectRootAtom } from "../state/atoms/projectAtoms";
-import { createDirectory, writeFile, copyFile } from "../../lib/api/files";
+import { createProjectRoot, writeFile, importFiles } from "../../lib/api/files";
 import { useAutoIndex } from "./useAutoIndex";
 
 export function useProjectActions() {
@@ -47,11 +47,11 @@ export function useProjectActions() {
       const newProjectPath = await join(parentDir, projectName);
       
       // Create directory
-      await createDirectory(newProjectPath);
+      await createProjectRoot(newProjectPath);
       
       // Create initial README
       const readmePath = await join(newProjectPath, "README.md");
-      await writeFile(readmePath, `# ${projectName}\n\nWelcome to your new CodexLotus project.`);
+      await writeFile(newProjectPath, readmePath, `# ${projectName}\n\nWelcome to your new CodexLotus project.`);
 
       setProjectRoot(newProjectPath);
       // Invalidate queries to ensure fresh state
@@ -66,29 +66,7 @@ export function useProjectActions() {
   const handleImportFiles = async () => {
     if (!projectRoot) return;
     try {
-      const selected = await open({
-        multiple: true,
-        title: "Import Files",
-        filters: [{
-          name: "Supported Files",
-          extensions: ["md", "markdown", "mdx", "png", "jpg", "jpeg", "gif", "webp", "svg"]
-        }]
-      });
-
-      if (!selected) return;
-      const files = Array.isArray(selected) ? selected : [selected];
-
-      let importedCount = 0;
-      for (const srcPath of files) {
-        const fileName = await basename(srcPath);
-        const destPath = await join(projectRoot, fileName);
-        
-        if (srcPath === destPath) continue;
-
-        await copyFile(srcPath, destPath);
-        importedCount++;
-      }
-
+      const importedCount = await importFiles(projectRoot);
       if (importedCount > 0) {
         queryClient.invalidateQueries({ queryKey: ["project-files"] });
         // Trigger re-indexing for newly imported markdown files
@@ -12,6 +12,7 @@ export interface AppSettings {
   input_font_color: string | null;
   statblock_bg_color: string | null;
   statblock_font_color: string | null;
+  rerank_enabled: boolean;
 }
 
 export const defaultSettings: AppSettings = {
@@ -26,6 +27,7 @@ export const defaultSettings: AppSettings = {
   input_font_color: null,
   statblock_bg_color: "#fdf1dc",
   statblock_font_color: "#58180D",
+  rerank_enabled: false,
 };
 
 export const settingsAtom = atom<AppSettings>(defaultSettings);
@@ -82,6 +82,34 @@ const openFileTabAtom = atom(null, (get, set, path: string) => {
     }
 });
 
+// Point editor tabs at a renamed file, or at the files under a renamed folder.
+const renameFileTabsAtom = atom(null, (get, set, from: string, to: string) => {
+    const state = get(baseStateAtom);
+    const renamed = (path: string) =>
+        path === from ? to : path.startsWith(`${from}/`) ? `${to}${path.slice(from.length)}` : path;
+
+    let activeTabId = state.activeTabId;
+    const tabs = state.tabs.map((tab) => {
+        if (tab.type !== "editor" || !tab.path || renamed(tab.path) === tab.path) return tab;
+        const path = renamed(tab.path);
+        const segments = path.split(/[/\\]/);
+        const id = `file-${path}`;
+        if (tab.id === state.activeTabId) activeTabId = id;
+        return { ...tab, id, path, title: segments[segments.length - 1] || path };
+    });
+
+    set(baseStateAtom, { tabs, activeTabId });
+});
+
+// Close the editor tabs of a deleted file, or of the files under a deleted folder.
+const closeFileTabsAtom = atom(null, (get, set, path: string) => {
+    const state = get(baseStateAtom);
+    const doomed = state.tabs.filter(
+        (t) => t.type === "editor" && t.path && (t.path === path || t.path.startsWith(`${path}/`))
+    );
+    doomed.forEach((tab) => set(closeTabAtom, tab.id));
+});
+
 const openSettingsTabAtom = atom(null, (get, set) => {
     const state = get(baseStateAtom);
     const existingTab = state.tabs.find((t) => t.type === "settings");
@@ -128,6 +156,8 @@ export const workspaceAtoms = {
   setActiveTabAtom,
   closeTabAtom,
   openFileTabAtom,
+  renameFileTabsAtom,
+  closeFileTabsAtom,
   openSettingsTabAtom,
   openToolTabAtom,
 };
@@ -10,22 +10,100 @@ export interface ChatRequest {
   prompt: string;
   project_root?: string;
   conversation?: ChatMessage[];
+  session_id?: number;
   provider?: string;
   model?: string;
 }
 
+export interface Citation {
+  id: string;
+  file_path: string | null;
+  heading: string | null;
+  start_line: number | null;
+  end_line: number | null;
+  verified: boolean;
+  problem: string | null;
+}
+
+export interface ManifestChunk {
+  id: string;
+  relative_path: string;
+  score: number;
+  source: "rag" | "mentioned_file";
+  estimated_tokens: number;
+}
+
+export interface MentionedFileStatus {
+  name: string;
+  loaded: boolean;
+  already_in_context: boolean;
+  problem: string | null;
+}
+
+export interface TokenEstimates {
+  instructions: number;
+  templates: number;
+  files: number;
+  summary: number;
+  history: number;
+  prompt: number;
+  total: number;
+}
+
+export interface ContextManifest {
+  provider: string;
+  model: string;
+  reranked: boolean;
+  chunks: ManifestChunk[];
+  mentioned_file: MentionedFileStatus | null;
+  templates: string[];
+  compacted_messages: number;
+  tokens: TokenEstimates;
+}
+
+export interface HunkResult {
+  index: number;
+  applied: boolean;
+  fuzzy: boolean;
+  error: string | null;
+  search_preview: string;
+}
+
+export interface ProposedEdit {
+  kind: "full_file" | "patch";
+  path: string;
+  absolute_path: string | null;
+  exists: boolean;
+  original_contents: string | null;
+  new_contents: string;
+  diff: string;
+  hunks: HunkResult[];
+  errors: string[];
+}
+
 export interface ChatResponse {
   content: string;
+  citations: Citation[];
+  manifest: ContextManifest | null;
+  edits: ProposedEdit[];
 }
 
 export interface FileEditRequest {
     path: string;
     contents: string;
     instruction: string;
+    /** "patch" asks for search/replace hunks instead of a whole-file rewrite. */
+    mode?: "full" | "patch";
+    /** Heading of the section to edit; `path#Heading` works too. */
+    section?: string;
 }
 
 export interface FileEditResponse {
     updated_contents: string;
+    /** The contents the AI edited; the base for a three-way merge. */
+    base_contents: string;
+    hunks: HunkResult[];
+    error: string | null;
 }
 
 export interface InitIndexRequest {
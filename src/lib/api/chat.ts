@@ -0,0 +1,55 @@
+import { call } from "./client";
+
+export interface ChatSession {
+  id: number;
+  title: string;
+  forked_from: number | null;
+  created_at: number;
+  updated_at: number;
+  message_count: number;
+}
+
+export interface StoredChatMessage {
+  id: number;
+  session_id: number;
+  role: string;
+  content: string;
+  created_at: number;
+}
+
+export interface ChatSearchHit {
+  session_id: number;
+  session_title: string;
+  message_id: number;
+  role: string;
+  snippet: string;
+  created_at: number;
+}
+
+export async function createChatSession(projectRoot: string, title?: string) {
+  return call<ChatSession>("create_chat_session", { projectRoot, title });
+}
+
+export async function listChatSessions(projectRoot: string) {
+  return call<ChatSession[]>("list_chat_sessions", { projectRoot });
+}
+
+export async function getChatSessionMessages(projectRoot: string, sessionId: number) {
+  return call<StoredChatMessage[]>("get_chat_session_messages", { projectRoot, sessionId });
+}
+
+export async function renameChatSession(projectRoot: string, sessionId: number, title: string) {
+  return call<ChatSession>("rename_chat_session", { projectRoot, sessionId, title });
+}
+
+export async function deleteChatSession(projectRoot: string, sessionId: number) {
+  return call<void>("delete_chat_session", { projectRoot, sessionId });
+}
+
+export async function forkChatSession(projectRoot: string, sessionId: number, messageId: number) {
+  return call<ChatSession>("fork_chat_session", { projectRoot, sessionId, messageId });
+}
+
+export async function searchChatSessions(projectRoot: string, query: string, limit?: number) {
+  return call<ChatSearchHit[]>("search_chat_sessions", { projectRoot, query, limit });
+}
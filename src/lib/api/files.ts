@@ -1,4 +1,6 @@
 import { call } from "./client";
+import type { HunkResult } from "./ai";
+import type { TransactionSummary } from "./history";
 
 export interface FileEntry {
   path: string;
@@ -8,24 +10,150 @@ export async function listMarkdownFiles(projectRoot: string) {
   return call<FileEntry[]>("list_markdown_files", { projectRoot });
 }
 
-export async function readFile(path: string) {
-  return call<string>("read_file", { path });
+// File commands are sandboxed to the project: `path` may be relative to
+// `projectRoot` or absolute, but must resolve inside it.
+export async function readFile(projectRoot: string, path: string) {
+  return call<string>("read_file", { projectRoot, path });
 }
 
-export async function writeFile(path: string, contents: String) {
-  return call<void>("write_file", { path, contents });
+export interface VersionedFile {
+  contents: string;
+  version: string;
 }
 
-export async function createDirectory(path: string) {
-  return call<void>("create_directory", { path });
+// Error returned by writeFile when the file changed on disk since `expectedVersion` was read.
+export interface FileConflictError {
+  kind: "conflict";
+  message: string;
+  path: string;
+  expected_version: string;
+  actual_version: string | null;
+}
+
+export function isFileConflict(err: unknown): err is FileConflictError {
+  return typeof err === "object" && err !== null && (err as { kind?: unknown }).kind === "conflict";
 }
 
-export async function copyFile(source: String, destination: String) {
-  return call<void>("copy_file", { source, destination });
+export async function readFileVersioned(projectRoot: string, path: string) {
+  return call<VersionedFile>("read_file_versioned", { projectRoot, path });
 }
 
-export async function listFilesInDir(path: string) {
-  return call<string[]>("list_files_in_dir", { path });
+// Returns the new version. With `expectedVersion`, fails with a FileConflictError
+// instead of overwriting changes made by other programs.
+export async function writeFile(projectRoot: string, path: string, contents: String, expectedVersion?: string) {
+  return call<string>("write_file", { projectRoot, path, contents, expectedVersion });
 }
 
+export async function createDirectory(projectRoot: string, path: string) {
+  return call<void>("create_directory", { projectRoot, path });
+}
+
+// Creates the folder of a new project, before there is a project to sandbox to.
+export async function createProjectRoot(path: string) {
+  return call<void>("create_project_root", { path });
+}
 
+// Both paths must be inside the project; outside files come in through importFiles.
+export async function copyFile(projectRoot: string, source: String, destination: String) {
+  return call<void>("copy_file", { projectRoot, source, destination });
+}
+
+// Opens the native file picker and copies the chosen files into the project
+// root. Resolves to the number of files imported, 0 if the picker was cancelled.
+export async function importFiles(projectRoot: string) {
+  return call<number>("import_files", { projectRoot });
+}
+
+export async function listFilesInDir(projectRoot: string, path: string) {
+  return call<string[]>("list_files_in_dir", { projectRoot, path });
+}
+
+export interface RenameResponse {
+  from: string;
+  to: string;
+  // Links rewritten to point at the new location
+  updated_links: number;
+  transaction: TransactionSummary;
+}
+
+// Moves a file or folder and rewrites every wiki and markdown link to it.
+// Undo with undoEditTransaction.
+export async function movePath(projectRoot: string, from: string, to: string) {
+  return call<RenameResponse>("move_path", { projectRoot, from, to });
+}
+
+// Renames in place; `newName` is a file name, not a path.
+export async function renamePath(projectRoot: string, path: string, newName: string) {
+  return call<RenameResponse>("rename_path", { projectRoot, path, newName });
+}
+
+
+
+export interface ApplyPatchResponse {
+  applied: boolean;
+  hunks: HunkResult[];
+  error: string | null;
+}
+
+// Writes the file only if every hunk of the patch applies.
+export async function applyFilePatch(projectRoot: string, path: string, patch: string) {
+  return call<ApplyPatchResponse>("apply_file_patch", { projectRoot, path, patch });
+}
+
+export type MergeResolution = "current" | "proposed" | "both";
+
+export interface MergeConflict {
+  index: number;
+  start_line: number;
+  base: string;
+  current: string;
+  proposed: string;
+}
+
+export interface MergeResult {
+  merged: string;
+  conflicts: MergeConflict[];
+}
+
+export interface MergeFileEditRequest {
+  project_root: string;
+  path: string;
+  base: string;
+  proposed: string;
+  /** Defaults to the file on disk. */
+  current?: string;
+  resolutions?: MergeResolution[];
+}
+
+// Three-way merge of an AI edit with changes made since the AI read the file.
+export async function mergeFileEdit(req: MergeFileEditRequest) {
+  return call<MergeResult>("merge_file_edit", { req });
+}
+
+export type IndexStatus = "indexed" | "stale" | "not_indexed";
+
+export interface DocumentInfo {
+  word_count: number;
+  // Frontmatter title, or else the first heading
+  title: string | null;
+  tags: string[];
+  index_status: IndexStatus;
+}
+
+export interface TreeNode {
+  name: string;
+  // Relative to the project root; "" for the root itself
+  path: string;
+  is_dir: boolean;
+  size: number;
+  // Seconds since the epoch; for folders, the newest file inside
+  modified: number | null;
+  // Set for markdown files
+  document: DocumentInfo | null;
+  children: TreeNode[];
+}
+
+// Nested folders and files with metadata. `markdownOnly` leaves out other files.
+export async function getProjectTree(projectRoot: string, markdownOnly = false) {
+  return call<TreeNode>("get_project_tree", { projectRoot, markdownOnly });
+}
@@ -0,0 +1,83 @@
+import { call } from "./client";
+import type { TransactionSummary } from "./history";
+
+export type GitChange =
+  | "added"
+  | "modified"
+  | "deleted"
+  | "renamed"
+  | "type_change"
+  | "untracked"
+  | "conflicted";
+
+export interface GitFileStatus {
+  /** Relative to the project root. */
+  path: string;
+  staged: GitChange | null;
+  unstaged: GitChange | null;
+}
+
+export interface GitStatus {
+  branch: string | null;
+  head: string | null;
+  files: GitFileStatus[];
+}
+
+export interface GitCommit {
+  id: string;
+  short_id: string;
+  summary: string;
+  message: string;
+  author_name: string;
+  author_email: string;
+  time: number;
+}
+
+export interface GitDiffFile {
+  path: string;
+  change: GitChange;
+  insertions: number;
+  deletions: number;
+}
+
+export interface GitDiff {
+  patch: string;
+  files: GitDiffFile[];
+}
+
+// All git commands work on the repository containing the project root and
+// only report files inside the project.
+export async function gitStatus(projectRoot: string) {
+  return call<GitStatus>("git_status", { projectRoot });
+}
+
+export async function gitStage(projectRoot: string, paths: string[]) {
+  return call<void>("git_stage", { projectRoot, paths });
+}
+
+export async function gitUnstage(projectRoot: string, paths: string[]) {
+  return call<void>("git_unstage", { projectRoot, paths });
+}
+
+export async function gitCommit(projectRoot: string, message: string) {
+  return call<GitCommit>("git_commit", { projectRoot, message });
+}
+
+// Diff against HEAD for one file, or the whole project when path is omitted.
+export async function gitDiff(projectRoot: string, path?: string) {
+  return call<GitDiff>("git_diff", { projectRoot, path: path ?? null });
+}
+
+export async function gitLog(projectRoot: string, path?: string, limit?: number) {
+  return call<GitCommit[]>("git_log", { projectRoot, path: path ?? null, limit: limit ?? null });
+}
+
+// Null when the file did not exist in that revision.
+export async function gitShowFile(projectRoot: string, path: string, revision: string) {
+  return call<string | null>("git_show_file", { projectRoot, path, revision });
+}
+
+// Undoable through the edit history, like other edits.
+export async function gitCheckoutFile(projectRoot: string, path: string, revision: string) {
+  return call<TransactionSummary>("git_checkout_file", { projectRoot, path, revision });
+}
@@ -0,0 +1,113 @@
+import { call } from "./client";
+
+export type NodeKind = "file" | "heading" | "tag" | "entity";
+
+export type EdgeKind = "link" | "heading" | "tag" | "co_occurrence" | "defines";
+
+export interface GraphNode {
+  // File path, "heading:<path>#<slug>", "tag:#<tag>" or "entity:<name>"
+  id: string;
+  kind: NodeKind;
+  name: string;
+  // File the node belongs to; for an entity, the first file describing it
+  path: string | null;
+  line: number | null;
+  // Top-level folder, or "root", "_tags", "_entities"
+  group: string;
+  // Connections, not counting file-to-heading edges
+  degree: number;
+}
+
+export interface GraphEdge {
+  source: string;
+  target: string;
+  kind: EdgeKind;
+  weight: number;
+}
+
+export interface KnowledgeGraph {
+  nodes: GraphNode[];
+  edges: GraphEdge[];
+}
+
+export interface Neighbourhood {
+  nodes: Array<GraphNode & { distance: number }>;
+  edges: GraphEdge[];
+}
+
+// The graph as react-force-graph takes it; headings are folded into files.
+export interface LoreGraphNode {
+  id: string;
+  name: string;
+  group: string;
+  kind: NodeKind;
+  path: string | null;
+  val: number;
+}
+
+export interface LoreGraphLink {
+  source: string;
+  target: string;
+  type: EdgeKind;
+}
+
+export interface LoreGraph {
+  nodes: LoreGraphNode[];
+  links: LoreGraphLink[];
+}
+
+export async function getKnowledgeGraph(projectRoot: string) {
+  return call<KnowledgeGraph>("get_knowledge_graph", { projectRoot });
+}
+
+// The drawn graph plus its `hubLimit` (default 10) most connected nodes and
+// its orphans, from a single build of the graph.
+export interface LoreMap extends LoreGraph {
+  hubs: GraphNode[];
+  orphans: GraphNode[];
+}
+
+export async function getLoreGraph(projectRoot: string, hubLimit?: number) {
+  return call<LoreMap>("get_lore_graph", { projectRoot, hubLimit });
+}
+
+// `node` may be a node id, a file name or path, "File#Heading", "#tag" or an
+// entity name.
+export async function getGraphNeighbours(projectRoot: string, node: string, hops?: number) {
+  return call<Neighbourhood>("graph_neighbours", { projectRoot, node, hops });
+}
+
+// Null when the two are not connected.
+export async function getGraphShortestPath(projectRoot: string, from: string, to: string) {
+  return call<{ nodes: GraphNode[]; edges: GraphEdge[] } | null>("graph_shortest_path", { projectRoot, from, to });
+}
+
+export async function getGraphOrphans(projectRoot: string) {
+  return call<GraphNode[]>("graph_orphans", { projectRoot });
+}
+
+export async function getGraphHubs(projectRoot: string, limit?: number) {
+  return call<GraphNode[]>("graph_hubs", { projectRoot, limit });
+}
+
+// Node ids of each connected part, largest first.
+export async function getGraphComponents(projectRoot: string) {
+  return call<string[][]>("graph_components", { projectRoot });
+}
+
+export type GraphFormat = "graphml" | "dot" | "canvas";
+
+// Empty lists do not filter.
+export interface GraphFilter {
+  // Keep files in these folders or below them
+  folders?: string[];
+  // Keep files carrying at least one of these tags
+  tags?: string[];
+  // Keep only these kinds of connection
+  edge_kinds?: EdgeKind[];
+}
+
+// Returns the file contents; the caller picks where to save them.
+export async function exportGraph(projectRoot: string, format: GraphFormat, filter: GraphFilter = {}) {
+  return call<string>("export_graph", { req: { project_root: projectRoot, format, ...filter } });
+}
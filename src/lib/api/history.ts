@@ -0,0 +1,73 @@
+import { call } from "./client";
+
+export interface TransactionEdit {
+  path: string;
+  /** New file contents; null deletes the file. */
+  contents: string | null;
+}
+
+export interface ApplyTransactionRequest {
+  project_root: string;
+  label: string;
+  edits: TransactionEdit[];
+}
+
+export interface TransactionSummary {
+  id: number;
+  label: string;
+  created_at: number;
+  status: "applied" | "undone";
+  paths: string[];
+}
+
+// Writes all edits or none, recorded as one undoable step in .codexlotus/history.
+export async function applyEditTransaction(req: ApplyTransactionRequest) {
+  return call<TransactionSummary>("apply_edit_transaction", { req });
+}
+
+export async function undoEditTransaction(projectRoot: string) {
+  return call<TransactionSummary | null>("undo_edit_transaction", { projectRoot });
+}
+
+export async function redoEditTransaction(projectRoot: string) {
+  return call<TransactionSummary | null>("redo_edit_transaction", { projectRoot });
+}
+
+export async function listEditTransactions(projectRoot: string) {
+  return call<TransactionSummary[]>("list_edit_transactions", { projectRoot });
+}
+
+export type SnapshotSource = "external" | "save" | "ai_edit" | "undo" | "redo" | "restore" | "replace" | "rename";
+
+export interface FileSnapshot {
+  id: number;
+  path: string;
+  content_hash: string;
+  size: number;
+  source: SnapshotSource;
+  label: string | null;
+  created_at: number;
+}
+
+export interface FileVersionDiff {
+  original: string;
+  modified: string;
+  unified: string;
+  insertions: number;
+  deletions: number;
+}
+
+// Saved versions of one file from .codexlotus/snapshots, newest first.
+export async function listFileVersions(projectRoot: string, path: string) {
+  return call<FileSnapshot[]>("list_file_versions", { projectRoot, path });
+}
+
+// Without toId the version is compared with the file on disk.
+export async function diffFileVersions(projectRoot: string, path: string, fromId: number, toId?: number) {
+  return call<FileVersionDiff>("diff_file_versions", { projectRoot, path, fromId, toId: toId ?? null });
+}
+
+// Restores through the edit journal, so it can be undone.
+export async function restoreFileVersion(projectRoot: string, path: string, snapshotId: number) {
+  return call<TransactionSummary>("restore_file_version", { projectRoot, path, snapshotId });
+}
@@ -0,0 +1,62 @@
+import { call } from "./client";
+
+export type LinkKind = "wiki" | "markdown";
+
+export type LinkProblem = "missing_file" | "missing_heading";
+
+export interface BrokenLink {
+  path: string;
+  line: number;
+  // 1-based, in characters
+  column: number;
+  kind: LinkKind;
+  // Target and anchor as written, e.g. "Combat#Flanking"
+  link: string;
+  problem: LinkProblem;
+  // Replacement targets in the link's own style, best match first
+  suggestions: string[];
+}
+
+export interface LinkReport {
+  broken: BrokenLink[];
+  files_checked: number;
+  links_checked: number;
+}
+
+// Checks wiki links, markdown links, images and heading anchors in every
+// markdown file of the project.
+export async function checkLinks(projectRoot: string) {
+  return call<LinkReport>("check_links", { projectRoot });
+}
+
+// A link read from the link index, which is refreshed from disk before
+// every query.
+export interface IndexedLink {
+  source: string;
+  line: number;
+  // 1-based, in characters
+  column: number;
+  kind: LinkKind;
+  // Target as written, without the anchor
+  target: string;
+  anchor: string | null;
+  // The line the link is on, trimmed
+  context: string;
+  // The file or folder the link points to, or null if it does not exist
+  resolved: string | null;
+}
+
+// Links pointing at `path`, or only at one of its headings (by text or slug).
+export async function getBacklinks(projectRoot: string, path: string, heading?: string) {
+  return call<IndexedLink[]>("get_backlinks", { projectRoot, path, heading });
+}
+
+// Links in `path`, in document order.
+export async function getOutgoingLinks(projectRoot: string, path: string) {
+  return call<IndexedLink[]>("get_outgoing_links", { projectRoot, path });
+}
+
+// Links anywhere in the project whose target does not exist.
+export async function getUnresolvedLinks(projectRoot: string) {
+  return call<IndexedLink[]>("get_unresolved_links", { projectRoot });
+}
@@ -0,0 +1,84 @@
+import { call } from "./client";
+import type { TransactionSummary } from "./history";
+
+export interface SearchOptions {
+  query: string;
+  /** Treat the query as a regular expression. */
+  regex?: boolean;
+  case_sensitive?: boolean;
+  whole_word?: boolean;
+  context_lines?: number;
+  max_results?: number;
+}
+
+export interface SearchProjectRequest extends SearchOptions {
+  project_root: string;
+}
+
+export interface SearchMatch {
+  path: string;
+  /** 1-based. */
+  line: number;
+  /** 1-based, in characters. */
+  column: number;
+  length: number;
+  line_text: string;
+  context_before: string[];
+  context_after: string[];
+}
+
+export interface SearchResults {
+  matches: SearchMatch[];
+  files_searched: number;
+  truncated: boolean;
+}
+
+// Text search over the project files; unlike ragQuery it needs no index or API key.
+export async function searchProject(req: SearchProjectRequest) {
+  return call<SearchResults>("search_project", { req });
+}
+
+export interface ReplaceOptions extends SearchOptions {
+  /** In regex mode $1, ${name} and $$ refer to capture groups and a literal $. */
+  replacement: string;
+}
+
+export interface ReplacementChange {
+  /** Position among the file's matches; pass back in ReplaceSelection.indices. */
+  index: number;
+  line: number;
+  column: number;
+  matched: string;
+  replacement: string;
+  line_text: string;
+  new_line_text: string;
+}
+
+export interface FileReplacePreview {
+  path: string;
+  version: string;
+  changes: ReplacementChange[];
+}
+
+export interface ReplacePreview {
+  files: FileReplacePreview[];
+  total_changes: number;
+  truncated: boolean;
+}
+
+export interface ReplaceSelection {
+  path: string;
+  /** The version from the preview; files changed since are refused. */
+  version: string;
+  indices: number[];
+}
+
+// Dry run: lists every change without writing anything.
+export async function previewReplace(projectRoot: string, options: ReplaceOptions) {
+  return call<ReplacePreview>("preview_replace", { req: { project_root: projectRoot, ...options } });
+}
+
+// Applies the selected changes as one undoable step.
+export async function applyReplace(projectRoot: string, options: ReplaceOptions, selections: ReplaceSelection[]) {
+  return call<TransactionSummary>("apply_replace", { req: { project_root: projectRoot, ...options, selections } });
+}
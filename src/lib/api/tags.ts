@@ -0,0 +1,59 @@
+import { call } from "./client";
+
+export interface MentionTarget {
+  // File the mention should link to
+  path: string;
+  // Title, file name or codex entity name
+  name: string;
+  // Tag to suggest, with "#"
+  tag: string;
+  // Wiki link target, e.g. "wizard-guild"
+  link: string;
+}
+
+export interface Mention {
+  line: number;
+  // 1-based, in characters
+  column: number;
+  // In characters
+  length: number;
+  // As written, e.g. "Wizard's Guild"
+  text: string;
+  target: MentionTarget;
+}
+
+export interface FileMentions {
+  path: string;
+  current_tags: string[];
+  suggested_tags: string[];
+  mentions: Mention[];
+}
+
+// Finds mentions of project files (by name, title or alias) and codex
+// entities, in one file or in every markdown file when `path` is omitted.
+export async function detectMentions(projectRoot: string, path?: string) {
+  return call<FileMentions[]>("detect_mentions", { projectRoot, path });
+}
+
+export interface SemanticTag {
+  // With "#"
+  tag: string;
+  // 0 to 1; 1 when the file is as close to the tag as the files carrying it
+  confidence: number;
+  // Cosine similarity of the file's closest chunk to the tag
+  similarity: number;
+  // Files carrying the tag that are most like this one
+  examples: string[];
+}
+
+export interface FileSemanticTags {
+  path: string;
+  current_tags: string[];
+  suggestions: SemanticTag[];
+}
+
+// Suggests tags a file is about without naming them, from the embeddings in
+// the project index. Only indexed files get suggestions.
+export async function suggestSemanticTags(projectRoot: string, path?: string) {
+  return call<FileSemanticTags[]>("suggest_semantic_tags", { projectRoot, path });
+}
@@ -0,0 +1,32 @@
+import { call } from "./client";
+
+export interface TrashEntry {
+  id: number;
+  // Where the item was, relative to the project root
+  original_path: string;
+  is_dir: boolean;
+  file_count: number;
+  size: number;
+  deleted_at: number;
+}
+
+// Moves a file or folder into the project trash (.codexlotus/trash).
+export async function deletePath(projectRoot: string, path: string) {
+  return call<TrashEntry>("delete_path", { projectRoot, path });
+}
+
+// Most recently deleted first.
+export async function listTrash(projectRoot: string) {
+  return call<TrashEntry[]>("list_trash", { projectRoot });
+}
+
+// Returns the path the item was restored to. Pass `destination` when the
+// original path is taken.
+export async function restoreFromTrash(projectRoot: string, id: number, destination?: string) {
+  return call<string>("restore_from_trash", { projectRoot, id, destination });
+}
+
+// Permanently deletes the given items, or everything when `ids` is omitted.
+export async function purgeTrash(projectRoot: string, ids?: number[]) {
+  return call<number>("purge_trash", { projectRoot, ids });
+}